@@ -0,0 +1,57 @@
+//! スレッド数ごとにExoticBSEngineのスループットを測定する。
+//! 各ワーカーがgathererを持ち最後に結合するため、スレッド数に応じてスループットが伸びることを確認できる。
+use rust_design_pattern_derivative_pricing::chapter4::parameters::ParametersConstant;
+use rust_design_pattern_derivative_pricing::chapter4::payoff3::{Payoff, PayoffCall};
+use rust_design_pattern_derivative_pricing::chapter5::mc_statistics::{
+    StatisticsMC, StatisticsMean,
+};
+use rust_design_pattern_derivative_pricing::chapter6::anti_thetic::AntiThetic;
+use rust_design_pattern_derivative_pricing::chapter6::park_miller::RandomParkMiller;
+use rust_design_pattern_derivative_pricing::chapter7::exotic_bs_engine::ExoticBSEngine;
+use rust_design_pattern_derivative_pricing::chapter7::exotic_engine::{
+    ExoticEngine, ExoticEngineData,
+};
+use rust_design_pattern_derivative_pricing::chapter7::path_dependent_asian::PathDependentAsian;
+use std::time::Instant;
+
+pub fn main() {
+    let expiry = 1.0;
+    let number_of_dates = 100;
+    let number_of_paths = 200000;
+    let times: Vec<f64> = (0..number_of_dates)
+        .map(|i| (i as f64 + 1.0) * expiry / number_of_dates as f64)
+        .collect();
+    let r_param = ParametersConstant::new(0.05);
+    let d_param = ParametersConstant::new(0.0);
+    let vol_param = ParametersConstant::new(0.2);
+    let the_payoff = PayoffCall::new(100.0);
+    let the_option = PathDependentAsian::new(times.clone(), expiry, &the_payoff);
+    let exotic_engine_data = ExoticEngineData::new(&the_option, &r_param);
+    let max_threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+
+    println!("threads, seconds, paths per second, price");
+    let mut number_of_threads = 1;
+    while number_of_threads <= max_threads {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(number_of_threads)
+            .build()
+            .unwrap();
+        let generator = AntiThetic::new(RandomParkMiller::new(number_of_dates, 1));
+        let mut the_engine =
+            ExoticBSEngine::new(&times, &r_param, d_param, vol_param, generator, 100.0);
+        let mut gatherer = StatisticsMean::default();
+        let start = Instant::now();
+        pool.install(|| {
+            the_engine.do_simulation(&exotic_engine_data, &mut gatherer, number_of_paths)
+        });
+        let seconds = start.elapsed().as_secs_f64();
+        println!(
+            "{}, {}, {}, {}",
+            number_of_threads,
+            seconds,
+            number_of_paths as f64 / seconds,
+            gatherer.get_results_so_far()[0][0]
+        );
+        number_of_threads *= 2;
+    }
+}
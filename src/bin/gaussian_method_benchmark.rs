@@ -0,0 +1,64 @@
+//! 一様乱数から正規乱数への変換方法ごとに、正規乱数の生成速度を測定する。
+//! 棄却法ではパス単位のskipのために予備を含む固定の数の一様乱数を毎パス割り当てるので、
+//! 割り当てた一様乱数を全て生成する場合と、使う分だけ生成して残りをskipする場合(`new_on_demand`)の両方を測定する。
+//! 一様乱数の生成の費用によって順位が変わりうるので、Park-MillerとPCG32の二つの生成器で測定する。
+use rust_design_pattern_derivative_pricing::chapter6::gaussian_transform::{
+    GaussianGenerator, GaussianMethod,
+};
+use rust_design_pattern_derivative_pricing::chapter6::park_miller::RandomParkMiller;
+use rust_design_pattern_derivative_pricing::chapter6::pcg::RandomPcg32;
+use rust_design_pattern_derivative_pricing::chapter6::random2::Random;
+use std::time::Instant;
+
+/// Prints the time to generate `number_of_paths` paths of gaussians by each method from `generator`.
+fn measure(name: &str, generator: impl Random, number_of_paths: usize) {
+    let dimensionality = generator.get_dimensionality();
+    let mut variates = vec![0.0; dimensionality];
+    for (method, on_demand) in [
+        (GaussianMethod::InverseCumulative, false),
+        (GaussianMethod::BoxMuller, false),
+        (GaussianMethod::MarsagliaPolar, false),
+        (GaussianMethod::MarsagliaPolar, true),
+        (GaussianMethod::Ziggurat, false),
+        (GaussianMethod::Ziggurat, true),
+    ] {
+        let mut generator = if on_demand {
+            GaussianGenerator::new_on_demand(generator.clone(), method)
+        } else {
+            GaussianGenerator::new(generator.clone(), method)
+        };
+        let mut sum = 0.0;
+        let start = Instant::now();
+        for _ in 0..number_of_paths {
+            generator.get_gaussians(&mut variates);
+            sum += variates.iter().sum::<f64>();
+        }
+        let seconds = start.elapsed().as_secs_f64();
+        let number_of_gaussians = (number_of_paths * dimensionality) as f64;
+        println!(
+            "{}, {:?}, {}, {}, {}, {}",
+            name,
+            method,
+            on_demand,
+            seconds,
+            number_of_gaussians / seconds,
+            sum / number_of_gaussians
+        );
+    }
+}
+
+pub fn main() {
+    let dimensionality = 100;
+    let number_of_paths = 200000;
+    println!("generator, method, on demand, seconds, gaussians per second, mean");
+    measure(
+        "ParkMiller",
+        RandomParkMiller::new(dimensionality, 1),
+        number_of_paths,
+    );
+    measure(
+        "Pcg32",
+        RandomPcg32::new(dimensionality, 1, 0),
+        number_of_paths,
+    );
+}
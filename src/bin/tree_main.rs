@@ -0,0 +1,40 @@
+use rust_design_pattern_derivative_pricing::chapter4::parameters::ParametersConstant;
+use rust_design_pattern_derivative_pricing::chapter4::payoff3::{Payoff, PayoffCall};
+use rust_design_pattern_derivative_pricing::chapter8::binomial_tree::SimpleBinomialTree;
+use rust_design_pattern_derivative_pricing::chapter8::tree_american::TreeAmerican;
+use rust_design_pattern_derivative_pricing::chapter8::tree_european::TreeEuropean;
+
+pub fn main() {
+    println!("\nEnter expiry\n");
+    let expiry: f64 = text_io::read!();
+
+    println!("\nEnter strike\n");
+    let strike = text_io::read!();
+
+    println!("\nEnter spot\n");
+    let spot = text_io::read!();
+
+    println!("\nEnter vol\n");
+    let vol = text_io::read!();
+
+    println!("\nEnter r\n");
+    let r: f64 = text_io::read!();
+
+    println!("\nEnter d\n");
+    let d: f64 = text_io::read!();
+
+    println!("\nNumber of steps\n");
+    let steps = text_io::read!();
+
+    let the_payoff = PayoffCall::new(strike);
+    let r_param: ParametersConstant = r.into();
+    let d_param: ParametersConstant = d.into();
+
+    let european_option = TreeEuropean::new(expiry, &the_payoff);
+    let american_option = TreeAmerican::new(expiry, &the_payoff);
+    let mut the_tree = SimpleBinomialTree::new(spot, &r_param, &d_param, vol, steps, expiry);
+
+    let euro = the_tree.get_the_price(&european_option);
+    let american = the_tree.get_the_price(&american_option);
+    println!("euro price {} amer price {}\n", euro, american);
+}
@@ -1,5 +1,11 @@
 pub mod parameters;
+pub mod parameters_bumped;
+pub mod parameters_error;
+pub mod parameters_parametric;
+#[cfg(test)]
+pub(crate) mod parameters_test_support;
 pub mod payoff3;
+pub mod rate_instruments;
 pub mod simple_mc3;
 pub mod simple_mc4;
 pub mod simple_mc5;
@@ -7,3 +13,5 @@ pub mod simple_mc6;
 pub mod vanilla1;
 pub mod vanilla2;
 pub mod vanilla3;
+pub mod yield_curve;
+pub mod yield_curve_error;
@@ -1,19 +1,137 @@
+use crate::chapter4::parameters_error::ParametersError;
 use std::convert::From;
 
+/// The absolute tolerance of the default quadrature.
+const QUADRATURE_TOLERANCE: f64 = 1e-12;
+/// The maximum depth of the bisections of the default quadrature.
+const QUADRATURE_MAX_DEPTH: u32 = 50;
+/// The number of panels the interval is divided into before the adaptive quadrature.
+const QUADRATURE_PANELS: usize = 8;
+/// The relative step of the central difference of `instantaneous_forward`.
+const DIFFERENCE_STEP: f64 = 1e-6;
+
+/// A parameter depending on time such as an interest rate or a volatility.
+///
+/// Only `value_at` is required.
+/// The integrals fall back to adaptive Simpson quadrature of `value_at`,
+/// which should be overridden by types with closed forms.
 pub trait Parameters: Send + Sync {
+    /// Returns the value at time `x`.
     fn value_at(&self, x: f64) -> f64;
-    fn integral(&self, time1: f64, time2: f64) -> f64;
-    fn integral_square(&self, time1: f64, time2: f64) -> f64;
+    /// Returns the integral from `time1` to `time2`, which is negative if `time2 < time1`.
+    fn integral(&self, time1: f64, time2: f64) -> f64 {
+        integrate(&|x| self.value_at(x), time1, time2)
+    }
+    /// Returns the integral of the square from `time1` to `time2`, which is negative if `time2 < time1`.
+    fn integral_square(&self, time1: f64, time2: f64) -> f64 {
+        integrate(&|x| self.value_at(x).powi(2), time1, time2)
+    }
+    /// Returns the mean over the interval between `time1` and `time2`, which is `value_at(time1)` if they are equal.
     fn mean(&self, time1: f64, time2: f64) -> f64 {
-        let total = self.integral(time1, time2);
-        total / (time2 - time1)
+        if time1 == time2 {
+            return self.value_at(time1);
+        }
+        self.integral(time1, time2) / (time2 - time1)
+    }
+    /// Returns the mean of the square over the interval between `time1` and `time2`,
+    /// which is the square of `value_at(time1)` if they are equal.
+    fn mean_square(&self, time1: f64, time2: f64) -> f64 {
+        if time1 == time2 {
+            return self.value_at(time1).powi(2);
+        }
+        self.integral_square(time1, time2) / (time2 - time1)
     }
+    /// Returns the root of `mean_square`, such as the volatility of the Black-Scholes formula with the same variance.
     fn root_mean_square(&self, time1: f64, time2: f64) -> f64 {
-        let total = self.integral_square(time1, time2);
-        total / (time2 - time1)
+        self.mean_square(time1, time2).sqrt()
+    }
+    /// Returns the derivative of the integral at `time` by the central difference of `integral`.
+    ///
+    /// This agrees with `value_at` where the parameter is continuous,
+    /// and is the average of the left and right limits at a jump.
+    fn instantaneous_forward(&self, time: f64) -> f64 {
+        let step = DIFFERENCE_STEP * time.abs().max(1.0);
+        self.mean(time - step, time + step)
     }
 }
 
+/// Forwards to the referenced parameter, so that decorators can wrap a reference without cloning.
+impl<P: Parameters + ?Sized> Parameters for &P {
+    fn value_at(&self, x: f64) -> f64 {
+        (**self).value_at(x)
+    }
+    fn integral(&self, time1: f64, time2: f64) -> f64 {
+        (**self).integral(time1, time2)
+    }
+    fn integral_square(&self, time1: f64, time2: f64) -> f64 {
+        (**self).integral_square(time1, time2)
+    }
+    fn mean(&self, time1: f64, time2: f64) -> f64 {
+        (**self).mean(time1, time2)
+    }
+    fn mean_square(&self, time1: f64, time2: f64) -> f64 {
+        (**self).mean_square(time1, time2)
+    }
+    fn root_mean_square(&self, time1: f64, time2: f64) -> f64 {
+        (**self).root_mean_square(time1, time2)
+    }
+    fn instantaneous_forward(&self, time: f64) -> f64 {
+        (**self).instantaneous_forward(time)
+    }
+}
+
+/// Integrates `f` from `a` to `b` by adaptive Simpson quadrature.
+fn integrate(f: &impl Fn(f64) -> f64, a: f64, b: f64) -> f64 {
+    if a == b {
+        return 0.0;
+    }
+    let width = (b - a) / QUADRATURE_PANELS as f64;
+    let tolerance = QUADRATURE_TOLERANCE / QUADRATURE_PANELS as f64;
+    (0..QUADRATURE_PANELS)
+        .map(|i| {
+            let (left, right) = (a + i as f64 * width, a + (i + 1) as f64 * width);
+            let middle = 0.5 * (left + right);
+            let (f_left, f_middle, f_right) = (f(left), f(middle), f(right));
+            let whole = (right - left) / 6.0 * (f_left + 4.0 * f_middle + f_right);
+            let ends = [(left, f_left), (middle, f_middle), (right, f_right)];
+            simpson_step(f, ends, whole, tolerance, QUADRATURE_MAX_DEPTH)
+        })
+        .sum()
+}
+
+/// Refines the Simpson estimate `whole` on the interval of `ends` = [left, middle, right] by bisection.
+fn simpson_step(
+    f: &impl Fn(f64) -> f64,
+    ends: [(f64, f64); 3],
+    whole: f64,
+    tolerance: f64,
+    depth: u32,
+) -> f64 {
+    let [(a, fa), (m, fm), (b, fb)] = ends;
+    let (left_middle, right_middle) = (0.5 * (a + m), 0.5 * (m + b));
+    let (f_left_middle, f_right_middle) = (f(left_middle), f(right_middle));
+    let left = (m - a) / 6.0 * (fa + 4.0 * f_left_middle + fm);
+    let right = (b - m) / 6.0 * (fm + 4.0 * f_right_middle + fb);
+    let delta = left + right - whole;
+    if depth == 0 || delta.abs() <= 15.0 * tolerance {
+        // Richardson extrapolation of the two estimates.
+        return left + right + delta / 15.0;
+    }
+    simpson_step(
+        f,
+        [(a, fa), (left_middle, f_left_middle), (m, fm)],
+        left,
+        0.5 * tolerance,
+        depth - 1,
+    ) + simpson_step(
+        f,
+        [(m, fm), (right_middle, f_right_middle), (b, fb)],
+        right,
+        0.5 * tolerance,
+        depth - 1,
+    )
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct ParametersConstant {
     constant: f64,
@@ -48,9 +166,273 @@ impl From<f64> for ParametersConstant {
     }
 }
 
+/// Checks that `pillars` are non-empty, strictly increasing in time and finite.
+/// The first time must be positive if `positive_times`, and non-negative otherwise.
+fn validate_pillars(pillars: &[(f64, f64)], positive_times: bool) -> Result<(), ParametersError> {
+    if pillars.is_empty() {
+        return Err(ParametersError::NoPillars);
+    }
+    for (index, &(time, value)) in pillars.iter().enumerate() {
+        if !time.is_finite() || !value.is_finite() {
+            return Err(ParametersError::NotFinite { index, time, value });
+        }
+    }
+    let first_time = pillars[0].0;
+    if positive_times && first_time <= 0.0 {
+        return Err(ParametersError::NonPositiveTime {
+            index: 0,
+            time: first_time,
+        });
+    }
+    if !positive_times && first_time < 0.0 {
+        return Err(ParametersError::NegativeTime {
+            index: 0,
+            time: first_time,
+        });
+    }
+    for (index, pair) in pillars.windows(2).enumerate() {
+        let (previous, time) = (pair[0].0, pair[1].0);
+        if time <= previous {
+            return Err(ParametersError::TimesNotIncreasing {
+                index: index + 1,
+                previous,
+                time,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Checks that the values of `pillars` are positive, as required for volatilities.
+fn validate_positive_values(pillars: &[(f64, f64)]) -> Result<(), ParametersError> {
+    match pillars.iter().position(|&(_, value)| value <= 0.0) {
+        Some(index) => Err(ParametersError::NonPositiveValue {
+            index,
+            value: pillars[index].1,
+        }),
+        None => Ok(()),
+    }
+}
+
+/// The parameter taking the value of a pillar on the interval from the previous pillar time to its time.
+///
+/// The first value is extrapolated to times before the first pillar, and the last value after the last pillar.
+#[derive(Debug, Clone)]
+pub struct ParametersPiecewiseConstant {
+    times: Vec<f64>,
+    values: Vec<f64>,
+    values_square: Vec<f64>,
+    /// The integrals from 0 to each time
+    integrals: Vec<f64>,
+    /// The integrals of the square from 0 to each time
+    integrals_square: Vec<f64>,
+}
+
+impl ParametersPiecewiseConstant {
+    /// Constructor.
+    ///
+    /// # Arguments
+    ///
+    /// * `pillars` - The pairs of the end time of an interval and the value on the interval
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `pillars` is empty, the first time is not positive,
+    /// the times are not strictly increasing or a time or a value is not finite.
+    pub fn new(pillars: &[(f64, f64)]) -> Result<Self, ParametersError> {
+        validate_pillars(pillars, true)?;
+        let (times, values): (Vec<f64>, Vec<f64>) = pillars.iter().copied().unzip();
+        let values_square: Vec<f64> = values.iter().map(|value| value * value).collect();
+        let cumulate = |values: &[f64]| -> Vec<f64> {
+            let mut previous = 0.0;
+            times
+                .iter()
+                .zip(values)
+                .scan(0.0, |total, (&time, value)| {
+                    *total += (time - previous) * value;
+                    previous = time;
+                    Some(*total)
+                })
+                .collect()
+        };
+        let integrals = cumulate(&values);
+        let integrals_square = cumulate(&values_square);
+        Ok(Self {
+            times,
+            values,
+            values_square,
+            integrals,
+            integrals_square,
+        })
+    }
+
+    /// Constructor for a parameter which must be positive such as a volatility.
+    ///
+    /// # Arguments
+    ///
+    /// * `pillars` - The pairs of the end time of an interval and the value on the interval
+    ///
+    /// # Errors
+    ///
+    /// Returns an error in the cases of `new` or if a value is not positive.
+    pub fn new_positive(pillars: &[(f64, f64)]) -> Result<Self, ParametersError> {
+        let parameters = Self::new(pillars)?;
+        validate_positive_values(pillars)?;
+        Ok(parameters)
+    }
+
+    /// Returns the integral of `values` from 0 to `time`, where `integrals` are those up to each pillar.
+    fn primitive(&self, time: f64, values: &[f64], integrals: &[f64]) -> f64 {
+        let k = self.times.partition_point(|&t| t < time);
+        if k == 0 {
+            values[0] * time
+        } else {
+            let value = values[k.min(values.len() - 1)];
+            integrals[k - 1] + value * (time - self.times[k - 1])
+        }
+    }
+}
+
+impl Parameters for ParametersPiecewiseConstant {
+    fn value_at(&self, x: f64) -> f64 {
+        let k = self.times.partition_point(|&t| t < x);
+        self.values[k.min(self.values.len() - 1)]
+    }
+
+    fn integral(&self, time1: f64, time2: f64) -> f64 {
+        self.primitive(time2, &self.values, &self.integrals)
+            - self.primitive(time1, &self.values, &self.integrals)
+    }
+
+    fn integral_square(&self, time1: f64, time2: f64) -> f64 {
+        self.primitive(time2, &self.values_square, &self.integrals_square)
+            - self.primitive(time1, &self.values_square, &self.integrals_square)
+    }
+}
+
+/// The parameter interpolated linearly between pillars.
+///
+/// The first value is extrapolated to times before the first pillar, and the last value after the last pillar.
+#[derive(Debug, Clone)]
+pub struct ParametersPiecewiseLinear {
+    times: Vec<f64>,
+    values: Vec<f64>,
+    /// The integrals from 0 to each time
+    integrals: Vec<f64>,
+    /// The integrals of the square from 0 to each time
+    integrals_square: Vec<f64>,
+}
+
+impl ParametersPiecewiseLinear {
+    /// Constructor.
+    ///
+    /// # Arguments
+    ///
+    /// * `pillars` - The pairs of a time and the value at the time
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `pillars` is empty, the first time is negative,
+    /// the times are not strictly increasing or a time or a value is not finite.
+    pub fn new(pillars: &[(f64, f64)]) -> Result<Self, ParametersError> {
+        validate_pillars(pillars, false)?;
+        let (times, values): (Vec<f64>, Vec<f64>) = pillars.iter().copied().unzip();
+        let mut integrals = vec![times[0] * values[0]];
+        let mut integrals_square = vec![times[0] * values[0] * values[0]];
+        for k in 1..times.len() {
+            let length = times[k] - times[k - 1];
+            let (p, q) = (values[k - 1], values[k]);
+            integrals.push(integrals[k - 1] + Self::segment_integral(length, p, q));
+            integrals_square
+                .push(integrals_square[k - 1] + Self::segment_integral_square(length, p, q));
+        }
+        Ok(Self {
+            times,
+            values,
+            integrals,
+            integrals_square,
+        })
+    }
+
+    /// Constructor for a parameter which must be positive such as a volatility.
+    ///
+    /// # Arguments
+    ///
+    /// * `pillars` - The pairs of a time and the value at the time
+    ///
+    /// # Errors
+    ///
+    /// Returns an error in the cases of `new` or if a value is not positive.
+    pub fn new_positive(pillars: &[(f64, f64)]) -> Result<Self, ParametersError> {
+        let parameters = Self::new(pillars)?;
+        validate_positive_values(pillars)?;
+        Ok(parameters)
+    }
+
+    /// Returns the integral of the linear function from `p` to `q` over an interval of `length`.
+    fn segment_integral(length: f64, p: f64, q: f64) -> f64 {
+        0.5 * length * (p + q)
+    }
+
+    /// Returns the integral of the square of the linear function from `p` to `q` over an interval of `length`.
+    fn segment_integral_square(length: f64, p: f64, q: f64) -> f64 {
+        length * (p * p + p * q + q * q) / 3.0
+    }
+
+    /// Returns the integral from 0 to `time` of the value, or of its square if `square`.
+    fn primitive(&self, time: f64, square: bool) -> f64 {
+        let (segment, integrals): (fn(f64, f64, f64) -> f64, _) = if square {
+            (Self::segment_integral_square, &self.integrals_square)
+        } else {
+            (Self::segment_integral, &self.integrals)
+        };
+        let n = self.times.len();
+        let k = self.times.partition_point(|&t| t < time);
+        if k == 0 {
+            segment(time, self.values[0], self.values[0])
+        } else if k == n {
+            let last = self.values[n - 1];
+            integrals[n - 1] + segment(time - self.times[n - 1], last, last)
+        } else {
+            integrals[k - 1]
+                + segment(
+                    time - self.times[k - 1],
+                    self.values[k - 1],
+                    self.value_at(time),
+                )
+        }
+    }
+}
+
+impl Parameters for ParametersPiecewiseLinear {
+    fn value_at(&self, x: f64) -> f64 {
+        let n = self.times.len();
+        let k = self.times.partition_point(|&t| t < x);
+        if k == 0 {
+            self.values[0]
+        } else if k == n {
+            self.values[n - 1]
+        } else {
+            let weight = (x - self.times[k - 1]) / (self.times[k] - self.times[k - 1]);
+            self.values[k - 1] + weight * (self.values[k] - self.values[k - 1])
+        }
+    }
+
+    fn integral(&self, time1: f64, time2: f64) -> f64 {
+        self.primitive(time2, false) - self.primitive(time1, false)
+    }
+
+    fn integral_square(&self, time1: f64, time2: f64) -> f64 {
+        self.primitive(time2, true) - self.primitive(time1, true)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::chapter4::parameters_test_support::assert_integrals_match_midpoint;
+    use crate::chapter9::black_scholes_formulas::black_scholes_call;
+    use approx::assert_relative_eq;
 
     #[test]
     fn test_value_at() {
@@ -82,8 +464,54 @@ mod tests {
     #[test]
     fn test_root_mean_square() {
         let params = ParametersConstant::new(2.0);
-        assert_eq!(params.root_mean_square(0.0, 1.0), 4.0);
-        assert_eq!(params.root_mean_square(0.0, 2.0), 4.0);
+        assert_eq!(params.mean_square(0.0, 1.0), 4.0);
+        assert_eq!(params.root_mean_square(0.0, 1.0), 2.0);
+        assert_eq!(params.root_mean_square(0.0, 2.0), 2.0);
+    }
+
+    #[test]
+    fn test_equal_times() {
+        let params =
+            ParametersPiecewiseConstant::new(&[(1.0, 0.1), (2.0, 0.2), (4.0, 0.3)]).unwrap();
+        assert_eq!(params.mean(1.5, 1.5), 0.2);
+        assert_relative_eq!(params.mean_square(1.5, 1.5), 0.04);
+        assert_relative_eq!(params.root_mean_square(1.5, 1.5), 0.2);
+        assert_relative_eq!(params.mean(3.0, 0.5), 0.55 / 2.5);
+    }
+
+    #[test]
+    fn test_instantaneous_forward() {
+        let params = ParametersPiecewiseLinear::new(&[(0.0, 0.1), (1.0, 0.3), (2.0, 0.2)]).unwrap();
+        assert_relative_eq!(params.instantaneous_forward(0.5), 0.2, epsilon = 1e-9);
+        let params = ParametersPiecewiseConstant::new(&[(1.0, 0.1), (2.0, 0.2)]).unwrap();
+        // The average of the limits at the jump.
+        assert_relative_eq!(params.instantaneous_forward(1.0), 0.15, epsilon = 1e-9);
+    }
+
+    /// A parameter with only `value_at`, whose integrals fall back to the quadrature.
+    struct ParametersSquareRoot;
+
+    impl Parameters for ParametersSquareRoot {
+        fn value_at(&self, x: f64) -> f64 {
+            x.sqrt()
+        }
+    }
+
+    #[test]
+    fn test_quadrature_fallback() {
+        let params = ParametersSquareRoot;
+        assert_relative_eq!(params.integral(0.0, 4.0), 16.0 / 3.0, epsilon = 1e-10);
+        assert_relative_eq!(params.integral(4.0, 1.0), -14.0 / 3.0, epsilon = 1e-10);
+        assert_relative_eq!(params.integral_square(1.0, 3.0), 4.0, epsilon = 1e-12);
+        assert_relative_eq!(params.root_mean_square(0.0, 2.0), 1.0, epsilon = 1e-12);
+        assert_eq!(params.integral(2.0, 2.0), 0.0);
+        // A jump of a piecewise constant parameter.
+        let piecewise = ParametersPiecewiseConstant::new(&[(1.0, 0.1), (2.0, 0.2)]).unwrap();
+        assert_relative_eq!(
+            integrate(&|x| piecewise.value_at(x), 0.3, 1.7),
+            piecewise.integral(0.3, 1.7),
+            epsilon = 1e-12
+        );
     }
 
     #[test]
@@ -92,4 +520,112 @@ mod tests {
         assert_eq!(params.constant, 2.0);
         assert_eq!(params.constant_square, 4.0);
     }
+
+    #[test]
+    fn test_piecewise_constant() {
+        let params =
+            ParametersPiecewiseConstant::new(&[(1.0, 0.1), (2.0, 0.2), (4.0, 0.3)]).unwrap();
+        assert_eq!(params.value_at(-1.0), 0.1);
+        assert_eq!(params.value_at(1.0), 0.1);
+        assert_eq!(params.value_at(1.5), 0.2);
+        assert_eq!(params.value_at(5.0), 0.3);
+        assert_relative_eq!(params.integral(0.5, 3.0), 0.05 + 0.2 + 0.3);
+        assert_relative_eq!(params.integral(3.0, 0.5), -0.55);
+        assert_relative_eq!(params.integral(0.0, 6.0), 0.1 + 0.2 + 0.6 + 0.6);
+        assert_relative_eq!(params.integral_square(0.5, 3.0), 0.005 + 0.04 + 0.09);
+        assert_relative_eq!(params.integral_square(-1.0, 0.5), 0.015);
+    }
+
+    #[test]
+    fn test_piecewise_linear() {
+        let params = ParametersPiecewiseLinear::new(&[(0.5, 0.1), (1.0, 0.3), (2.0, 0.2)]).unwrap();
+        assert_eq!(params.value_at(0.0), 0.1);
+        assert_relative_eq!(params.value_at(0.75), 0.2);
+        assert_relative_eq!(params.value_at(1.5), 0.25);
+        assert_eq!(params.value_at(3.0), 0.2);
+        assert_relative_eq!(params.integral(0.0, 2.0), 0.05 + 0.1 + 0.25);
+        assert_relative_eq!(params.integral_square(0.5, 1.0), 0.5 * 0.13 / 3.0);
+        // Against the midpoint rule on intervals crossing the pillars.
+        for (time1, time2) in [(-0.5, 0.7), (0.6, 1.9), (0.2, 3.0)] {
+            assert_integrals_match_midpoint(&params, time1, time2, 1e-10);
+        }
+    }
+
+    #[test]
+    fn test_single_pillar() {
+        let constant = ParametersPiecewiseConstant::new(&[(1.0, 0.2)]).unwrap();
+        let linear = ParametersPiecewiseLinear::new(&[(1.0, 0.2)]).unwrap();
+        for params in [&constant as &dyn Parameters, &linear] {
+            assert_relative_eq!(params.integral(0.0, 3.0), 0.6);
+            assert_relative_eq!(params.integral_square(0.5, 3.0), 0.1);
+        }
+    }
+
+    #[test]
+    fn test_invalid_pillars() {
+        assert_eq!(
+            ParametersPiecewiseConstant::new(&[]).unwrap_err(),
+            ParametersError::NoPillars
+        );
+        assert_eq!(
+            ParametersPiecewiseConstant::new(&[(0.0, 0.1)]).unwrap_err(),
+            ParametersError::NonPositiveTime {
+                index: 0,
+                time: 0.0
+            }
+        );
+        assert!(ParametersPiecewiseLinear::new(&[(0.0, 0.1)]).is_ok());
+        assert_eq!(
+            ParametersPiecewiseLinear::new(&[(-1.0, 0.1)]).unwrap_err(),
+            ParametersError::NegativeTime {
+                index: 0,
+                time: -1.0
+            }
+        );
+        assert_eq!(
+            ParametersPiecewiseLinear::new(&[(1.0, 0.1), (1.0, 0.2)]).unwrap_err(),
+            ParametersError::TimesNotIncreasing {
+                index: 1,
+                previous: 1.0,
+                time: 1.0
+            }
+        );
+        assert_eq!(
+            ParametersPiecewiseConstant::new(&[(1.0, 0.1), (2.0, f64::NAN)])
+                .unwrap_err()
+                .to_string(),
+            "The pillar 1 must be finite, but got (2, NaN)"
+        );
+        // Negative values are allowed for rates but not for volatilities.
+        assert!(ParametersPiecewiseConstant::new(&[(1.0, -0.2)]).is_ok());
+        assert_eq!(
+            ParametersPiecewiseConstant::new_positive(&[(1.0, -0.2)]).unwrap_err(),
+            ParametersError::NonPositiveValue {
+                index: 0,
+                value: -0.2
+            }
+        );
+        assert_eq!(
+            ParametersPiecewiseLinear::new_positive(&[(0.0, 0.1), (1.0, 0.0)]).unwrap_err(),
+            ParametersError::NonPositiveValue {
+                index: 1,
+                value: 0.0
+            }
+        );
+        assert!(ParametersPiecewiseLinear::new_positive(&[(0.0, 0.1), (1.0, 0.2)]).is_ok());
+    }
+
+    #[test]
+    fn test_black_scholes_with_term_structure() {
+        let vol = ParametersPiecewiseConstant::new_positive(&[(0.5, 0.1), (1.0, 0.3)]).unwrap();
+        let r = ParametersPiecewiseLinear::new(&[(0.0, 0.01), (1.0, 0.05)]).unwrap();
+        let d = ParametersConstant::new(0.0);
+        let flat_vol = ParametersConstant::new((vol.integral_square(0.0, 1.0)).sqrt());
+        let flat_r = ParametersConstant::new(r.integral(0.0, 1.0));
+        assert_relative_eq!(
+            black_scholes_call(100.0, 100.0, &r, &d, &vol, 1.0),
+            black_scholes_call(100.0, 100.0, &flat_r, &d, &flat_vol, 1.0),
+            epsilon = 1e-12
+        );
+    }
 }
@@ -0,0 +1,269 @@
+//! デコレーターパターンで任意の`Parameters`をずらし、有限差分でvegaやrhoを求められるようにした。
+//! - 平行シフト: 全ての時刻で一定の値を加える。
+//! - 相対的なバンプ: 全ての時刻で一定の割合だけ拡大する。
+//! - バケットのシフト: ある時間区間の中だけ一定の値を加え、期間構造のどの部分に価格が敏感かを調べる。
+//!
+//! いずれも元の`integral`と`integral_square`から閉じた式で積分を求めるので、元のパラメータの精度を保つ。
+//! 参照にも`Parameters`を実装しているので、元のパラメータを複製せずに参照をデコレートできる。
+use crate::chapter4::parameters::Parameters;
+
+/// A parallel shift implemented in the decorator pattern.
+#[derive(Debug, Clone)]
+pub struct ParametersShifted<P: Parameters> {
+    inner: P,
+    shift: f64,
+}
+
+impl<P: Parameters> ParametersShifted<P> {
+    /// Constructor.
+    ///
+    /// # Arguments
+    ///
+    /// * `inner` - The parameter to be shifted
+    /// * `shift` - The amount added at all times
+    pub fn new(inner: P, shift: f64) -> Self {
+        Self { inner, shift }
+    }
+}
+
+impl<P: Parameters> Parameters for ParametersShifted<P> {
+    fn value_at(&self, x: f64) -> f64 {
+        self.inner.value_at(x) + self.shift
+    }
+    fn integral(&self, time1: f64, time2: f64) -> f64 {
+        self.inner.integral(time1, time2) + self.shift * (time2 - time1)
+    }
+    fn integral_square(&self, time1: f64, time2: f64) -> f64 {
+        self.inner.integral_square(time1, time2)
+            + 2.0 * self.shift * self.inner.integral(time1, time2)
+            + self.shift * self.shift * (time2 - time1)
+    }
+}
+
+/// A relative bump implemented in the decorator pattern.
+#[derive(Debug, Clone)]
+pub struct ParametersScaled<P: Parameters> {
+    inner: P,
+    factor: f64,
+}
+
+impl<P: Parameters> ParametersScaled<P> {
+    /// Constructor.
+    ///
+    /// # Arguments
+    ///
+    /// * `inner` - The parameter to be bumped
+    /// * `relative_bump` - The relative change at all times, e.g. 0.01 multiplies the values by 1.01
+    pub fn new(inner: P, relative_bump: f64) -> Self {
+        Self {
+            inner,
+            factor: 1.0 + relative_bump,
+        }
+    }
+}
+
+impl<P: Parameters> Parameters for ParametersScaled<P> {
+    fn value_at(&self, x: f64) -> f64 {
+        self.factor * self.inner.value_at(x)
+    }
+    fn integral(&self, time1: f64, time2: f64) -> f64 {
+        self.factor * self.inner.integral(time1, time2)
+    }
+    fn integral_square(&self, time1: f64, time2: f64) -> f64 {
+        self.factor * self.factor * self.inner.integral_square(time1, time2)
+    }
+}
+
+/// A shift within the bucket `[start, end)` implemented in the decorator pattern.
+///
+/// Shifting each bucket of a partition of times in turn gives the sensitivities to each part of the term structure,
+/// which add up to the sensitivity to the parallel shift for small shifts.
+#[derive(Debug, Clone)]
+pub struct ParametersBucketShifted<P: Parameters> {
+    inner: P,
+    shift: f64,
+    start: f64,
+    end: f64,
+}
+
+impl<P: Parameters> ParametersBucketShifted<P> {
+    /// Constructor.
+    ///
+    /// # Arguments
+    ///
+    /// * `inner` - The parameter to be shifted
+    /// * `shift` - The amount added within the bucket
+    /// * `start` - The start of the bucket
+    /// * `end` - The end of the bucket
+    ///
+    /// # Panics
+    ///
+    /// Panics if `start` is not less than `end`.
+    pub fn new(inner: P, shift: f64, start: f64, end: f64) -> Self {
+        if start.is_nan() || end.is_nan() || start >= end {
+            panic!("The bucket must satisfy start < end, but got [{start}, {end}).");
+        }
+        Self {
+            inner,
+            shift,
+            start,
+            end,
+        }
+    }
+
+    /// Returns the part of the interval from `time1` to `time2` within the bucket, keeping the direction.
+    fn overlap(&self, time1: f64, time2: f64) -> Option<(f64, f64)> {
+        let (low, high) = (time1.min(time2), time1.max(time2));
+        let (low, high) = (low.max(self.start), high.min(self.end));
+        if low >= high {
+            None
+        } else if time1 <= time2 {
+            Some((low, high))
+        } else {
+            Some((high, low))
+        }
+    }
+}
+
+impl<P: Parameters> Parameters for ParametersBucketShifted<P> {
+    fn value_at(&self, x: f64) -> f64 {
+        if self.start <= x && x < self.end {
+            self.inner.value_at(x) + self.shift
+        } else {
+            self.inner.value_at(x)
+        }
+    }
+    fn integral(&self, time1: f64, time2: f64) -> f64 {
+        let shifted = self
+            .overlap(time1, time2)
+            .map_or(0.0, |(low, high)| self.shift * (high - low));
+        self.inner.integral(time1, time2) + shifted
+    }
+    fn integral_square(&self, time1: f64, time2: f64) -> f64 {
+        let shifted = self.overlap(time1, time2).map_or(0.0, |(low, high)| {
+            2.0 * self.shift * self.inner.integral(low, high)
+                + self.shift * self.shift * (high - low)
+        });
+        self.inner.integral_square(time1, time2) + shifted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chapter4::parameters::{ParametersConstant, ParametersPiecewiseLinear};
+    use crate::chapter4::parameters_test_support::assert_integrals_match_midpoint;
+    use crate::chapter4::payoff3::{Payoff, PayoffCall};
+    use crate::chapter5::mc_statistics::{StatisticsMC, StatisticsMean};
+    use crate::chapter6::park_miller::RandomParkMiller;
+    use crate::chapter7::exotic_bs_engine::ExoticBSEngine;
+    use crate::chapter7::exotic_engine::{ExoticEngine, ExoticEngineData};
+    use crate::chapter7::path_dependent_asian::PathDependentAsian;
+    use crate::chapter9::black_scholes_formulas::{black_scholes_call_rho, black_scholes_vega};
+    use approx::assert_relative_eq;
+
+    fn linear() -> ParametersPiecewiseLinear {
+        ParametersPiecewiseLinear::new(&[(0.0, 0.1), (1.0, 0.3), (2.0, 0.2)]).unwrap()
+    }
+
+    #[test]
+    fn test_shifted_and_scaled() {
+        let inner = linear();
+        let shifted = ParametersShifted::new(&inner, 0.01);
+        assert_relative_eq!(shifted.value_at(0.5), 0.21);
+        assert_relative_eq!(shifted.integral(0.0, 2.0), inner.integral(0.0, 2.0) + 0.02);
+        let scaled = ParametersScaled::new(&inner, 0.1);
+        assert_relative_eq!(scaled.value_at(0.5), 0.22);
+        assert_relative_eq!(
+            scaled.integral_square(0.5, 1.5),
+            1.21 * inner.integral_square(0.5, 1.5)
+        );
+        assert_integrals_match_midpoint(&shifted, -0.2, 2.3, 1e-9);
+        assert_integrals_match_midpoint(&scaled, -0.2, 2.3, 1e-9);
+    }
+
+    #[test]
+    fn test_bucket_shifted() {
+        let inner = linear();
+        let bucket = ParametersBucketShifted::new(&inner, 0.05, 0.5, 1.5);
+        assert_eq!(bucket.value_at(0.4), inner.value_at(0.4));
+        assert_relative_eq!(bucket.value_at(0.5), inner.value_at(0.5) + 0.05);
+        assert_eq!(bucket.value_at(1.5), inner.value_at(1.5));
+        assert_relative_eq!(bucket.integral(0.0, 1.0), inner.integral(0.0, 1.0) + 0.025);
+        assert_relative_eq!(bucket.integral(1.0, 0.0), -bucket.integral(0.0, 1.0));
+        assert_eq!(bucket.integral(1.6, 2.0), inner.integral(1.6, 2.0));
+        // The buckets of a partition add up to the parallel shift.
+        let parallel = ParametersShifted::new(&inner, 0.05);
+        let buckets = [(-1.0, 0.5), (0.5, 1.5), (1.5, 3.0)];
+        let total: f64 = buckets
+            .iter()
+            .map(|&(start, end)| {
+                let bucket = ParametersBucketShifted::new(&inner, 0.05, start, end);
+                bucket.integral_square(0.0, 2.0) - inner.integral_square(0.0, 2.0)
+            })
+            .sum();
+        let linear_part = parallel.integral_square(0.0, 2.0) - inner.integral_square(0.0, 2.0);
+        assert_relative_eq!(total, linear_part, epsilon = 1e-15);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_empty_bucket() {
+        ParametersBucketShifted::new(ParametersConstant::new(0.1), 0.01, 1.0, 1.0);
+    }
+
+    /// Returns the price of a call on the average of `times` by `ExoticBSEngine` with common random numbers.
+    fn price(times: &[f64], r: &impl Parameters, vol: impl Parameters) -> f64 {
+        let the_payoff = PayoffCall::new(100.0);
+        let the_option = PathDependentAsian::new(times.to_vec(), 1.0, &the_payoff);
+        let data = ExoticEngineData::new(&the_option, r);
+        let mut the_engine = ExoticBSEngine::new(
+            times,
+            r,
+            ParametersConstant::new(0.0),
+            vol,
+            RandomParkMiller::new(times.len(), 1),
+            100.0,
+        );
+        let mut gatherer = StatisticsMean::default();
+        the_engine.do_simulation_reproducible(&data, &mut gatherer, 100_000);
+        gatherer.get_results_so_far()[0][0]
+    }
+
+    #[test]
+    fn test_finite_difference_greeks() {
+        let r = ParametersConstant::new(0.05);
+        let d = ParametersConstant::new(0.0);
+        let vol = ParametersConstant::new(0.2);
+        let times = [1.0];
+        let h = 1e-4;
+        let vega = (price(&times, &r, ParametersShifted::new(vol, h))
+            - price(&times, &r, ParametersShifted::new(vol, -h)))
+            / (2.0 * h);
+        let expected = black_scholes_vega(100.0, 100.0, &r, &d, &vol, 1.0);
+        assert_relative_eq!(vega, expected, max_relative = 2e-2);
+        let rho = (price(&times, &ParametersShifted::new(r, h), vol)
+            - price(&times, &ParametersShifted::new(r, -h), vol))
+            / (2.0 * h);
+        let expected = black_scholes_call_rho(100.0, 100.0, &r, &d, &vol, 1.0);
+        assert_relative_eq!(rho, expected, max_relative = 2e-2);
+        // The bucketed vegas of an Asian option add up to the parallel vega.
+        let times = [0.25, 0.5, 0.75, 1.0];
+        let bucket_vegas: f64 = [(0.0, 0.5), (0.5, 1.0)]
+            .iter()
+            .map(|&(start, end)| {
+                (price(&times, &r, ParametersBucketShifted::new(vol, h, start, end))
+                    - price(
+                        &times,
+                        &r,
+                        ParametersBucketShifted::new(vol, -h, start, end),
+                    ))
+                    / (2.0 * h)
+            })
+            .sum();
+        let vega = (price(&times, &r, ParametersScaled::new(vol, h / 0.2))
+            - price(&times, &r, ParametersScaled::new(vol, -h / 0.2)))
+            / (2.0 * h);
+        assert_relative_eq!(bucket_vegas, vega, max_relative = 1e-4);
+    }
+}
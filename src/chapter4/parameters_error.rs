@@ -0,0 +1,26 @@
+use std::fmt::Debug;
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum ParametersError {
+    #[error("At least one pillar is required")]
+    NoPillars,
+    #[error("The time of the pillar {index} must be positive, but got {time}")]
+    NonPositiveTime { index: usize, time: f64 },
+    #[error("The time of the pillar {index} must not be negative, but got {time}")]
+    NegativeTime { index: usize, time: f64 },
+    #[error(
+        "The times must be strictly increasing, but the pillar {index} has {time} after {previous}"
+    )]
+    TimesNotIncreasing {
+        index: usize,
+        previous: f64,
+        time: f64,
+    },
+    #[error("The pillar {index} must be finite, but got ({time}, {value})")]
+    NotFinite { index: usize, time: f64, value: f64 },
+    #[error("The value of the pillar {index} must be positive, but got {value}")]
+    NonPositiveValue { index: usize, value: f64 },
+    #[error("The parameter {name} must be positive, but got {value}")]
+    NonPositiveParameter { name: &'static str, value: f64 },
+}
@@ -0,0 +1,355 @@
+//! よく用いられる関数形の`Parameters`。
+//! - 指数減衰: a + b exp(-ct)
+//! - Nelson-Siegel-Svensson \[Svensson\]: 瞬間フォワードレートを短期金利とみなす。
+//! - Rebonatoのabcd \[Rebonato\]: 満期Tのフォワードの時刻tでのボラティリティを(a + b(T - t))exp(-c(T - t)) + dとする。
+//!
+//! いずれも c t^k exp(-λt) の形の項の和であり、その二乗もまた同じ形の項の和となるので、
+//! `integral`と`integral_square`を数値積分によらず閉じた式で求められる。
+//! λtが小さい場合は原始関数の項が打ち消し合って精度を失うので、指数関数のTaylor展開を項別に積分する。
+//!
+//! \[Svensson\] Svensson, L. E. O. "Estimating and interpreting forward interest rates: Sweden 1992-1994." NBER Working Paper 4871 (1994).
+//!
+//! \[Rebonato\] Rebonato, R. "Volatility and Correlation: The Perfect Hedger and the Fox." 2nd ed. Wiley (2004).
+use crate::chapter4::parameters::Parameters;
+use crate::chapter4::parameters_error::ParametersError;
+
+/// The bound of `|λt|` below which the Taylor series is integrated.
+const SERIES_THRESHOLD: f64 = 1.0;
+/// The number of terms of the Taylor series, whose remainder is below `1 / 25!` for `|λt|` below the threshold.
+const SERIES_TERMS: i32 = 25;
+
+/// The term `coefficient * t^power * exp(-rate * t)`.
+#[derive(Debug, Clone, Copy)]
+struct Term {
+    coefficient: f64,
+    power: i32,
+    rate: f64,
+}
+
+/// Returns the integral of `t^power * exp(-rate * t)` from `time1` to `time2`.
+fn integral_of_term(power: i32, rate: f64, time1: f64, time2: f64) -> f64 {
+    if rate.abs() * time1.abs().max(time2.abs()) < SERIES_THRESHOLD {
+        let mut factor = 1.0;
+        let mut sum = 0.0;
+        for m in 0..SERIES_TERMS {
+            let n = power + m + 1;
+            sum += factor * (time2.powi(n) - time1.powi(n)) / n as f64;
+            factor *= -rate / (m + 1) as f64;
+        }
+        return sum;
+    }
+    // The antiderivative -exp(-rate t) sum_j power! / j! t^j / rate^(power - j + 1).
+    let antiderivative = |t: f64| {
+        let mut sum = 0.0;
+        let mut factor = 1.0 / rate;
+        for j in (0..=power).rev() {
+            sum += factor * t.powi(j);
+            factor *= j as f64 / rate;
+        }
+        -(-rate * t).exp() * sum
+    };
+    antiderivative(time2) - antiderivative(time1)
+}
+
+/// A sum of terms `c * t^k * exp(-λt)`.
+#[derive(Debug, Clone)]
+struct ExponentialPolynomial {
+    terms: Vec<Term>,
+}
+
+impl ExponentialPolynomial {
+    fn value(&self, t: f64) -> f64 {
+        self.terms
+            .iter()
+            .map(|term| term.coefficient * t.powi(term.power) * (-term.rate * t).exp())
+            .sum()
+    }
+
+    fn integral(&self, time1: f64, time2: f64) -> f64 {
+        self.terms
+            .iter()
+            .map(|term| term.coefficient * integral_of_term(term.power, term.rate, time1, time2))
+            .sum()
+    }
+
+    fn integral_square(&self, time1: f64, time2: f64) -> f64 {
+        self.terms
+            .iter()
+            .flat_map(|x| self.terms.iter().map(move |y| (x, y)))
+            .map(|(x, y)| {
+                x.coefficient
+                    * y.coefficient
+                    * integral_of_term(x.power + y.power, x.rate + y.rate, time1, time2)
+            })
+            .sum()
+    }
+}
+
+/// Returns an error unless `value` is positive.
+fn check_positive(name: &'static str, value: f64) -> Result<(), ParametersError> {
+    if value > 0.0 && value.is_finite() {
+        Ok(())
+    } else {
+        Err(ParametersError::NonPositiveParameter { name, value })
+    }
+}
+
+/// The parameter `a + b * exp(-c * t)` decaying from `a + b` to `a`.
+#[derive(Debug, Clone)]
+pub struct ParametersExponential {
+    function: ExponentialPolynomial,
+}
+
+impl ParametersExponential {
+    /// Constructor.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `c` is not positive.
+    pub fn new(a: f64, b: f64, c: f64) -> Result<Self, ParametersError> {
+        check_positive("c", c)?;
+        let terms = vec![
+            Term {
+                coefficient: a,
+                power: 0,
+                rate: 0.0,
+            },
+            Term {
+                coefficient: b,
+                power: 0,
+                rate: c,
+            },
+        ];
+        Ok(Self {
+            function: ExponentialPolynomial { terms },
+        })
+    }
+}
+
+impl Parameters for ParametersExponential {
+    fn value_at(&self, x: f64) -> f64 {
+        self.function.value(x)
+    }
+    fn integral(&self, time1: f64, time2: f64) -> f64 {
+        self.function.integral(time1, time2)
+    }
+    fn integral_square(&self, time1: f64, time2: f64) -> f64 {
+        self.function.integral_square(time1, time2)
+    }
+}
+
+/// The instantaneous forward rate of the Nelson-Siegel-Svensson model,
+/// `β0 + β1 exp(-t/τ1) + β2 (t/τ1) exp(-t/τ1) + β3 (t/τ2) exp(-t/τ2)`.
+///
+/// Setting `β3` to 0 gives the Nelson-Siegel model.
+#[derive(Debug, Clone)]
+pub struct ParametersNelsonSiegelSvensson {
+    function: ExponentialPolynomial,
+}
+
+impl ParametersNelsonSiegelSvensson {
+    /// Constructor.
+    ///
+    /// # Arguments
+    ///
+    /// * `betas` - The long rate `β0`, the short component `β1` and the humps `β2` and `β3`
+    /// * `tau1` - The time scale of `β1` and `β2`
+    /// * `tau2` - The time scale of `β3`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `tau1` or `tau2` is not positive.
+    pub fn new(betas: [f64; 4], tau1: f64, tau2: f64) -> Result<Self, ParametersError> {
+        check_positive("tau1", tau1)?;
+        check_positive("tau2", tau2)?;
+        let [beta0, beta1, beta2, beta3] = betas;
+        let terms = vec![
+            Term {
+                coefficient: beta0,
+                power: 0,
+                rate: 0.0,
+            },
+            Term {
+                coefficient: beta1,
+                power: 0,
+                rate: 1.0 / tau1,
+            },
+            Term {
+                coefficient: beta2 / tau1,
+                power: 1,
+                rate: 1.0 / tau1,
+            },
+            Term {
+                coefficient: beta3 / tau2,
+                power: 1,
+                rate: 1.0 / tau2,
+            },
+        ];
+        Ok(Self {
+            function: ExponentialPolynomial { terms },
+        })
+    }
+
+    /// Returns the continuously compounded zero rate to `time`, which is the short rate for `time` 0.
+    pub fn zero_rate(&self, time: f64) -> f64 {
+        if time == 0.0 {
+            self.value_at(0.0)
+        } else {
+            self.integral(0.0, time) / time
+        }
+    }
+}
+
+impl Parameters for ParametersNelsonSiegelSvensson {
+    fn value_at(&self, x: f64) -> f64 {
+        self.function.value(x)
+    }
+    fn integral(&self, time1: f64, time2: f64) -> f64 {
+        self.function.integral(time1, time2)
+    }
+    fn integral_square(&self, time1: f64, time2: f64) -> f64 {
+        self.function.integral_square(time1, time2)
+    }
+}
+
+/// The volatility `(a + b(T - t)) exp(-c(T - t)) + d` at time `t` of a forward maturing at `T`.
+#[derive(Debug, Clone)]
+pub struct ParametersAbcd {
+    /// The function of the time to maturity
+    function: ExponentialPolynomial,
+    maturity: f64,
+}
+
+impl ParametersAbcd {
+    /// Constructor.
+    ///
+    /// # Arguments
+    ///
+    /// * `a`, `b`, `c`, `d` - The parameters, where `a + d` is the volatility at maturity and `d` long before it
+    /// * `maturity` - The maturity `T` of the forward
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `c`, `d` or `a + d` is not positive.
+    pub fn new(a: f64, b: f64, c: f64, d: f64, maturity: f64) -> Result<Self, ParametersError> {
+        check_positive("c", c)?;
+        check_positive("d", d)?;
+        check_positive("a + d", a + d)?;
+        let terms = vec![
+            Term {
+                coefficient: a,
+                power: 0,
+                rate: c,
+            },
+            Term {
+                coefficient: b,
+                power: 1,
+                rate: c,
+            },
+            Term {
+                coefficient: d,
+                power: 0,
+                rate: 0.0,
+            },
+        ];
+        Ok(Self {
+            function: ExponentialPolynomial { terms },
+            maturity,
+        })
+    }
+}
+
+impl Parameters for ParametersAbcd {
+    fn value_at(&self, x: f64) -> f64 {
+        self.function.value(self.maturity - x)
+    }
+    fn integral(&self, time1: f64, time2: f64) -> f64 {
+        self.function
+            .integral(self.maturity - time2, self.maturity - time1)
+    }
+    fn integral_square(&self, time1: f64, time2: f64) -> f64 {
+        self.function
+            .integral_square(self.maturity - time2, self.maturity - time1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chapter4::parameters_test_support::{
+        assert_integrals_match_midpoint, midpoint_rule,
+    };
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_integral_of_term() {
+        // Across the threshold of the Taylor series.
+        for rate in [0.0, 1e-9, 5e-3, 0.3, 0.6, 3.0, -0.2, -1.0] {
+            for power in 0..3 {
+                let exact = integral_of_term(power, rate, 0.5, 2.0);
+                let midpoint = midpoint_rule(|t| t.powi(power) * (-rate * t).exp(), 0.5, 2.0);
+                assert_relative_eq!(exact, midpoint, max_relative = 1e-10);
+            }
+        }
+    }
+
+    #[test]
+    fn test_exponential() {
+        let params = ParametersExponential::new(0.2, 0.1, 2.0).unwrap();
+        assert_relative_eq!(params.value_at(0.0), 0.3);
+        assert_relative_eq!(
+            params.integral(0.0, 1.0),
+            0.2 + 0.1 * (1.0 - (-2.0_f64).exp()) / 2.0
+        );
+        assert_integrals_match_midpoint(&params, 0.25, 3.0, 1e-10);
+        assert_eq!(
+            ParametersExponential::new(0.2, 0.1, 0.0).unwrap_err(),
+            ParametersError::NonPositiveParameter {
+                name: "c",
+                value: 0.0
+            }
+        );
+    }
+
+    #[test]
+    fn test_nelson_siegel_svensson() {
+        let (tau1, tau2) = (1.5, 6.0);
+        let betas = [0.04, -0.02, 0.01, 0.015];
+        let params = ParametersNelsonSiegelSvensson::new(betas, tau1, tau2).unwrap();
+        // The closed form of the zero rate.
+        let loading = |t: f64, tau: f64| (1.0 - (-t / tau).exp()) / (t / tau);
+        let t = 4.0;
+        let expected = betas[0]
+            + betas[1] * loading(t, tau1)
+            + betas[2] * (loading(t, tau1) - (-t / tau1).exp())
+            + betas[3] * (loading(t, tau2) - (-t / tau2).exp());
+        assert_relative_eq!(params.zero_rate(t), expected, epsilon = 1e-15);
+        assert_relative_eq!(params.zero_rate(0.0), betas[0] + betas[1]);
+        assert_relative_eq!(
+            params.zero_rate(1e-12),
+            betas[0] + betas[1],
+            epsilon = 1e-12
+        );
+        assert_integrals_match_midpoint(&params, 0.0, 10.0, 1e-10);
+    }
+
+    #[test]
+    fn test_abcd() {
+        let (a, b, c, d) = (-0.05, 0.5, 1.5, 0.15);
+        let params = ParametersAbcd::new(a, b, c, d, 5.0).unwrap();
+        assert_relative_eq!(params.value_at(5.0), a + d);
+        assert_relative_eq!(
+            params.value_at(3.0),
+            (a + b * 2.0) * (-c * 2.0_f64).exp() + d
+        );
+        assert_integrals_match_midpoint(&params, 0.0, 5.0, 1e-10);
+        assert_integrals_match_midpoint(&params, 1.0, 4.9, 1e-10);
+        assert_eq!(
+            ParametersAbcd::new(-0.2, b, c, d, 5.0).unwrap_err(),
+            ParametersError::NonPositiveParameter {
+                name: "a + d",
+                value: -0.2 + d
+            }
+        );
+    }
+}
@@ -0,0 +1,36 @@
+//! `Parameters`のテストで共通に用いる補助関数。
+//! 閉じた式で求めた積分を、`value_at`の中点則による数値積分と比較する。
+use crate::chapter4::parameters::Parameters;
+use approx::assert_relative_eq;
+
+/// The number of steps of the midpoint rule.
+const MIDPOINT_STEPS: usize = 240_000;
+
+/// Returns the integral of `f` from `time1` to `time2` by the midpoint rule.
+pub(crate) fn midpoint_rule(f: impl Fn(f64) -> f64, time1: f64, time2: f64) -> f64 {
+    let h = (time2 - time1) / MIDPOINT_STEPS as f64;
+    (0..MIDPOINT_STEPS)
+        .map(|i| f(time1 + (i as f64 + 0.5) * h))
+        .sum::<f64>()
+        * h
+}
+
+/// Asserts that `integral` and `integral_square` of `params` agree with the midpoint rule of `value_at`
+/// within the absolute `tolerance`.
+pub(crate) fn assert_integrals_match_midpoint(
+    params: &(impl Parameters + ?Sized),
+    time1: f64,
+    time2: f64,
+    tolerance: f64,
+) {
+    assert_relative_eq!(
+        params.integral(time1, time2),
+        midpoint_rule(|x| params.value_at(x), time1, time2),
+        epsilon = tolerance
+    );
+    assert_relative_eq!(
+        params.integral_square(time1, time2),
+        midpoint_rule(|x| params.value_at(x).powi(2), time1, time2),
+        epsilon = tolerance
+    );
+}
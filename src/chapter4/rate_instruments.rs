@@ -0,0 +1,185 @@
+//! イールドカーブのブートストラップに用いる金利商品の気配値。
+//! 時刻は評価日からの年数で表し、日数計算規則や営業日の調整は呼び出し側で済ませたものとする。
+//! - 預金: 評価日から満期まで単利で運用する。
+//! - FRA: 将来の期間の単利の金利を約定する。
+//! - 金利先物: 価格は100から年率の百分率の金利を引いたものとし、凸性調整はしない。
+//! - スワップ: 評価日に始まり、固定金利を年に一定回数支払うスワップのパーレート。
+//!   固定金利の支払日は満期から一定の間隔で遡って決め、端数は最初の短い期間とする。
+//!   変動金利側は割引に用いるカーブと同じカーブで評価するので額面に等しい。
+use crate::chapter4::yield_curve::YieldCurve;
+use crate::chapter4::yield_curve_error::YieldCurveError;
+
+/// A quoted instrument from which a yield curve is bootstrapped.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RateInstrument {
+    /// A deposit with the simply compounded `rate` from 0 to `maturity`
+    Deposit { maturity: f64, rate: f64 },
+    /// A forward rate agreement with the simply compounded `rate` from `start` to `end`
+    ForwardRateAgreement { start: f64, end: f64, rate: f64 },
+    /// An interest rate future on the period from `start` to `end` quoted as `100 - 100 * rate`
+    Future { start: f64, end: f64, price: f64 },
+    /// A spot starting swap with the par `rate` paid `payments_per_year` times a year until `maturity`
+    Swap {
+        maturity: f64,
+        rate: f64,
+        payments_per_year: u32,
+    },
+}
+
+impl RateInstrument {
+    /// Returns the time of the last cash flow, where the bootstrapped pillar is placed.
+    pub fn maturity(&self) -> f64 {
+        match *self {
+            RateInstrument::Deposit { maturity, .. } => maturity,
+            RateInstrument::ForwardRateAgreement { end, .. } => end,
+            RateInstrument::Future { end, .. } => end,
+            RateInstrument::Swap { maturity, .. } => maturity,
+        }
+    }
+
+    /// Returns the quoted rate or price.
+    pub fn quote(&self) -> f64 {
+        match *self {
+            RateInstrument::Deposit { rate, .. } => rate,
+            RateInstrument::ForwardRateAgreement { rate, .. } => rate,
+            RateInstrument::Future { price, .. } => price,
+            RateInstrument::Swap { rate, .. } => rate,
+        }
+    }
+
+    /// Returns the quote implied by the discount factors of `curve`.
+    pub fn implied_quote(&self, curve: &YieldCurve) -> f64 {
+        let forward_rate = |start: f64, end: f64| {
+            (curve.discount_factor(start) / curve.discount_factor(end) - 1.0) / (end - start)
+        };
+        match *self {
+            RateInstrument::Deposit { maturity, .. } => forward_rate(0.0, maturity),
+            RateInstrument::ForwardRateAgreement { start, end, .. } => forward_rate(start, end),
+            RateInstrument::Future { start, end, .. } => 100.0 * (1.0 - forward_rate(start, end)),
+            RateInstrument::Swap {
+                maturity,
+                payments_per_year,
+                ..
+            } => {
+                let payment_times = swap_payment_times(maturity, payments_per_year);
+                let mut previous = 0.0;
+                let annuity: f64 = payment_times
+                    .iter()
+                    .map(|&time| {
+                        let accrual = time - previous;
+                        previous = time;
+                        accrual * curve.discount_factor(time)
+                    })
+                    .sum();
+                (1.0 - curve.discount_factor(maturity)) / annuity
+            }
+        }
+    }
+
+    /// Checks that the times, the quote and the frequency are valid.
+    pub(crate) fn validate(&self) -> Result<(), YieldCurveError> {
+        let (start, end) = match *self {
+            RateInstrument::Deposit { maturity, .. } => (0.0, maturity),
+            RateInstrument::ForwardRateAgreement { start, end, .. } => (start, end),
+            RateInstrument::Future { start, end, .. } => (start, end),
+            RateInstrument::Swap {
+                maturity,
+                payments_per_year,
+                ..
+            } => {
+                if payments_per_year == 0 {
+                    return Err(YieldCurveError::InvalidFrequency);
+                }
+                (0.0, maturity)
+            }
+        };
+        if !(start.is_finite() && end.is_finite() && 0.0 <= start && start < end) {
+            return Err(YieldCurveError::InvalidPeriod { start, end });
+        }
+        if !self.quote().is_finite() {
+            return Err(YieldCurveError::InvalidQuote(self.quote()));
+        }
+        Ok(())
+    }
+}
+
+/// Returns the payment times of the fixed leg rolled back from `maturity`, with a short first period.
+fn swap_payment_times(maturity: f64, payments_per_year: u32) -> Vec<f64> {
+    let period = 1.0 / payments_per_year as f64;
+    // Periods shorter than a tiny fraction are rounding errors of the maturity.
+    let number_of_payments = ((maturity / period) - 1e-9).ceil().max(1.0) as usize;
+    (0..number_of_payments)
+        .map(|k| maturity - (number_of_payments - 1 - k) as f64 * period)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chapter4::yield_curve::CurveInterpolation;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_swap_payment_times() {
+        assert_eq!(swap_payment_times(2.0, 2), [0.5, 1.0, 1.5, 2.0]);
+        assert_eq!(swap_payment_times(1.25, 2), [0.25, 0.75, 1.25]);
+        assert_eq!(swap_payment_times(0.3, 1), [0.3]);
+    }
+
+    #[test]
+    fn test_implied_quotes_on_flat_curve() {
+        // The continuously compounded rate 5%.
+        let curve = YieldCurve::from_discount_factors(
+            &[(10.0, (-0.5_f64).exp())],
+            CurveInterpolation::LogLinearDiscount,
+        )
+        .unwrap();
+        let deposit = RateInstrument::Deposit {
+            maturity: 0.5,
+            rate: 0.0,
+        };
+        assert_relative_eq!(deposit.implied_quote(&curve), (0.025_f64.exp() - 1.0) / 0.5);
+        let future = RateInstrument::Future {
+            start: 1.0,
+            end: 1.25,
+            price: 0.0,
+        };
+        assert_relative_eq!(
+            future.implied_quote(&curve),
+            100.0 * (1.0 - (0.0125_f64.exp() - 1.0) / 0.25)
+        );
+        let swap = RateInstrument::Swap {
+            maturity: 3.0,
+            rate: 0.0,
+            payments_per_year: 1,
+        };
+        // Annual compounding of the continuous rate.
+        assert_relative_eq!(
+            swap.implied_quote(&curve),
+            0.05_f64.exp() - 1.0,
+            epsilon = 1e-14
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        let fra = RateInstrument::ForwardRateAgreement {
+            start: 1.0,
+            end: 0.5,
+            rate: 0.01,
+        };
+        assert_eq!(
+            fra.validate(),
+            Err(YieldCurveError::InvalidPeriod {
+                start: 1.0,
+                end: 0.5
+            })
+        );
+        let swap = RateInstrument::Swap {
+            maturity: 1.0,
+            rate: 0.01,
+            payments_per_year: 0,
+        };
+        assert_eq!(swap.validate(), Err(YieldCurveError::InvalidFrequency));
+    }
+}
@@ -0,0 +1,648 @@
+//! 割引債価格(ディスカウントファクター)のカーブ。
+//! 瞬間フォワードレートを短期金利とみなして`Parameters`を実装するので、
+//! 定数の金利の代わりに`ExoticEngineData::new`や`ExoticBSEngine`にそのまま渡すことができる。
+//! `integral`は割引債価格の対数の差であり、`integral_square`はフォワードレートが多項式となる区間ごとのGauss-Legendre公式で正確に求める。
+//!
+//! 柱の間の補間は次から選ぶ。
+//! - 割引債価格の対数の線形補間: フォワードレートは区分的に一定となる。
+//! - ゼロレートの線形補間: フォワードレートは区分的に線形となり、柱で不連続となる。
+//! - Monotone Convex \[HaganWest\]: 柱の間の離散的なフォワードレートを保ったまま、瞬間フォワードレートを連続にする。
+//!   正値性の補正は行わない。
+//!
+//! 最初の柱より前と最後の柱より後は、端の瞬間フォワードレートを一定として外挿する。
+//!
+//! ブートストラップでは商品を満期の順に並べ、各満期に柱を置いて気配値を再現する割引債価格をBrent法で求める。
+//! Monotone Convexでは後の柱がそれより前の区間の補間を変えるので、全ての商品が再現されるまで柱ごとの求解を繰り返す。
+//!
+//! \[HaganWest\] Hagan, P. S. and West, G. "Interpolation methods for curve construction." Appl. Math. Finance 13 (2006): 89-129.
+use crate::chapter4::parameters::Parameters;
+use crate::chapter4::rate_instruments::RateInstrument;
+use crate::chapter4::yield_curve_error::YieldCurveError;
+use crate::chapter9::brent::Brent;
+use crate::chapter9::solver::{Solver, SolverSettings};
+
+/// The maximum number of sweeps of the global bootstrap.
+const MAX_SWEEPS: usize = 100;
+/// The tolerance of the change of the logarithms of the discount factors in a sweep.
+const SWEEP_TOLERANCE: f64 = 1e-13;
+
+/// The interpolation between pillars of a yield curve.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum CurveInterpolation {
+    /// The logarithms of the discount factors are linear.
+    #[default]
+    LogLinearDiscount,
+    /// The continuously compounded zero rates are linear.
+    LinearZero,
+    /// The monotone convex method of Hagan and West.
+    MonotoneConvex,
+}
+
+/// The shape of the monotone convex correction `g` on a segment, see \[HaganWest\].
+#[derive(Debug, Clone, Copy)]
+enum Sector {
+    /// A quadratic through `g0` and `g1`
+    Quadratic,
+    /// Flat at `g0` until `eta` and quadratic after
+    FlatThenQuadratic { eta: f64 },
+    /// Quadratic until `eta` and flat at `g1` after
+    QuadraticThenFlat { eta: f64 },
+    /// Quadratics on both sides of the minimum or maximum `a` at `eta`
+    TwoQuadratics { eta: f64, a: f64 },
+}
+
+impl Sector {
+    fn new(g0: f64, g1: f64) -> Sector {
+        if g0 == 0.0
+            || g1 == 0.0
+            || (g0 < 0.0 && -0.5 * g0 <= g1 && g1 <= -2.0 * g0)
+            || (g0 > 0.0 && -2.0 * g0 <= g1 && g1 <= -0.5 * g0)
+        {
+            Sector::Quadratic
+        } else if (g0 < 0.0 && g1 > -2.0 * g0) || (g0 > 0.0 && g1 < -2.0 * g0) {
+            Sector::FlatThenQuadratic {
+                eta: (g1 + 2.0 * g0) / (g1 - g0),
+            }
+        } else if g0.signum() != g1.signum() {
+            Sector::QuadraticThenFlat {
+                eta: 3.0 * g1 / (g1 - g0),
+            }
+        } else {
+            Sector::TwoQuadratics {
+                eta: g1 / (g1 + g0),
+                a: -g0 * g1 / (g0 + g1),
+            }
+        }
+    }
+
+    /// Returns the point where the shape changes within the segment.
+    fn break_point(&self) -> Option<f64> {
+        match *self {
+            Sector::Quadratic => None,
+            Sector::FlatThenQuadratic { eta }
+            | Sector::QuadraticThenFlat { eta }
+            | Sector::TwoQuadratics { eta, .. } => Some(eta),
+        }
+    }
+
+    /// Returns `g(x)` for `x` in `[0, 1]`.
+    fn value(&self, g0: f64, g1: f64, x: f64) -> f64 {
+        match *self {
+            Sector::Quadratic => g0 * (1.0 - 4.0 * x + 3.0 * x * x) + g1 * (-2.0 * x + 3.0 * x * x),
+            Sector::FlatThenQuadratic { eta } => {
+                if x <= eta {
+                    g0
+                } else {
+                    g0 + (g1 - g0) * ((x - eta) / (1.0 - eta)).powi(2)
+                }
+            }
+            Sector::QuadraticThenFlat { eta } => {
+                if x < eta {
+                    g1 + (g0 - g1) * ((eta - x) / eta).powi(2)
+                } else {
+                    g1
+                }
+            }
+            Sector::TwoQuadratics { eta, a } => {
+                if x < eta {
+                    a + (g0 - a) * ((eta - x) / eta).powi(2)
+                } else {
+                    a + (g1 - a) * ((x - eta) / (1.0 - eta)).powi(2)
+                }
+            }
+        }
+    }
+
+    /// Returns the integral of `g` from 0 to `x`.
+    fn integral(&self, g0: f64, g1: f64, x: f64) -> f64 {
+        // The integral of ((eta - y) / eta)^2 from 0 to min(x, eta).
+        let left = |eta: f64| {
+            let y = x.min(eta);
+            (eta.powi(3) - (eta - y).powi(3)) / (3.0 * eta * eta)
+        };
+        // The integral of ((y - eta) / (1 - eta))^2 from eta to max(x, eta).
+        let right = |eta: f64| (x.max(eta) - eta).powi(3) / (3.0 * (1.0 - eta).powi(2));
+        match *self {
+            Sector::Quadratic => g0 * (x - 2.0 * x * x + x.powi(3)) + g1 * (-x * x + x.powi(3)),
+            Sector::FlatThenQuadratic { eta } => g0 * x + (g1 - g0) * right(eta),
+            Sector::QuadraticThenFlat { eta } => g1 * x + (g0 - g1) * left(eta),
+            Sector::TwoQuadratics { eta, a } => {
+                a * x + (g0 - a) * left(eta) + (g1 - a) * right(eta)
+            }
+        }
+    }
+}
+
+/// A yield curve of discount factors interpolated between pillars.
+#[derive(Debug, Clone)]
+pub struct YieldCurve {
+    interpolation: CurveInterpolation,
+    /// The pillar times following 0
+    times: Vec<f64>,
+    /// The logarithms of the discount factors at `times`
+    log_discounts: Vec<f64>,
+    /// The discrete forward rates on the segments between consecutive times
+    discrete_forwards: Vec<f64>,
+    /// The instantaneous forward rates at `times` for the monotone convex method
+    pillar_forwards: Vec<f64>,
+}
+
+impl YieldCurve {
+    /// Creates a curve through the discount factors.
+    ///
+    /// # Arguments
+    ///
+    /// * `pillars` - The pairs of a time and the discount factor at the time, where the factor at 0 is 1
+    /// * `interpolation` - The interpolation between pillars
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `pillars` is empty, the times are not positive and strictly increasing
+    /// or a discount factor is not positive.
+    pub fn from_discount_factors(
+        pillars: &[(f64, f64)],
+        interpolation: CurveInterpolation,
+    ) -> Result<Self, YieldCurveError> {
+        if pillars.is_empty() {
+            return Err(YieldCurveError::NoPillars);
+        }
+        let mut previous = 0.0;
+        for &(time, value) in pillars {
+            if !(time.is_finite() && time > previous) {
+                return Err(YieldCurveError::InvalidTime { previous, time });
+            }
+            if !(value.is_finite() && value > 0.0) {
+                return Err(YieldCurveError::InvalidDiscountFactor { time, value });
+            }
+            previous = time;
+        }
+        let times = std::iter::once(0.0).chain(pillars.iter().map(|pillar| pillar.0));
+        let log_discounts = std::iter::once(0.0).chain(pillars.iter().map(|pillar| pillar.1.ln()));
+        Ok(YieldCurve::from_log_discounts(
+            times.collect(),
+            log_discounts.collect(),
+            interpolation,
+        ))
+    }
+
+    /// Bootstraps the curve reproducing the quotes of `instruments`.
+    ///
+    /// # Arguments
+    ///
+    /// * `instruments` - The quoted instruments, which may be in any order
+    /// * `interpolation` - The interpolation between pillars
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `instruments` is empty, an instrument is invalid,
+    /// two instruments have the same maturity or a discount factor is not found.
+    pub fn bootstrap(
+        instruments: &[RateInstrument],
+        interpolation: CurveInterpolation,
+    ) -> Result<Self, YieldCurveError> {
+        if instruments.is_empty() {
+            return Err(YieldCurveError::NoPillars);
+        }
+        for instrument in instruments {
+            instrument.validate()?;
+        }
+        let mut instruments = instruments.to_vec();
+        instruments.sort_by(|a, b| a.maturity().total_cmp(&b.maturity()));
+        if let Some(pair) = instruments
+            .windows(2)
+            .find(|pair| pair[0].maturity() == pair[1].maturity())
+        {
+            return Err(YieldCurveError::DuplicateMaturity(pair[0].maturity()));
+        }
+        let mut times = vec![0.0];
+        let mut log_discounts = vec![0.0];
+        for instrument in &instruments {
+            times.push(instrument.maturity());
+            log_discounts.push(0.0);
+        }
+        let number_of_sweeps = match interpolation {
+            CurveInterpolation::MonotoneConvex => MAX_SWEEPS,
+            _ => 1,
+        };
+        for sweep in 0..number_of_sweeps {
+            let mut max_change: f64 = 0.0;
+            for (k, instrument) in instruments.iter().enumerate() {
+                let length = times[k + 1] - times[k];
+                let price = |forward: f64| {
+                    let mut trial = log_discounts.clone();
+                    trial[k + 1] = trial[k] - forward * length;
+                    let curve = YieldCurve::from_log_discounts(times.clone(), trial, interpolation);
+                    instrument.implied_quote(&curve)
+                };
+                let solver = Brent::new(-1.0, 2.0, SolverSettings::new(1e-15, 1e-15, 200));
+                let forward = solver.solve(instrument.quote(), price).map_err(|source| {
+                    YieldCurveError::BootstrapFailed {
+                        maturity: instrument.maturity(),
+                        source,
+                    }
+                })?;
+                let log_discount = log_discounts[k] - forward * length;
+                max_change = max_change.max((log_discount - log_discounts[k + 1]).abs());
+                log_discounts[k + 1] = log_discount;
+                if sweep == 0 {
+                    // The flat extrapolation of the forward is the initial guess for the later pillars.
+                    for j in k + 2..times.len() {
+                        log_discounts[j] =
+                            log_discounts[j - 1] - forward * (times[j] - times[j - 1]);
+                    }
+                }
+            }
+            if number_of_sweeps == 1 || max_change < SWEEP_TOLERANCE {
+                return Ok(YieldCurve::from_log_discounts(
+                    times,
+                    log_discounts,
+                    interpolation,
+                ));
+            }
+        }
+        Err(YieldCurveError::NotConverged(MAX_SWEEPS))
+    }
+
+    /// Builds a curve from validated pillars including the time 0.
+    fn from_log_discounts(
+        times: Vec<f64>,
+        log_discounts: Vec<f64>,
+        interpolation: CurveInterpolation,
+    ) -> Self {
+        let n = times.len() - 1;
+        let discrete_forwards: Vec<f64> = (1..=n)
+            .map(|i| (log_discounts[i - 1] - log_discounts[i]) / (times[i] - times[i - 1]))
+            .collect();
+        let mut pillar_forwards = vec![];
+        if interpolation == CurveInterpolation::MonotoneConvex {
+            pillar_forwards = vec![discrete_forwards[0]; n + 1];
+            for i in 1..n {
+                let (left, right) = (times[i] - times[i - 1], times[i + 1] - times[i]);
+                pillar_forwards[i] = (left * discrete_forwards[i]
+                    + right * discrete_forwards[i - 1])
+                    / (left + right);
+            }
+            if n > 1 {
+                pillar_forwards[0] =
+                    discrete_forwards[0] - 0.5 * (pillar_forwards[1] - discrete_forwards[0]);
+                pillar_forwards[n] = discrete_forwards[n - 1]
+                    - 0.5 * (pillar_forwards[n - 1] - discrete_forwards[n - 1]);
+            }
+        }
+        YieldCurve {
+            interpolation,
+            times,
+            log_discounts,
+            discrete_forwards,
+            pillar_forwards,
+        }
+    }
+
+    /// Returns the discount factor from 0 to `time`.
+    pub fn discount_factor(&self, time: f64) -> f64 {
+        self.log_discount(time).exp()
+    }
+
+    /// Returns the continuously compounded zero rate to `time`, which is the forward rate at 0 for `time` 0.
+    pub fn zero_rate(&self, time: f64) -> f64 {
+        if time == 0.0 {
+            self.value_at(0.0)
+        } else {
+            -self.log_discount(time) / time
+        }
+    }
+
+    /// Returns the times of the pillars excluding 0.
+    pub fn pillar_times(&self) -> &[f64] {
+        &self.times[1..]
+    }
+
+    /// Returns the index of the segment containing `time` and the position in it in `[0, 1]`.
+    fn locate(&self, time: f64) -> (usize, f64) {
+        let n = self.times.len() - 1;
+        let i = self.times.partition_point(|&t| t < time).clamp(1, n);
+        let (start, end) = (self.times[i - 1], self.times[i]);
+        (i, ((time - start) / (end - start)).clamp(0.0, 1.0))
+    }
+
+    /// Returns the correction of the monotone convex method at the ends of the `i`-th segment.
+    fn monotone_convex_ends(&self, i: usize) -> (f64, f64) {
+        let discrete = self.discrete_forwards[i - 1];
+        (
+            self.pillar_forwards[i - 1] - discrete,
+            self.pillar_forwards[i] - discrete,
+        )
+    }
+
+    /// Returns the instantaneous forward rate at the position `x` of the `i`-th segment.
+    fn forward_in_segment(&self, i: usize, x: f64) -> f64 {
+        let discrete = self.discrete_forwards[i - 1];
+        match self.interpolation {
+            CurveInterpolation::LogLinearDiscount => discrete,
+            CurveInterpolation::LinearZero => {
+                if i == 1 {
+                    return discrete;
+                }
+                let (start, end) = (self.times[i - 1], self.times[i]);
+                let zero_start = -self.log_discounts[i - 1] / start;
+                let zero_end = -self.log_discounts[i] / end;
+                let slope = (zero_end - zero_start) / (end - start);
+                let time = start + x * (end - start);
+                zero_start + slope * (time - start) + slope * time
+            }
+            CurveInterpolation::MonotoneConvex => {
+                let (g0, g1) = self.monotone_convex_ends(i);
+                discrete + Sector::new(g0, g1).value(g0, g1, x)
+            }
+        }
+    }
+
+    /// Returns the logarithm of the discount factor at the position `x` of the `i`-th segment.
+    fn log_discount_in_segment(&self, i: usize, x: f64) -> f64 {
+        let (start, end) = (self.times[i - 1], self.times[i]);
+        let length = end - start;
+        let discrete = self.discrete_forwards[i - 1];
+        match self.interpolation {
+            CurveInterpolation::LogLinearDiscount => {
+                self.log_discounts[i - 1] - discrete * x * length
+            }
+            CurveInterpolation::LinearZero => {
+                if i == 1 {
+                    return -discrete * x * length;
+                }
+                let zero_start = -self.log_discounts[i - 1] / start;
+                let zero_end = -self.log_discounts[i] / end;
+                let time = start + x * length;
+                -(zero_start + x * (zero_end - zero_start)) * time
+            }
+            CurveInterpolation::MonotoneConvex => {
+                let (g0, g1) = self.monotone_convex_ends(i);
+                let correction = Sector::new(g0, g1).integral(g0, g1, x);
+                self.log_discounts[i - 1] - (discrete * x + correction) * length
+            }
+        }
+    }
+
+    /// Returns the logarithm of the discount factor to `time`.
+    fn log_discount(&self, time: f64) -> f64 {
+        let n = self.times.len() - 1;
+        if time <= 0.0 {
+            -self.forward_in_segment(1, 0.0) * time
+        } else if time >= self.times[n] {
+            self.log_discounts[n] - self.forward_in_segment(n, 1.0) * (time - self.times[n])
+        } else {
+            let (i, x) = self.locate(time);
+            self.log_discount_in_segment(i, x)
+        }
+    }
+
+    /// Returns the times in `(time1, time2)` where the forward rate may not be smooth.
+    fn break_points(&self, time1: f64, time2: f64) -> Vec<f64> {
+        let mut points: Vec<f64> = self.times.clone();
+        if self.interpolation == CurveInterpolation::MonotoneConvex {
+            for i in 1..self.times.len() {
+                let (g0, g1) = self.monotone_convex_ends(i);
+                if let Some(eta) = Sector::new(g0, g1).break_point() {
+                    points.push(self.times[i - 1] + eta * (self.times[i] - self.times[i - 1]));
+                }
+            }
+        }
+        points.retain(|&t| time1 < t && t < time2);
+        points.sort_by(f64::total_cmp);
+        points
+    }
+}
+
+impl Parameters for YieldCurve {
+    /// Returns the instantaneous forward rate at `x`, which is regarded as the short rate.
+    fn value_at(&self, x: f64) -> f64 {
+        let n = self.times.len() - 1;
+        if x <= 0.0 {
+            self.forward_in_segment(1, 0.0)
+        } else if x >= self.times[n] {
+            self.forward_in_segment(n, 1.0)
+        } else {
+            let (i, position) = self.locate(x);
+            self.forward_in_segment(i, position)
+        }
+    }
+
+    fn integral(&self, time1: f64, time2: f64) -> f64 {
+        self.log_discount(time1) - self.log_discount(time2)
+    }
+
+    /// The forward rate is a polynomial of degree at most 2 between break points,
+    /// so that the Gauss-Legendre rule of 3 points is exact.
+    fn integral_square(&self, time1: f64, time2: f64) -> f64 {
+        if time1 > time2 {
+            return -self.integral_square(time2, time1);
+        }
+        const NODES: [f64; 3] = [-0.774_596_669_241_483_4, 0.0, 0.774_596_669_241_483_4];
+        const WEIGHTS: [f64; 3] = [5.0 / 9.0, 8.0 / 9.0, 5.0 / 9.0];
+        let mut points = vec![time1];
+        points.extend(self.break_points(time1, time2));
+        points.push(time2);
+        points
+            .windows(2)
+            .map(|pair| {
+                let (middle, half) = (0.5 * (pair[0] + pair[1]), 0.5 * (pair[1] - pair[0]));
+                NODES
+                    .iter()
+                    .zip(WEIGHTS)
+                    .map(|(node, weight)| weight * self.value_at(middle + half * node).powi(2))
+                    .sum::<f64>()
+                    * half
+            })
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chapter4::parameters::ParametersConstant;
+    use crate::chapter4::parameters_test_support::assert_integrals_match_midpoint;
+    use crate::chapter4::payoff3::{Payoff, PayoffCall};
+    use crate::chapter5::mc_statistics::{StatisticsMC, StatisticsMean};
+    use crate::chapter6::park_miller::RandomParkMiller;
+    use crate::chapter7::exotic_bs_engine::ExoticBSEngine;
+    use crate::chapter7::exotic_engine::{ExoticEngine, ExoticEngineData};
+    use crate::chapter7::path_dependent_asian::PathDependentAsian;
+    use approx::assert_relative_eq;
+
+    const INTERPOLATIONS: [CurveInterpolation; 3] = [
+        CurveInterpolation::LogLinearDiscount,
+        CurveInterpolation::LinearZero,
+        CurveInterpolation::MonotoneConvex,
+    ];
+
+    fn instruments() -> Vec<RateInstrument> {
+        vec![
+            RateInstrument::Swap {
+                maturity: 5.0,
+                rate: 0.032,
+                payments_per_year: 1,
+            },
+            RateInstrument::Deposit {
+                maturity: 0.25,
+                rate: 0.02,
+            },
+            RateInstrument::ForwardRateAgreement {
+                start: 0.25,
+                end: 0.5,
+                rate: 0.022,
+            },
+            RateInstrument::Future {
+                start: 0.5,
+                end: 0.75,
+                price: 97.5,
+            },
+            RateInstrument::Swap {
+                maturity: 2.0,
+                rate: 0.028,
+                payments_per_year: 2,
+            },
+            RateInstrument::Swap {
+                maturity: 10.0,
+                rate: 0.031,
+                payments_per_year: 1,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_bootstrap_reprices_instruments() {
+        for interpolation in INTERPOLATIONS {
+            let curve = YieldCurve::bootstrap(&instruments(), interpolation).unwrap();
+            assert_eq!(curve.pillar_times(), [0.25, 0.5, 0.75, 2.0, 5.0, 10.0]);
+            for instrument in instruments() {
+                assert_relative_eq!(
+                    instrument.implied_quote(&curve),
+                    instrument.quote(),
+                    epsilon = 1e-11
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_interpolations() {
+        let pillars = [(1.0, 0.98), (2.0, 0.95), (5.0, 0.85)];
+        let log_linear =
+            YieldCurve::from_discount_factors(&pillars, CurveInterpolation::LogLinearDiscount)
+                .unwrap();
+        assert_relative_eq!(log_linear.discount_factor(1.5), (0.98_f64 * 0.95).sqrt());
+        assert_relative_eq!(log_linear.value_at(1.5), (0.98_f64 / 0.95).ln());
+        let linear_zero =
+            YieldCurve::from_discount_factors(&pillars, CurveInterpolation::LinearZero).unwrap();
+        let (zero1, zero2) = (-(0.98_f64.ln()), -(0.95_f64.ln()) / 2.0);
+        assert_relative_eq!(linear_zero.zero_rate(1.5), 0.5 * (zero1 + zero2));
+        assert_relative_eq!(linear_zero.zero_rate(0.5), zero1);
+        let monotone_convex =
+            YieldCurve::from_discount_factors(&pillars, CurveInterpolation::MonotoneConvex)
+                .unwrap();
+        for (time, value) in pillars {
+            for curve in [&log_linear, &linear_zero, &monotone_convex] {
+                assert_relative_eq!(curve.discount_factor(time), value, epsilon = 1e-15);
+            }
+            // The monotone convex forward rates are continuous at the pillars.
+            assert_relative_eq!(
+                monotone_convex.value_at(time - 1e-9),
+                monotone_convex.value_at(time + 1e-9),
+                epsilon = 1e-7
+            );
+        }
+        // The flat extrapolation of the forward rate.
+        assert_relative_eq!(
+            log_linear.discount_factor(6.0),
+            0.85 * (0.85_f64 / 0.95).powf(1.0 / 3.0)
+        );
+    }
+
+    #[test]
+    fn test_parameters() {
+        for interpolation in INTERPOLATIONS {
+            let curve = YieldCurve::bootstrap(&instruments(), interpolation).unwrap();
+            assert_relative_eq!(
+                curve.integral(0.0, 3.0),
+                -curve.discount_factor(3.0).ln(),
+                epsilon = 1e-15
+            );
+            // Against the midpoint rule over 12 years, whose grid of 240000 steps contains the pillars,
+            // where the forward rates may jump.
+            assert_integrals_match_midpoint(&curve, 0.0, 12.0, 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_exotic_engine_discounts_off_curve() {
+        let rate: f64 = 0.03;
+        let pillars: Vec<(f64, f64)> = [1.0, 2.0].iter().map(|&t| (t, (-rate * t).exp())).collect();
+        let curve = YieldCurve::from_discount_factors(&pillars, CurveInterpolation::MonotoneConvex)
+            .unwrap();
+        let constant = ParametersConstant::new(rate);
+        let times = [0.5, 1.0];
+        let the_payoff = PayoffCall::new(100.0);
+        let the_option = PathDependentAsian::new(times.to_vec(), 1.0, &the_payoff);
+        let mut prices = vec![];
+        for data in [
+            ExoticEngineData::new(&the_option, &curve),
+            ExoticEngineData::new(&the_option, &constant),
+        ] {
+            let mut the_engine = ExoticBSEngine::new(
+                &times,
+                &curve,
+                ParametersConstant::new(0.0),
+                ParametersConstant::new(0.2),
+                RandomParkMiller::new(2, 1),
+                100.0,
+            );
+            let mut gatherer = StatisticsMean::default();
+            the_engine.do_simulation_reproducible(&data, &mut gatherer, 1000);
+            prices.push(gatherer.get_results_so_far()[0][0]);
+        }
+        assert_relative_eq!(prices[0], prices[1], epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            YieldCurve::bootstrap(&[], CurveInterpolation::default()).unwrap_err(),
+            YieldCurveError::NoPillars
+        );
+        let duplicate = [
+            RateInstrument::Deposit {
+                maturity: 1.0,
+                rate: 0.01,
+            },
+            RateInstrument::ForwardRateAgreement {
+                start: 0.5,
+                end: 1.0,
+                rate: 0.01,
+            },
+        ];
+        assert_eq!(
+            YieldCurve::bootstrap(&duplicate, CurveInterpolation::default()).unwrap_err(),
+            YieldCurveError::DuplicateMaturity(1.0)
+        );
+        assert_eq!(
+            YieldCurve::from_discount_factors(&[(1.0, 0.0)], CurveInterpolation::default())
+                .unwrap_err(),
+            YieldCurveError::InvalidDiscountFactor {
+                time: 1.0,
+                value: 0.0
+            }
+        );
+        assert_eq!(
+            YieldCurve::from_discount_factors(
+                &[(1.0, 0.99), (1.0, 0.98)],
+                CurveInterpolation::default()
+            )
+            .unwrap_err(),
+            YieldCurveError::InvalidTime {
+                previous: 1.0,
+                time: 1.0
+            }
+        );
+    }
+}
@@ -0,0 +1,29 @@
+use crate::chapter9::solver_error::SolverError;
+use std::fmt::Debug;
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum YieldCurveError {
+    #[error("At least one pillar is required")]
+    NoPillars,
+    #[error("The times must be positive, finite and strictly increasing, but got {time} after {previous}")]
+    InvalidTime { previous: f64, time: f64 },
+    #[error("The discount factor at {time} must be positive and finite, but got {value}")]
+    InvalidDiscountFactor { time: f64, value: f64 },
+    #[error("The period must satisfy 0 <= start < end, but got [{start}, {end}]")]
+    InvalidPeriod { start: f64, end: f64 },
+    #[error("The quote {0} must be finite")]
+    InvalidQuote(f64),
+    #[error("The number of payments per year of a swap must be positive")]
+    InvalidFrequency,
+    #[error("Two instruments mature at {0}")]
+    DuplicateMaturity(f64),
+    #[error("Failed to bootstrap the discount factor at {maturity}: {source}")]
+    BootstrapFailed {
+        maturity: f64,
+        #[source]
+        source: SolverError,
+    },
+    #[error("The global bootstrap did not converge within {0} sweeps over the instruments")]
+    NotConverged(usize),
+}
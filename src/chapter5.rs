@@ -1,3 +1,8 @@
 pub mod convergence_table;
 pub mod mc_statistics;
 pub mod simple_mc7;
+pub mod statistics_histogram;
+pub mod statistics_moments;
+pub mod statistics_pair_average;
+pub mod statistics_quantile;
+pub mod statistics_tail_risk;
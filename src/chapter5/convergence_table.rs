@@ -1,3 +1,6 @@
+//! 記録する各時点の統計データは、それまでの全てのパスを順番に取り込んだ時点でしか得られない。
+//! 別々に取り込んだ統計データを結合すると途中の時点の行を失うので、`MergeableStatisticsMC`は実装しない。
+//! `ExoticEngine`では`do_simulation_reproducible`に渡すことで、逐次のシミュレーションと同じ表が得られる。
 use crate::chapter5::mc_statistics::StatisticsMC;
 
 pub struct ConvergenceTable<T: StatisticsMC> {
@@ -47,6 +50,7 @@ impl<T: StatisticsMC> StatisticsMC for ConvergenceTable<T> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::chapter5::mc_statistics::StatisticsVariance;
     use approx::assert_relative_eq;
 
     struct MockStats {
@@ -87,4 +91,22 @@ mod tests {
             assert_relative_eq!(actual[1], expected[1]);
         }
     }
+
+    #[test]
+    fn test_convergence_table_with_variance() {
+        let mut conv_table = ConvergenceTable::new(StatisticsVariance::default());
+        for i in 1..=4 {
+            conv_table.dump_one_result(i as f64);
+        }
+        let results = conv_table.get_results_so_far();
+        assert_eq!(results.len(), 2);
+        // mean, variance, standard error, the confidence interval and the number of paths
+        assert_eq!(results[0].len(), 6);
+        assert_relative_eq!(results[0][0], 1.5);
+        assert_relative_eq!(results[0][1], 0.5);
+        assert_relative_eq!(results[0][5], 2.0);
+        assert_relative_eq!(results[1][0], 2.5);
+        assert_relative_eq!(results[1][2], (5.0_f64 / 12.0).sqrt());
+        assert_relative_eq!(results[1][5], 4.0);
+    }
 }
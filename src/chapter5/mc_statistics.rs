@@ -8,6 +8,9 @@
 //!
 //! PayoffやParameterとは違って統計データはコピーをすることが滅多にないため、
 //! 参照を利用することで、わざわざBridgeパターンを使う必要がない。
+//!
+//! 並列にシミュレーションする場合、一つのオブジェクトをMutexで共有すると毎パスごとにロックを取ることになり、並列化の効果が失われる。
+//! そこでスレッドごとに空のオブジェクトを用意して別々に統計データを取り込み、最後に結合できるようにした。
 
 /// Statistics used in the Monte Carlo method.
 pub trait StatisticsMC: Send + Sync {
@@ -22,6 +25,19 @@ pub trait StatisticsMC: Send + Sync {
     fn get_results_so_far(&self) -> Vec<Vec<f64>>;
 }
 
+/// Statistics which can be gathered separately, e.g. on each thread, and combined afterwards.
+pub trait MergeableStatisticsMC: StatisticsMC + Sized {
+    /// Returns a gatherer with the same settings as `self` and no results.
+    fn empty(&self) -> Self;
+
+    /// Combines the results gathered in `other` into `self`.
+    ///
+    /// # Arguments
+    ///
+    /// * other - A gatherer created by `empty` of `self` or of the same origin.
+    fn merge(&mut self, other: Self);
+}
+
 #[derive(Default)]
 /// For obtaining mean values which equals to `running_sum`/`paths_done`.
 pub struct StatisticsMean {
@@ -43,9 +59,92 @@ impl StatisticsMC for StatisticsMean {
     }
 }
 
+impl MergeableStatisticsMC for StatisticsMean {
+    fn empty(&self) -> Self {
+        StatisticsMean::default()
+    }
+    fn merge(&mut self, other: Self) {
+        self.running_sum += other.running_sum;
+        self.paths_done += other.paths_done;
+    }
+}
+
+/// The 97.5% quantile of the standard normal distribution.
+const NORMAL_QUANTILE_975: f64 = 1.959963984540054;
+
+#[derive(Default)]
+/// For obtaining the mean, the sample variance, the standard error of the mean and its 95% confidence interval.
+///
+/// The results are stored in a row in the order of
+/// `[mean, variance, standard_error, lower_bound, upper_bound]`.
+/// The variance is updated by Welford's algorithm,
+/// which avoids the cancellation of `running_sum_of_squares`/`paths_done` - `mean` * `mean`.
+/// The variance and the standard error are 0 until the second path, where the sample variance is undefined.
+///
+/// The results of the paths must be independent.
+/// Wrap this in `StatisticsPairAverage` for the paths generated by `AntiThetic`.
+pub struct StatisticsVariance {
+    /// The mean of all result so far.
+    mean: f64,
+    /// The sum of squared deviations from `mean`.
+    sum_of_squared_deviations: f64,
+    /// The number of paths so far.
+    paths_done: u64,
+}
+
+impl StatisticsMC for StatisticsVariance {
+    fn dump_one_result(&mut self, result: f64) {
+        self.paths_done += 1;
+        let delta = result - self.mean;
+        self.mean += delta / self.paths_done as f64;
+        self.sum_of_squared_deviations += delta * (result - self.mean);
+    }
+    fn get_results_so_far(&self) -> Vec<Vec<f64>> {
+        let (variance, standard_error) = if self.paths_done < 2 {
+            (0.0, 0.0)
+        } else {
+            let variance = self.sum_of_squared_deviations / (self.paths_done as f64 - 1.0);
+            (variance, (variance / self.paths_done as f64).sqrt())
+        };
+        let half_width = NORMAL_QUANTILE_975 * standard_error;
+        vec![vec![
+            self.mean,
+            variance,
+            standard_error,
+            self.mean - half_width,
+            self.mean + half_width,
+        ]]
+    }
+}
+
+impl MergeableStatisticsMC for StatisticsVariance {
+    fn empty(&self) -> Self {
+        StatisticsVariance::default()
+    }
+    /// Combines the two sets of results by the parallel algorithm of Chan et al.
+    fn merge(&mut self, other: Self) {
+        if other.paths_done == 0 {
+            return;
+        }
+        if self.paths_done == 0 {
+            *self = other;
+            return;
+        }
+        let n_a = self.paths_done as f64;
+        let n_b = other.paths_done as f64;
+        let n = n_a + n_b;
+        let delta = other.mean - self.mean;
+        self.mean += delta * n_b / n;
+        self.sum_of_squared_deviations +=
+            other.sum_of_squared_deviations + delta * delta * n_a * n_b / n;
+        self.paths_done += other.paths_done;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use approx::assert_relative_eq;
 
     #[test]
     fn test_statistics_mean() {
@@ -58,4 +157,74 @@ mod tests {
         let results = stats.get_results_so_far();
         assert_eq!(results, vec![vec![2.0]]);
     }
+
+    #[test]
+    fn test_statistics_variance() {
+        let mut stats = StatisticsVariance::default();
+
+        stats.dump_one_result(1.0);
+        stats.dump_one_result(2.0);
+        stats.dump_one_result(3.0);
+        stats.dump_one_result(4.0);
+
+        let results = stats.get_results_so_far();
+        let standard_error = (5.0_f64 / 12.0).sqrt();
+        assert_eq!(results.len(), 1);
+        assert_relative_eq!(results[0][0], 2.5);
+        assert_relative_eq!(results[0][1], 5.0 / 3.0);
+        assert_relative_eq!(results[0][2], standard_error);
+        assert_relative_eq!(results[0][3], 2.5 - NORMAL_QUANTILE_975 * standard_error);
+        assert_relative_eq!(results[0][4], 2.5 + NORMAL_QUANTILE_975 * standard_error);
+    }
+
+    #[test]
+    fn test_statistics_variance_single_path() {
+        let mut stats = StatisticsVariance::default();
+        stats.dump_one_result(3.0);
+        assert_eq!(
+            stats.get_results_so_far(),
+            vec![vec![3.0, 0.0, 0.0, 3.0, 3.0]]
+        );
+    }
+
+    #[test]
+    fn test_statistics_variance_stability() {
+        // The naive formula loses all digits of the variance for a large offset.
+        let mut stats = StatisticsVariance::default();
+        for i in 0..1000 {
+            stats.dump_one_result(1e9 + (i % 2) as f64);
+        }
+        let results = stats.get_results_so_far();
+        assert_relative_eq!(results[0][1], 0.25 * 1000.0 / 999.0, max_relative = 1e-9);
+    }
+
+    #[test]
+    fn test_merge() {
+        let data = [1.0, 4.0, 2.0, 8.0, 5.0, 7.0, 3.0];
+        let mut mean_all = StatisticsMean::default();
+        let mut variance_all = StatisticsVariance::default();
+        for x in data {
+            mean_all.dump_one_result(x);
+            variance_all.dump_one_result(x);
+        }
+        let mut mean = mean_all.empty();
+        let mut variance = variance_all.empty();
+        for chunk in data.chunks(3) {
+            let mut mean_part = mean.empty();
+            let mut variance_part = variance.empty();
+            for &x in chunk {
+                mean_part.dump_one_result(x);
+                variance_part.dump_one_result(x);
+            }
+            mean.merge(mean_part);
+            variance.merge(variance_part);
+        }
+        assert_eq!(mean.get_results_so_far(), mean_all.get_results_so_far());
+        for (actual, expected) in variance.get_results_so_far()[0]
+            .iter()
+            .zip(&variance_all.get_results_so_far()[0])
+        {
+            assert_relative_eq!(actual, expected, epsilon = 1e-12);
+        }
+    }
 }
@@ -0,0 +1,149 @@
+//! パスごとの割引価値の分布を等幅のビンに分けたヒストグラムとして求める。
+//! 一つのビンを一行とすることで、get_results_so_farの戻り値をそのままテーブルとして扱える。
+use crate::chapter5::mc_statistics::{MergeableStatisticsMC, StatisticsMC};
+
+/// For obtaining the relative frequencies of the results in bins of the same width.
+///
+/// Each bin is stored in a row in the order of `[lower_bound, upper_bound, relative_frequency]`.
+/// The first and the last rows are for the results below `lower` and not below `upper` respectively.
+pub struct StatisticsHistogram {
+    /// The lower end of the bins
+    lower: f64,
+    /// The upper end of the bins
+    upper: f64,
+    /// The number of results in each bin
+    counts: Vec<u64>,
+    /// The number of results below `lower`
+    underflow: u64,
+    /// The number of results not below `upper`
+    overflow: u64,
+    /// The number of paths so far.
+    paths_done: u64,
+}
+
+impl StatisticsHistogram {
+    /// Constructor.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `lower` is not less than `upper` or `number_of_bins` is zero.
+    pub fn new(lower: f64, upper: f64, number_of_bins: usize) -> Self {
+        if lower >= upper || number_of_bins == 0 {
+            panic!(
+                "The histogram needs lower < upper and at least one bin, but got lower = {lower}, upper = {upper} and {number_of_bins} bins."
+            );
+        }
+        StatisticsHistogram {
+            lower,
+            upper,
+            counts: vec![0; number_of_bins],
+            underflow: 0,
+            overflow: 0,
+            paths_done: 0,
+        }
+    }
+
+    fn width(&self) -> f64 {
+        (self.upper - self.lower) / self.counts.len() as f64
+    }
+}
+
+impl StatisticsMC for StatisticsHistogram {
+    fn dump_one_result(&mut self, result: f64) {
+        self.paths_done += 1;
+        if result < self.lower {
+            self.underflow += 1;
+        } else if result >= self.upper {
+            self.overflow += 1;
+        } else {
+            // Rounding may push a result just below `upper` into a non-existent bin.
+            let index =
+                (((result - self.lower) / self.width()) as usize).min(self.counts.len() - 1);
+            self.counts[index] += 1;
+        }
+    }
+    fn get_results_so_far(&self) -> Vec<Vec<f64>> {
+        let paths_done = self.paths_done as f64;
+        let width = self.width();
+        let mut results = Vec::with_capacity(self.counts.len() + 2);
+        results.push(vec![
+            f64::NEG_INFINITY,
+            self.lower,
+            self.underflow as f64 / paths_done,
+        ]);
+        for (i, count) in self.counts.iter().enumerate() {
+            results.push(vec![
+                self.lower + i as f64 * width,
+                self.lower + (i + 1) as f64 * width,
+                *count as f64 / paths_done,
+            ]);
+        }
+        results.push(vec![
+            self.upper,
+            f64::INFINITY,
+            self.overflow as f64 / paths_done,
+        ]);
+        results
+    }
+}
+
+impl MergeableStatisticsMC for StatisticsHistogram {
+    fn empty(&self) -> Self {
+        StatisticsHistogram::new(self.lower, self.upper, self.counts.len())
+    }
+    /// # Panics
+    ///
+    /// Panics if the bins of `other` differ from those of `self`.
+    fn merge(&mut self, other: Self) {
+        if self.lower != other.lower
+            || self.upper != other.upper
+            || self.counts.len() != other.counts.len()
+        {
+            panic!("Histograms with different bins can not be merged.");
+        }
+        for (count, other_count) in self.counts.iter_mut().zip(other.counts) {
+            *count += other_count;
+        }
+        self.underflow += other.underflow;
+        self.overflow += other.overflow;
+        self.paths_done += other.paths_done;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chapter5::convergence_table::ConvergenceTable;
+
+    #[test]
+    fn test_statistics_histogram() {
+        let mut stats = StatisticsHistogram::new(0.0, 1.0, 4);
+        for x in [-1.0, 0.0, 0.1, 0.3, 0.6, 0.7, 0.8, 1.0] {
+            stats.dump_one_result(x);
+        }
+        assert_eq!(
+            stats.get_results_so_far(),
+            vec![
+                vec![f64::NEG_INFINITY, 0.0, 0.125],
+                vec![0.0, 0.25, 0.25],
+                vec![0.25, 0.5, 0.125],
+                vec![0.5, 0.75, 0.25],
+                vec![0.75, 1.0, 0.125],
+                vec![1.0, f64::INFINITY, 0.125],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_with_convergence_table() {
+        let mut table = ConvergenceTable::new(StatisticsHistogram::new(0.0, 1.0, 2));
+        for x in [0.2, 0.7, 0.8, 0.9] {
+            table.dump_one_result(x);
+        }
+        let results = table.get_results_so_far();
+        // Four rows for each of two and four paths.
+        assert_eq!(results.len(), 8);
+        assert_eq!(results[2], vec![0.5, 1.0, 0.5, 2.0]);
+        assert_eq!(results[6], vec![0.5, 1.0, 0.75, 4.0]);
+    }
+}
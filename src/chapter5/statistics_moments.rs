@@ -0,0 +1,151 @@
+//! パスごとの割引価値の分布の形状を把握するために、歪度と尖度を求める。
+//! 中心モーメントを一度に計算しようとするとパスの値を全て保持する必要があるので、
+//! Welford法を高次のモーメントに拡張した逐次更新式を用いる。
+//! See \[Terriberry\].
+//!
+//! 結合には同じ文献にあるPébayの公式を用いる。
+//!
+//! \[Terriberry\] Terriberry, T. B. "Computing Higher-Order Moments Online." (2007).
+use crate::chapter5::mc_statistics::{MergeableStatisticsMC, StatisticsMC};
+
+#[derive(Default)]
+/// For obtaining the mean, the sample variance, the skewness and the excess kurtosis.
+///
+/// The results are stored in a row in the order of `[mean, variance, skewness, excess_kurtosis]`.
+/// The variance, the skewness and the excess kurtosis are 0 until the second path, where they are undefined.
+pub struct StatisticsMoments {
+    /// The mean of all result so far.
+    mean: f64,
+    /// The sum of squared deviations from `mean`.
+    m2: f64,
+    /// The sum of cubed deviations from `mean`.
+    m3: f64,
+    /// The sum of fourth powers of deviations from `mean`.
+    m4: f64,
+    /// The number of paths so far.
+    paths_done: u64,
+}
+
+impl StatisticsMC for StatisticsMoments {
+    fn dump_one_result(&mut self, result: f64) {
+        let n1 = self.paths_done as f64;
+        self.paths_done += 1;
+        let n = self.paths_done as f64;
+        let delta = result - self.mean;
+        let delta_n = delta / n;
+        let delta_n2 = delta_n * delta_n;
+        let term1 = delta * delta_n * n1;
+        self.mean += delta_n;
+        self.m4 += term1 * delta_n2 * (n * n - 3.0 * n + 3.0) + 6.0 * delta_n2 * self.m2
+            - 4.0 * delta_n * self.m3;
+        self.m3 += term1 * delta_n * (n - 2.0) - 3.0 * delta_n * self.m2;
+        self.m2 += term1;
+    }
+    fn get_results_so_far(&self) -> Vec<Vec<f64>> {
+        if self.paths_done < 2 {
+            return vec![vec![self.mean, 0.0, 0.0, 0.0]];
+        }
+        let n = self.paths_done as f64;
+        let variance = self.m2 / (n - 1.0);
+        let skewness = n.sqrt() * self.m3 / self.m2.powf(1.5);
+        let excess_kurtosis = n * self.m4 / (self.m2 * self.m2) - 3.0;
+        vec![vec![self.mean, variance, skewness, excess_kurtosis]]
+    }
+}
+
+impl MergeableStatisticsMC for StatisticsMoments {
+    fn empty(&self) -> Self {
+        StatisticsMoments::default()
+    }
+    fn merge(&mut self, other: Self) {
+        if other.paths_done == 0 {
+            return;
+        }
+        if self.paths_done == 0 {
+            *self = other;
+            return;
+        }
+        let n_a = self.paths_done as f64;
+        let n_b = other.paths_done as f64;
+        let n = n_a + n_b;
+        let delta = other.mean - self.mean;
+        let delta2 = delta * delta;
+        let m4 = self.m4
+            + other.m4
+            + delta2 * delta2 * n_a * n_b * (n_a * n_a - n_a * n_b + n_b * n_b) / (n * n * n)
+            + 6.0 * delta2 * (n_a * n_a * other.m2 + n_b * n_b * self.m2) / (n * n)
+            + 4.0 * delta * (n_a * other.m3 - n_b * self.m3) / n;
+        let m3 = self.m3
+            + other.m3
+            + delta2 * delta * n_a * n_b * (n_a - n_b) / (n * n)
+            + 3.0 * delta * (n_a * other.m2 - n_b * self.m2) / n;
+        self.m2 += other.m2 + delta2 * n_a * n_b / n;
+        self.m3 = m3;
+        self.m4 = m4;
+        self.mean += delta * n_b / n;
+        self.paths_done += other.paths_done;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_statistics_moments() {
+        let data = [2.0, 8.0, 0.0, 4.0, 1.0, 9.0, 9.0, 0.0];
+        let mut stats = StatisticsMoments::default();
+        for x in data {
+            stats.dump_one_result(x);
+        }
+
+        let n = data.len() as f64;
+        let mean = data.iter().sum::<f64>() / n;
+        let central = |k: i32| data.iter().map(|x| (x - mean).powi(k)).sum::<f64>() / n;
+        let results = stats.get_results_so_far();
+        assert_relative_eq!(results[0][0], mean, epsilon = 1e-12);
+        assert_relative_eq!(results[0][1], central(2) * n / (n - 1.0), epsilon = 1e-12);
+        assert_relative_eq!(
+            results[0][2],
+            central(3) / central(2).powf(1.5),
+            epsilon = 1e-12
+        );
+        assert_relative_eq!(
+            results[0][3],
+            central(4) / (central(2) * central(2)) - 3.0,
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
+    fn test_less_than_two_paths() {
+        let mut stats = StatisticsMoments::default();
+        assert_eq!(stats.get_results_so_far(), vec![vec![0.0, 0.0, 0.0, 0.0]]);
+        stats.dump_one_result(3.0);
+        assert_eq!(stats.get_results_so_far(), vec![vec![3.0, 0.0, 0.0, 0.0]]);
+    }
+
+    #[test]
+    fn test_merge() {
+        let data = [2.0, 8.0, 0.0, 4.0, 1.0, 9.0, 9.0, 0.0];
+        let mut all = StatisticsMoments::default();
+        let mut first = all.empty();
+        let mut second = all.empty();
+        for (i, x) in data.into_iter().enumerate() {
+            all.dump_one_result(x);
+            if i < 3 {
+                first.dump_one_result(x);
+            } else {
+                second.dump_one_result(x);
+            }
+        }
+        first.merge(second);
+        for (actual, expected) in first.get_results_so_far()[0]
+            .iter()
+            .zip(&all.get_results_so_far()[0])
+        {
+            assert_relative_eq!(actual, expected, epsilon = 1e-12);
+        }
+    }
+}
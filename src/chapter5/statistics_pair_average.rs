@@ -0,0 +1,103 @@
+//! anti-thetic法の二つのパスの結果は負の相関を持つので、そのまま独立なパスとして分散を求めると標準誤差を誤る。
+//! デコレーターパターンを用いて、連続する二つのパスの結果の平均を一つの独立な結果として内側の統計データに渡す。
+//! 平均の推定値は変わらず、分散と標準誤差はペアの平均を標本として正しく求まる。
+//!
+//! 対になっていない最後のパスは次の結果が来るまで保持し、統計データには含めない。
+//! 並列に取り込んだ後に結合する場合は、対になっていない結果同士を平均する。
+//! `do_simulation`のブロックは偶数個のパスからなるので、対になっていない結果は最後のブロックにしか生じない。
+use crate::chapter5::mc_statistics::{MergeableStatisticsMC, StatisticsMC};
+
+/// Passes the average of each pair of consecutive results to the inner statistics,
+/// implemented in the decorator pattern.
+pub struct StatisticsPairAverage<T: StatisticsMC> {
+    inner: T,
+    /// The first result of the pair waiting for the second one.
+    unpaired: Option<f64>,
+}
+
+impl<T: StatisticsMC> StatisticsPairAverage<T> {
+    /// Constructor.
+    ///
+    /// # Arguments
+    ///
+    /// * `inner` - The statistics of the averages of pairs
+    pub fn new(inner: T) -> Self {
+        StatisticsPairAverage {
+            inner,
+            unpaired: None,
+        }
+    }
+}
+
+impl<T: StatisticsMC> StatisticsMC for StatisticsPairAverage<T> {
+    fn dump_one_result(&mut self, result: f64) {
+        match self.unpaired.take() {
+            Some(first) => self.inner.dump_one_result(0.5 * (first + result)),
+            None => self.unpaired = Some(result),
+        }
+    }
+    /// Gets the statistic results of the pairs completed so far.
+    fn get_results_so_far(&self) -> Vec<Vec<f64>> {
+        self.inner.get_results_so_far()
+    }
+}
+
+impl<T: MergeableStatisticsMC> MergeableStatisticsMC for StatisticsPairAverage<T> {
+    fn empty(&self) -> Self {
+        StatisticsPairAverage::new(self.inner.empty())
+    }
+    fn merge(&mut self, other: Self) {
+        self.inner.merge(other.inner);
+        if let Some(result) = other.unpaired {
+            self.dump_one_result(result);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chapter5::mc_statistics::StatisticsVariance;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_pair_average() {
+        let mut stats = StatisticsPairAverage::new(StatisticsVariance::default());
+        for result in [1.0, 3.0, 2.0, 6.0, 6.0] {
+            stats.dump_one_result(result);
+        }
+        // The pairs average to 2.0 and 4.0, and the last result is not paired yet.
+        let results = stats.get_results_so_far();
+        assert_relative_eq!(results[0][0], 3.0);
+        assert_relative_eq!(results[0][1], 2.0);
+        assert_relative_eq!(results[0][2], 1.0);
+
+        let mut other = stats.empty();
+        other.dump_one_result(0.0);
+        stats.merge(other);
+        let results = stats.get_results_so_far();
+        // The last result is paired with the unpaired one of `other` to 3.0.
+        assert_relative_eq!(results[0][0], 3.0);
+        assert_relative_eq!(results[0][1], 1.0);
+    }
+
+    #[test]
+    fn test_anti_thetic_standard_error() {
+        // Antithetic pairs of a monotone function are negatively correlated,
+        // so the standard error is smaller than that of the paths regarded as independent.
+        let mut pairs = StatisticsPairAverage::new(StatisticsVariance::default());
+        let mut paths = StatisticsVariance::default();
+        for i in 0..1000 {
+            let u = (i as f64 + 0.5) / 1000.0;
+            for x in [u, 1.0 - u] {
+                let result = x.exp();
+                pairs.dump_one_result(result);
+                paths.dump_one_result(result);
+            }
+        }
+        let pairs = pairs.get_results_so_far();
+        let paths = paths.get_results_so_far();
+        assert_relative_eq!(pairs[0][0], paths[0][0], max_relative = 1e-12);
+        assert!(pairs[0][2] < 0.5 * paths[0][2]);
+    }
+}
@@ -0,0 +1,181 @@
+//! パスごとの割引価値の分位点を求める。
+//! 全てのパスの値を保持してソートするとメモリと時間がかかるので、
+//! 5つのマーカーの高さを区分的放物線補間で逐次更新するP²アルゴリズムを用いる。
+//! See \[JainChlamtac\].
+//!
+//! マーカーは別々に取り込んだ結果を正確に結合できないので、`MergeableStatisticsMC`は実装しない。
+//! `ExoticEngine`では全てのパスの結果を順番に取り込む`do_simulation_reproducible`のみに対応し、`do_simulation`には渡せない。
+//!
+//! \[JainChlamtac\] Jain, R. and Chlamtac, I. "The P² algorithm for dynamic calculation of quantiles and histograms without storing observations." Commun. ACM 28 (1985): 1076-1085.
+use crate::chapter5::mc_statistics::StatisticsMC;
+
+/// For obtaining an estimate of the `probability`-quantile by the P² algorithm.
+///
+/// The results are stored in a row as `[quantile]`.
+/// The markers can not be combined exactly,
+/// so this does not implement `MergeableStatisticsMC` and needs results in a single sequence,
+/// e.g. by `ExoticEngine::do_simulation_reproducible`.
+pub struct StatisticsQuantile {
+    /// The probability of the quantile
+    probability: f64,
+    /// The heights of the markers
+    heights: [f64; 5],
+    /// The actual positions of the markers
+    positions: [f64; 5],
+    /// The desired positions of the markers
+    desired_positions: [f64; 5],
+    /// The increments of the desired positions per path
+    increments: [f64; 5],
+    /// The number of paths so far.
+    paths_done: u64,
+}
+
+impl StatisticsQuantile {
+    /// Constructor.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `probability` is not within the interval (0, 1).
+    pub fn new(probability: f64) -> Self {
+        if !(0.0 < probability && probability < 1.0) {
+            panic!(
+                "The probability of a quantile must be within the interval (0, 1), but got {value}.",
+                value = probability
+            );
+        }
+        StatisticsQuantile {
+            probability,
+            heights: [0.0; 5],
+            positions: [1.0, 2.0, 3.0, 4.0, 5.0],
+            desired_positions: [
+                1.0,
+                1.0 + 2.0 * probability,
+                1.0 + 4.0 * probability,
+                3.0 + 2.0 * probability,
+                5.0,
+            ],
+            increments: [
+                0.0,
+                0.5 * probability,
+                probability,
+                0.5 * (1.0 + probability),
+                1.0,
+            ],
+            paths_done: 0,
+        }
+    }
+
+    /// Returns the height of the `i`-th marker interpolated by a parabola through its neighbours.
+    fn parabolic(&self, i: usize, d: f64) -> f64 {
+        let (q, n) = (&self.heights, &self.positions);
+        q[i] + d / (n[i + 1] - n[i - 1])
+            * ((n[i] - n[i - 1] + d) * (q[i + 1] - q[i]) / (n[i + 1] - n[i])
+                + (n[i + 1] - n[i] - d) * (q[i] - q[i - 1]) / (n[i] - n[i - 1]))
+    }
+
+    /// Returns the height of the `i`-th marker interpolated linearly towards the neighbour in the direction `d`.
+    fn linear(&self, i: usize, d: f64) -> f64 {
+        let j = if d > 0.0 { i + 1 } else { i - 1 };
+        let (q, n) = (&self.heights, &self.positions);
+        q[i] + d * (q[j] - q[i]) / (n[j] - n[i])
+    }
+}
+
+impl StatisticsMC for StatisticsQuantile {
+    fn dump_one_result(&mut self, result: f64) {
+        self.paths_done += 1;
+        if self.paths_done <= 5 {
+            let count = self.paths_done as usize;
+            self.heights[count - 1] = result;
+            self.heights[..count].sort_by(f64::total_cmp);
+            return;
+        }
+        let k = if result < self.heights[0] {
+            self.heights[0] = result;
+            0
+        } else if result >= self.heights[4] {
+            self.heights[4] = result;
+            3
+        } else {
+            (1..5).find(|&i| result < self.heights[i]).unwrap() - 1
+        };
+        for position in self.positions.iter_mut().skip(k + 1) {
+            *position += 1.0;
+        }
+        for (desired, increment) in self.desired_positions.iter_mut().zip(self.increments) {
+            *desired += increment;
+        }
+        for i in 1..4 {
+            let d = self.desired_positions[i] - self.positions[i];
+            if (d >= 1.0 && self.positions[i + 1] - self.positions[i] > 1.0)
+                || (d <= -1.0 && self.positions[i - 1] - self.positions[i] < -1.0)
+            {
+                let d = d.signum();
+                let candidate = self.parabolic(i, d);
+                self.heights[i] =
+                    if self.heights[i - 1] < candidate && candidate < self.heights[i + 1] {
+                        candidate
+                    } else {
+                        self.linear(i, d)
+                    };
+                self.positions[i] += d;
+            }
+        }
+    }
+    fn get_results_so_far(&self) -> Vec<Vec<f64>> {
+        if self.paths_done == 0 {
+            return vec![vec![f64::NAN]];
+        }
+        if self.paths_done <= 5 {
+            // The exact quantile of the few observations sorted in `heights`.
+            let count = self.paths_done as usize;
+            let index = ((count as f64 * self.probability).ceil() as usize).clamp(1, count) - 1;
+            return vec![vec![self.heights[index]]];
+        }
+        vec![vec![self.heights[2]]]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chapter6::normals::inverse_cumulative_normal;
+    use crate::chapter6::park_miller::RandomParkMiller;
+    use crate::chapter6::random2::Random;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_statistics_quantile() {
+        let n = 100000;
+        let mut generator = RandomParkMiller::new(n, 1);
+        let mut variates = vec![0.0; n];
+        generator.get_gaussians(&mut variates);
+        for probability in [0.01, 0.25, 0.5, 0.95] {
+            let mut stats = StatisticsQuantile::new(probability);
+            for &x in &variates {
+                stats.dump_one_result(x);
+            }
+            let results = stats.get_results_so_far();
+            assert_relative_eq!(
+                results[0][0],
+                inverse_cumulative_normal(probability).unwrap(),
+                epsilon = 2e-2
+            );
+        }
+    }
+
+    #[test]
+    fn test_few_observations() {
+        let mut stats = StatisticsQuantile::new(0.5);
+        stats.dump_one_result(3.0);
+        stats.dump_one_result(1.0);
+        stats.dump_one_result(2.0);
+        assert_eq!(stats.get_results_so_far(), vec![vec![2.0]]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_invalid_probability() {
+        StatisticsQuantile::new(1.0);
+    }
+}
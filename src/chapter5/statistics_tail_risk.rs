@@ -0,0 +1,111 @@
+//! パスごとの割引価値を損益とみなし、その下側の裾からValue-at-RiskとExpected Shortfallを求める。
+//! Expected Shortfallは分位点より先の値の平均なので、分位点が確定するまでどの値を平均すべきかわからない。
+//! よってP²アルゴリズムのような逐次的な推定は用いず、パスの値を全て保持して結果の取得時にソートする。
+//! メモリはパス数に比例し、1パスあたり8バイトを要する。例えば1億パスでは約800MBになるので、
+//! パス数が多い場合は`StatisticsQuantile`で分位点のみを求めることも検討する。
+use crate::chapter5::mc_statistics::{MergeableStatisticsMC, StatisticsMC};
+
+/// For obtaining the Value-at-Risk and the Expected Shortfall at `confidence_level`.
+///
+/// Losses are the negatives of the results,
+/// so that the Value-at-Risk is the `confidence_level`-quantile of the losses
+/// and the Expected Shortfall is the mean of the losses not less than the Value-at-Risk.
+/// The results are stored in a row in the order of `[value_at_risk, expected_shortfall]`.
+/// All results are kept, so the memory grows linearly with the number of paths.
+pub struct StatisticsTailRisk {
+    /// The confidence level such as 0.99
+    confidence_level: f64,
+    /// All results so far
+    results: Vec<f64>,
+}
+
+impl StatisticsTailRisk {
+    /// Constructor.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `confidence_level` is not within the interval (0, 1).
+    pub fn new(confidence_level: f64) -> Self {
+        if !(0.0 < confidence_level && confidence_level < 1.0) {
+            panic!(
+                "The confidence level must be within the interval (0, 1), but got {value}.",
+                value = confidence_level
+            );
+        }
+        StatisticsTailRisk {
+            confidence_level,
+            results: Vec::new(),
+        }
+    }
+}
+
+impl StatisticsMC for StatisticsTailRisk {
+    fn dump_one_result(&mut self, result: f64) {
+        self.results.push(result);
+    }
+    fn get_results_so_far(&self) -> Vec<Vec<f64>> {
+        if self.results.is_empty() {
+            return vec![vec![f64::NAN, f64::NAN]];
+        }
+        let mut sorted = self.results.clone();
+        sorted.sort_by(f64::total_cmp);
+        // A product such as 100 * 0.29 = 28.999999999999996 is snapped to the nearest integer before the floor.
+        let product = sorted.len() as f64 * self.confidence_level;
+        let nearest = product.round();
+        let number_in_body = if (product - nearest).abs() <= 1e-9 * nearest.max(1.0) {
+            nearest
+        } else {
+            product.floor()
+        } as usize;
+        let number_in_tail = (sorted.len() - number_in_body).max(1);
+        let tail = &sorted[..number_in_tail];
+        let value_at_risk = -tail[number_in_tail - 1];
+        let expected_shortfall = -tail.iter().sum::<f64>() / number_in_tail as f64;
+        vec![vec![value_at_risk, expected_shortfall]]
+    }
+}
+
+impl MergeableStatisticsMC for StatisticsTailRisk {
+    fn empty(&self) -> Self {
+        StatisticsTailRisk::new(self.confidence_level)
+    }
+    fn merge(&mut self, mut other: Self) {
+        self.results.append(&mut other.results);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_statistics_tail_risk() {
+        let mut stats = StatisticsTailRisk::new(0.95);
+        for i in 1..=100 {
+            stats.dump_one_result(i as f64);
+        }
+        let results = stats.get_results_so_far();
+        // The five worst results are 1, 2, 3, 4 and 5.
+        assert_relative_eq!(results[0][0], -5.0);
+        assert_relative_eq!(results[0][1], -3.0);
+    }
+
+    #[test]
+    fn test_inexact_confidence_level() {
+        // 100 * 0.29 is slightly less than 29 in floating point, but the tail still has 71 results.
+        let mut stats = StatisticsTailRisk::new(0.29);
+        for i in 1..=100 {
+            stats.dump_one_result(i as f64);
+        }
+        let results = stats.get_results_so_far();
+        assert_relative_eq!(results[0][0], -71.0);
+        assert_relative_eq!(results[0][1], -36.0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_invalid_confidence_level() {
+        StatisticsTailRisk::new(0.0);
+    }
+}
@@ -1,5 +1,18 @@
 pub mod anti_thetic;
+pub mod batch_sampling;
+pub mod gaussian_transform;
+pub mod halton;
+pub mod lattice;
+pub mod mrg32k3a;
+pub mod multivariate_normals;
 pub mod normals;
+pub mod normals_error;
 pub mod park_miller;
+pub mod pcg;
+pub mod philox;
+pub mod quality_tests;
+pub mod randomized_qmc;
 pub mod random2;
 pub mod simple_mc8;
+pub mod sobol;
+pub mod sobol_error;
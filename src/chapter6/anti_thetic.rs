@@ -37,12 +37,14 @@ impl<T: Random> Random for AntiThetic<T> {
             self.odd_even = true;
         }
     }
+    /// Skips pairs of paths by the inner generator, so that this is as fast as the `skip` of the inner generator.
     fn skip(&mut self, mut number_of_paths: usize) {
         if number_of_paths == 0 {
             return;
         }
-        if self.odd_even {
-            self.odd_even = false;
+        if !self.odd_even {
+            // The next path is the antithetic one of the last path.
+            self.odd_even = true;
             number_of_paths -= 1;
         }
         self.generator.skip(number_of_paths / 2);
@@ -65,3 +67,48 @@ impl<T: Random> Random for AntiThetic<T> {
         self.generator.reset_dimensionality(new_dimensionality);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chapter6::park_miller::RandomParkMiller;
+
+    #[test]
+    fn test_anti_thetic() {
+        let mut generator = AntiThetic::new(RandomParkMiller::new(3, 1));
+        let mut first = vec![0.0; 3];
+        let mut second = vec![0.0; 3];
+        generator.get_uniforms(&mut first);
+        generator.get_uniforms(&mut second);
+        for (x, y) in first.iter().zip(&second) {
+            assert_eq!(x + y, 1.0);
+        }
+    }
+
+    #[test]
+    fn test_skip() {
+        let generator = AntiThetic::new(RandomParkMiller::new(3, 1));
+        let mut sequential = generator.clone();
+        let paths: Vec<Vec<f64>> = (0..7)
+            .map(|_| {
+                let mut variates = vec![0.0; 3];
+                sequential.get_uniforms(&mut variates);
+                variates
+            })
+            .collect();
+        for (number_of_paths, expected) in paths.iter().enumerate() {
+            let mut skipped = generator.clone();
+            skipped.skip(number_of_paths);
+            let mut variates = vec![0.0; 3];
+            skipped.get_uniforms(&mut variates);
+            assert_eq!(&variates, expected);
+        }
+        // Skips from the middle of a pair.
+        let mut skipped = generator.clone();
+        let mut variates = vec![0.0; 3];
+        skipped.get_uniforms(&mut variates);
+        skipped.skip(4);
+        skipped.get_uniforms(&mut variates);
+        assert_eq!(variates, paths[5]);
+    }
+}
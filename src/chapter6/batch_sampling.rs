@@ -0,0 +1,519 @@
+//! AntiTheticと同じくデコレーターパターンで、パスの組をまとめて作る分散減少法を任意の乱数生成に適用できるようにした。
+//! - モーメントマッチング: 正規乱数を各次元ごとに標本平均0、標本分散1となるように平行移動と拡大縮小をする。
+//! - 層別サンプリング: 1次元目の\[0,1\)をパスの数の層に等分し、各層から一つずつ一様乱数を取る。
+//! - ラテン超方格サンプリング: 全ての次元をそれぞれ層別にし、層の組み合わせを次元ごとの置換でばらばらにする。
+//!
+//! いずれもパスの組の全体を見て値を決めるので、`batch_size`本のパスを一度に作って順に返す。
+//! 組の境界は元の乱数生成のパスの位置で決まるので、skipは元の乱数生成を組の単位でskipし、途中の組だけを作り直せばよい。
+//! そのため`ExoticEngine::do_simulation`のようにブロックごとに乱数生成を複製してskipしても、逐次に生成した場合と同じパスが得られる。
+//!
+//! 層の置換は元の乱数生成から追加で引いた一様乱数の順位で決める。
+//! 組の途中までしか使わなかった場合も各パスは一様分布に従うので、最後の組が欠けても推定量に偏りは生じない。
+//! ただしモーメントマッチングは組の中のパスを独立でなくするので、組が欠けると僅かに偏りが生じ、組の大きさに反比例して小さくなる。
+use crate::chapter6::normals::cumulative_normal;
+use crate::chapter6::random2::Random;
+
+/// Generates paths in batches of a fixed number of paths drawn from an inner generator.
+#[derive(Clone)]
+struct PathBatch<T: Random> {
+    generator: T,
+    batch_size: usize,
+    dimensionality: usize,
+    /// The values of the paths in the current batch, stored path by path
+    values: Vec<f64>,
+    /// The number of paths returned since the last reset
+    path_index: usize,
+}
+
+impl<T: Random> PathBatch<T> {
+    /// # Panics
+    ///
+    /// Panics if `batch_size` is zero.
+    fn new(generator: T, batch_size: usize, dimensionality: usize) -> PathBatch<T> {
+        if batch_size == 0 {
+            panic!("The batch size must be positive.");
+        }
+        PathBatch {
+            generator,
+            batch_size,
+            dimensionality,
+            values: vec![0.0; batch_size * dimensionality],
+            path_index: 0,
+        }
+    }
+
+    /// Copies the next path into `variates`, filling a new batch by `fill` at the start of each batch.
+    fn next_path(&mut self, variates: &mut [f64], fill: impl FnOnce(&mut T, &mut [f64])) {
+        let position = self.path_index % self.batch_size;
+        if position == 0 {
+            fill(&mut self.generator, &mut self.values);
+        }
+        let start = position * self.dimensionality;
+        variates.copy_from_slice(&self.values[start..start + self.dimensionality]);
+        self.path_index += 1;
+    }
+
+    /// Skips paths by skipping whole batches of the inner generator and refilling the batch to resume from.
+    fn skip(&mut self, number_of_paths: usize, fill: impl FnOnce(&mut T, &mut [f64])) {
+        if number_of_paths == 0 {
+            return;
+        }
+        let batches_drawn = self.path_index.div_ceil(self.batch_size);
+        let target = self.path_index + number_of_paths;
+        let batch = target / self.batch_size;
+        if target.is_multiple_of(self.batch_size) {
+            self.generator
+                .skip((batch - batches_drawn) * self.batch_size);
+        } else if batch + 1 != batches_drawn {
+            self.generator
+                .skip((batch - batches_drawn) * self.batch_size);
+            fill(&mut self.generator, &mut self.values);
+        }
+        self.path_index = target;
+    }
+
+    fn set_seed(&mut self, seed: u64) {
+        self.generator.set_seed(seed);
+        self.path_index = 0;
+    }
+
+    fn reset(&mut self) {
+        self.generator.reset();
+        self.path_index = 0;
+    }
+
+    /// Updates the dimensionality of paths, which draw `number_of_uniforms` variables from the inner generator.
+    fn reset_dimensionality(&mut self, new_dimensionality: usize, number_of_uniforms: usize) {
+        self.dimensionality = new_dimensionality;
+        self.values
+            .resize(self.batch_size * new_dimensionality, 0.0);
+        self.generator.reset_dimensionality(number_of_uniforms);
+        self.path_index = 0;
+    }
+}
+
+/// Sets `variates` to `(strata[i] + jitters[i]) / batch_size`, where the strata are the ranks of `keys`.
+fn stratify(variates: &mut [f64], jitters: &[f64], keys: &[f64]) {
+    let batch_size = variates.len();
+    let mut order: Vec<usize> = (0..batch_size).collect();
+    order.sort_by(|&i, &j| keys[i].total_cmp(&keys[j]));
+    for (stratum, &i) in order.iter().enumerate() {
+        variates[i] = (stratum as f64 + jitters[i]) / batch_size as f64;
+    }
+}
+
+/// Moment matching implemented in the decorator pattern.
+///
+/// Each dimension of the Gaussian variables in a batch is shifted and scaled to have the sample mean 0
+/// and the sample variance 1, where the variance is divided by the batch size.
+/// The uniforms are the cumulative normal function of the matched Gaussian variables.
+#[derive(Clone)]
+pub struct MomentMatching<T: Random> {
+    batch: PathBatch<T>,
+}
+
+impl<T: Random> MomentMatching<T> {
+    /// Constructor.
+    ///
+    /// # Arguments
+    ///
+    /// * `generator` - The generator of the Gaussian variables to be matched
+    /// * `batch_size` - The number of paths whose moments are matched together
+    ///
+    /// # Panics
+    ///
+    /// Panics if `batch_size` is less than 2.
+    pub fn new(generator: T, batch_size: usize) -> MomentMatching<T> {
+        if batch_size < 2 {
+            panic!("The batch size for moment matching must be at least 2, but got {batch_size}.");
+        }
+        let dimensionality = generator.get_dimensionality();
+        MomentMatching {
+            batch: PathBatch::new(generator, batch_size, dimensionality),
+        }
+    }
+
+    /// Returns the closure filling a batch with matched Gaussian variables.
+    fn fill(dimensionality: usize) -> impl FnOnce(&mut T, &mut [f64]) {
+        move |generator, values| {
+            for path in values.chunks_exact_mut(dimensionality) {
+                generator.get_gaussians(path);
+            }
+            let batch_size = values.len() / dimensionality;
+            for j in 0..dimensionality {
+                let (sum, sum_of_squares) = values
+                    .iter()
+                    .skip(j)
+                    .step_by(dimensionality)
+                    .fold((0.0, 0.0), |(s, s2), x| (s + x, s2 + x * x));
+                let mean = sum / batch_size as f64;
+                let standard_deviation = (sum_of_squares / batch_size as f64 - mean * mean).sqrt();
+                for x in values.iter_mut().skip(j).step_by(dimensionality) {
+                    *x = (*x - mean) / standard_deviation;
+                }
+            }
+        }
+    }
+}
+
+impl<T: Random> Random for MomentMatching<T> {
+    fn get_dimensionality(&self) -> usize {
+        self.batch.dimensionality
+    }
+    fn get_uniforms(&mut self, variates: &mut [f64]) {
+        self.get_gaussians(variates);
+        for variate in variates {
+            *variate = cumulative_normal(*variate);
+        }
+    }
+    fn get_gaussians(&mut self, variates: &mut [f64]) {
+        let fill = Self::fill(self.batch.dimensionality);
+        self.batch.next_path(variates, fill);
+    }
+    fn skip(&mut self, number_of_paths: usize) {
+        let fill = Self::fill(self.batch.dimensionality);
+        self.batch.skip(number_of_paths, fill);
+    }
+    fn set_seed(&mut self, seed: u64) {
+        self.batch.set_seed(seed);
+    }
+    fn reset(&mut self) {
+        self.batch.reset();
+    }
+    fn reset_dimensionality(&mut self, new_dimensionality: usize) {
+        self.batch
+            .reset_dimensionality(new_dimensionality, new_dimensionality);
+    }
+}
+
+/// Stratified sampling of the first dimension implemented in the decorator pattern.
+///
+/// Each batch takes one first uniform from each of `batch_size` strata of equal length in a random order.
+/// The other dimensions are passed through. The inner generator draws one more uniform per path for the order.
+#[derive(Clone)]
+pub struct Stratified<T: Random> {
+    batch: PathBatch<T>,
+}
+
+impl<T: Random> Stratified<T> {
+    /// Constructor.
+    ///
+    /// # Arguments
+    ///
+    /// * `generator` - The generator of the uniforms in each stratum
+    /// * `batch_size` - The number of strata
+    ///
+    /// # Panics
+    ///
+    /// Panics if `batch_size` is zero.
+    pub fn new(mut generator: T, batch_size: usize) -> Stratified<T> {
+        let dimensionality = generator.get_dimensionality();
+        generator.reset_dimensionality(dimensionality + 1);
+        Stratified {
+            batch: PathBatch::new(generator, batch_size, dimensionality),
+        }
+    }
+
+    /// Returns the closure filling a batch with uniforms stratified in the first dimension.
+    fn fill(dimensionality: usize) -> impl FnOnce(&mut T, &mut [f64]) {
+        move |generator, values| {
+            let batch_size = values.len() / dimensionality.max(1);
+            let mut draws = vec![0.0; dimensionality + 1];
+            let mut jitters = vec![0.0; batch_size];
+            let mut keys = vec![0.0; batch_size];
+            for i in 0..batch_size {
+                generator.get_uniforms(&mut draws);
+                values[i * dimensionality..(i + 1) * dimensionality]
+                    .copy_from_slice(&draws[..dimensionality]);
+                jitters[i] = draws.first().copied().unwrap_or_default();
+                keys[i] = draws[dimensionality];
+            }
+            if dimensionality > 0 {
+                let mut first = vec![0.0; batch_size];
+                stratify(&mut first, &jitters, &keys);
+                for (path, u) in values.chunks_exact_mut(dimensionality).zip(first) {
+                    path[0] = u;
+                }
+            }
+        }
+    }
+}
+
+impl<T: Random> Random for Stratified<T> {
+    fn get_dimensionality(&self) -> usize {
+        self.batch.dimensionality
+    }
+    fn get_uniforms(&mut self, variates: &mut [f64]) {
+        let fill = Self::fill(self.batch.dimensionality);
+        self.batch.next_path(variates, fill);
+    }
+    fn skip(&mut self, number_of_paths: usize) {
+        let fill = Self::fill(self.batch.dimensionality);
+        self.batch.skip(number_of_paths, fill);
+    }
+    fn set_seed(&mut self, seed: u64) {
+        self.batch.set_seed(seed);
+    }
+    fn reset(&mut self) {
+        self.batch.reset();
+    }
+    fn reset_dimensionality(&mut self, new_dimensionality: usize) {
+        self.batch
+            .reset_dimensionality(new_dimensionality, new_dimensionality + 1);
+    }
+}
+
+/// Latin hypercube sampling implemented in the decorator pattern.
+///
+/// Each dimension of a batch takes one uniform from each of `batch_size` strata of equal length,
+/// and the strata are ordered by an independent random permutation in each dimension.
+/// The inner generator draws twice the dimensionality per path, the latter half for the permutations.
+#[derive(Clone)]
+pub struct LatinHypercube<T: Random> {
+    batch: PathBatch<T>,
+}
+
+impl<T: Random> LatinHypercube<T> {
+    /// Constructor.
+    ///
+    /// # Arguments
+    ///
+    /// * `generator` - The generator of the uniforms in each stratum
+    /// * `batch_size` - The number of strata in each dimension
+    ///
+    /// # Panics
+    ///
+    /// Panics if `batch_size` is zero.
+    pub fn new(mut generator: T, batch_size: usize) -> LatinHypercube<T> {
+        let dimensionality = generator.get_dimensionality();
+        generator.reset_dimensionality(2 * dimensionality);
+        LatinHypercube {
+            batch: PathBatch::new(generator, batch_size, dimensionality),
+        }
+    }
+
+    /// Returns the closure filling a batch with a Latin hypercube sample.
+    fn fill(dimensionality: usize) -> impl FnOnce(&mut T, &mut [f64]) {
+        move |generator, values| {
+            let batch_size = values.len() / dimensionality.max(1);
+            let mut draws = vec![0.0; 2 * dimensionality * batch_size];
+            for path in draws.chunks_exact_mut(2 * dimensionality) {
+                generator.get_uniforms(path);
+            }
+            let mut column = vec![0.0; batch_size];
+            for j in 0..dimensionality {
+                let jitters: Vec<f64> = draws
+                    .iter()
+                    .skip(j)
+                    .step_by(2 * dimensionality)
+                    .copied()
+                    .collect();
+                let keys: Vec<f64> = draws
+                    .iter()
+                    .skip(dimensionality + j)
+                    .step_by(2 * dimensionality)
+                    .copied()
+                    .collect();
+                stratify(&mut column, &jitters, &keys);
+                for (x, u) in values
+                    .iter_mut()
+                    .skip(j)
+                    .step_by(dimensionality)
+                    .zip(&column)
+                {
+                    *x = *u;
+                }
+            }
+        }
+    }
+}
+
+impl<T: Random> Random for LatinHypercube<T> {
+    fn get_dimensionality(&self) -> usize {
+        self.batch.dimensionality
+    }
+    fn get_uniforms(&mut self, variates: &mut [f64]) {
+        let fill = Self::fill(self.batch.dimensionality);
+        self.batch.next_path(variates, fill);
+    }
+    fn skip(&mut self, number_of_paths: usize) {
+        let fill = Self::fill(self.batch.dimensionality);
+        self.batch.skip(number_of_paths, fill);
+    }
+    fn set_seed(&mut self, seed: u64) {
+        self.batch.set_seed(seed);
+    }
+    fn reset(&mut self) {
+        self.batch.reset();
+    }
+    fn reset_dimensionality(&mut self, new_dimensionality: usize) {
+        self.batch
+            .reset_dimensionality(new_dimensionality, 2 * new_dimensionality);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chapter4::parameters::ParametersConstant;
+    use crate::chapter4::payoff3::{Payoff, PayoffCall};
+    use crate::chapter4::vanilla3::VanillaOption;
+    use crate::chapter5::mc_statistics::{StatisticsMC, StatisticsMean};
+    use crate::chapter6::park_miller::RandomParkMiller;
+    use crate::chapter6::simple_mc8::simple_montecarlo6;
+    use crate::chapter7::exotic_bs_engine::ExoticBSEngine;
+    use crate::chapter7::exotic_engine::{ExoticEngine, ExoticEngineData};
+    use crate::chapter7::path_dependent_asian::PathDependentAsian;
+    use crate::chapter9::black_scholes_formulas::black_scholes_call;
+    use approx::assert_relative_eq;
+
+    /// Draws `number_of_paths` paths of uniforms.
+    fn draw(generator: &mut impl Random, number_of_paths: usize) -> Vec<Vec<f64>> {
+        (0..number_of_paths)
+            .map(|_| {
+                let mut variates = vec![0.0; generator.get_dimensionality()];
+                generator.get_uniforms(&mut variates);
+                variates
+            })
+            .collect()
+    }
+
+    /// Checks that skipping from any path gives the same paths as generating them sequentially.
+    fn check_skip(generator: impl Random) {
+        let paths = draw(&mut generator.clone(), 20);
+        for start in 0..6 {
+            for number_of_paths in 0..12 {
+                let mut skipped = generator.clone();
+                draw(&mut skipped, start);
+                skipped.skip(number_of_paths);
+                assert_eq!(draw(&mut skipped, 2), paths[start + number_of_paths..][..2]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_moment_matching() {
+        let mut generator = MomentMatching::new(RandomParkMiller::new(3, 1), 10);
+        let mut batch = vec![vec![0.0; 3]; 10];
+        for path in batch.iter_mut() {
+            generator.get_gaussians(path);
+        }
+        for j in 0..3 {
+            let mean = batch.iter().map(|path| path[j]).sum::<f64>() / 10.0;
+            let variance = batch.iter().map(|path| path[j] * path[j]).sum::<f64>() / 10.0;
+            assert_relative_eq!(mean, 0.0, epsilon = 1e-15);
+            assert_relative_eq!(variance, 1.0, epsilon = 1e-14);
+        }
+        check_skip(MomentMatching::new(RandomParkMiller::new(3, 1), 4));
+    }
+
+    #[test]
+    fn test_stratified() {
+        let batch_size = 16;
+        let mut generator = Stratified::new(RandomParkMiller::new(2, 1), batch_size);
+        let paths = draw(&mut generator, batch_size);
+        let mut counts = vec![0; batch_size];
+        for path in &paths {
+            counts[(path[0] * batch_size as f64) as usize] += 1;
+        }
+        assert!(counts.iter().all(|&count| count == 1));
+        check_skip(Stratified::new(RandomParkMiller::new(2, 1), 4));
+    }
+
+    #[test]
+    fn test_latin_hypercube() {
+        let batch_size = 16;
+        let mut generator = LatinHypercube::new(RandomParkMiller::new(3, 1), batch_size);
+        let paths = draw(&mut generator, batch_size);
+        for j in 0..3 {
+            let mut counts = vec![0; batch_size];
+            for path in &paths {
+                counts[(path[j] * batch_size as f64) as usize] += 1;
+            }
+            assert!(counts.iter().all(|&count| count == 1));
+        }
+        check_skip(LatinHypercube::new(RandomParkMiller::new(3, 1), 4));
+    }
+
+    #[test]
+    fn test_vanilla_against_black_scholes() {
+        let the_payoff = PayoffCall::new(100.0);
+        let the_option = VanillaOption::new(&the_payoff, 1.0);
+        let vol = ParametersConstant::new(0.2);
+        let r = ParametersConstant::new(0.05);
+        let d = ParametersConstant::new(0.0);
+        let expected = black_scholes_call(100.0, 100.0, &r, &d, &vol, 1.0);
+        let inner = RandomParkMiller::new(1, 1);
+        let mut prices = vec![];
+        let mut generator = MomentMatching::new(inner.clone(), 1000);
+        let mut gatherer = StatisticsMean::default();
+        simple_montecarlo6(
+            &the_option,
+            100.0,
+            &vol,
+            &r,
+            10000,
+            &mut gatherer,
+            &mut generator,
+        );
+        prices.push(gatherer.get_results_so_far()[0][0]);
+        let mut generator = Stratified::new(inner.clone(), 1000);
+        let mut gatherer = StatisticsMean::default();
+        simple_montecarlo6(
+            &the_option,
+            100.0,
+            &vol,
+            &r,
+            10000,
+            &mut gatherer,
+            &mut generator,
+        );
+        prices.push(gatherer.get_results_so_far()[0][0]);
+        let mut generator = LatinHypercube::new(inner, 1000);
+        let mut gatherer = StatisticsMean::default();
+        simple_montecarlo6(
+            &the_option,
+            100.0,
+            &vol,
+            &r,
+            10000,
+            &mut gatherer,
+            &mut generator,
+        );
+        prices.push(gatherer.get_results_so_far()[0][0]);
+        // The standard error without variance reduction is about 0.15.
+        assert_relative_eq!(prices[0], expected, epsilon = 5e-2);
+        assert_relative_eq!(prices[1], expected, epsilon = 2e-2);
+        assert_relative_eq!(prices[2], expected, epsilon = 2e-2);
+    }
+
+    #[test]
+    fn test_exotic_engine_blocks() {
+        // The engine clones and skips the generator per block, which must reproduce the sequential paths.
+        let times = [0.25, 0.5, 0.75, 1.0];
+        let r = ParametersConstant::new(0.05);
+        let the_payoff = PayoffCall::new(100.0);
+        let the_option = PathDependentAsian::new(times.to_vec(), 1.0, &the_payoff);
+        let data = ExoticEngineData::new(&the_option, &r);
+        let generator = LatinHypercube::new(RandomParkMiller::new(4, 1), 100);
+        let mut results = vec![];
+        for parallel in [false, true] {
+            let mut the_engine = ExoticBSEngine::new(
+                &times,
+                &r,
+                ParametersConstant::new(0.0),
+                ParametersConstant::new(0.2),
+                generator.clone(),
+                100.0,
+            );
+            let mut gatherer = StatisticsMean::default();
+            if parallel {
+                the_engine.do_simulation(&data, &mut gatherer, 3000);
+            } else {
+                the_engine.do_simulation_reproducible(&data, &mut gatherer, 3000);
+            }
+            results.push(gatherer.get_results_so_far()[0][0]);
+        }
+        assert_relative_eq!(results[0], results[1], epsilon = 1e-10);
+    }
+}
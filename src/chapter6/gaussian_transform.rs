@@ -0,0 +1,445 @@
+//! 一様乱数を正規乱数に変換する方法をデコレーターパターンで選べるようにした。
+//! - 逆累積分布関数: 一様乱数と正規乱数が1対1に対応するので、Low Discrepancy Numberの構造を保つ。準モンテカルロ法ではこれを用いる。
+//! - Box-Muller: 二つの一様乱数から三角関数と対数で二つの正規乱数を作る。
+//! - Marsagliaの極座標法: Box-Mullerの三角関数を単位円内の棄却法で置き換える。
+//! - Ziggurat: 密度関数を同じ面積の128の層で覆い、ほとんどの場合に比較一回で正規乱数を得る。
+//!
+//! 棄却法では一つのパスに必要な一様乱数の数が一定でないので、そのままではパス単位のskipができない。
+//! そこで各パスに固定の数の一様乱数を割り当て、使い切った場合は残りの正規乱数を予備の一様乱数から逆累積分布関数で作る。
+//! 予備の一様乱数は棄却法で使われないので、この場合も正規乱数は独立に正規分布に従う。
+//!
+//! 極座標法では予備を含めて正規乱数一つあたり約4個の一様乱数を割り当てるので、全て生成すると一様乱数の生成の費用が支配的になる。
+//! `new_on_demand`では内側の生成器を1次元で用い、試行に使う一様乱数だけを生成して、残りは内側の生成器のskipで読み飛ばす。
+//! 一つのストリームからなる擬似乱数ではパスの区切りが変わらないので、正規乱数とパス単位のskipは`new`と一致する。
+//! bin/gaussian_method_benchmark.rsで100次元の20万パスを1スレッドで3回測定したところ、逆累積分布関数と比べた速さは
+//! Park-Millerでは全て生成すると極座標法もZigguratも約0.8倍だったが、`new_on_demand`では極座標法が1.2-1.4倍、Zigguratが1.4-1.7倍であった。
+//! PCG32では`new_on_demand`で極座標法が1.2-1.5倍、Zigguratが1.3-1.6倍であった。
+//!
+//! \[MarsagliaTsang\] Marsaglia, G. and Tsang, W. W. "The ziggurat method for generating random variables." J. Stat. Softw. 5 (2000): 1-7.
+use crate::chapter6::random2::{uniform_to_gaussian, Random};
+use std::f64::consts::PI;
+use std::sync::OnceLock;
+
+/// The methods to transform uniform variables to standard normal ones.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum GaussianMethod {
+    #[default]
+    InverseCumulative,
+    BoxMuller,
+    MarsagliaPolar,
+    Ziggurat,
+}
+
+impl GaussianMethod {
+    /// Returns the number of uniform variables allocated to a path of `dimensionality` normal variables.
+    fn number_of_uniforms(&self, dimensionality: usize) -> usize {
+        let pairs = dimensionality.div_ceil(2);
+        match self {
+            GaussianMethod::InverseCumulative => dimensionality,
+            GaussianMethod::BoxMuller => 2 * pairs,
+            // Each trial accepts with probability pi/4 and uses two uniforms.
+            GaussianMethod::MarsagliaPolar => 2 * (3 * pairs).div_ceil(2) + 16 + dimensionality,
+            // Almost every trial accepts with one uniform.
+            GaussianMethod::Ziggurat => (5 * dimensionality).div_ceil(4) + 8 + dimensionality,
+        }
+    }
+
+    /// Returns the number of uniform variables at the start of a path available to the trials,
+    /// which are followed by the reserved ones except for Box-Muller.
+    fn number_of_trial_uniforms(&self, number_of_uniforms: usize, dimensionality: usize) -> usize {
+        match self {
+            GaussianMethod::BoxMuller => number_of_uniforms,
+            _ => number_of_uniforms - dimensionality,
+        }
+    }
+
+    /// Fills `variates` by the trials with `uniforms` and returns the number of generated variables.
+    fn transform(&self, uniforms: impl Iterator<Item = f64>, variates: &mut [f64]) -> usize {
+        match self {
+            GaussianMethod::InverseCumulative => 0,
+            GaussianMethod::BoxMuller => box_muller(uniforms, variates),
+            GaussianMethod::MarsagliaPolar => marsaglia_polar(uniforms, variates),
+            GaussianMethod::Ziggurat => ziggurat(uniforms, variates),
+        }
+    }
+}
+
+/// Implemented in the decorator pattern.
+#[derive(Clone)]
+pub struct GaussianGenerator<T: Random> {
+    dimensionality: usize,
+    generator: T,
+    method: GaussianMethod,
+    /// Uniform variables drawn for a path
+    uniforms: Vec<f64>,
+    /// Whether the inner generator draws the uniform variables one by one as the trials need them
+    on_demand: bool,
+}
+
+impl<T: Random> GaussianGenerator<T> {
+    pub fn new(mut generator: T, method: GaussianMethod) -> GaussianGenerator<T> {
+        let dimensionality = generator.get_dimensionality();
+        let number_of_uniforms = method.number_of_uniforms(dimensionality);
+        generator.reset_dimensionality(number_of_uniforms);
+        GaussianGenerator {
+            dimensionality,
+            generator,
+            method,
+            uniforms: vec![0.0; number_of_uniforms],
+            on_demand: false,
+        }
+    }
+
+    /// Constructor drawing only the uniform variables used by the trials,
+    /// and skipping the rest of those allocated to the path by the inner generator.
+    ///
+    /// The inner generator must be a pseudo-random one whose paths are consecutive parts of a single stream,
+    /// such as `RandomParkMiller` and `RandomPcg32`, for which the normal variables equal those of `new`.
+    /// The inner generator is used with dimensionality 1, so this is not suitable for low discrepancy numbers.
+    ///
+    /// # Arguments
+    ///
+    /// * `generator` - A pseudo-random generator of a single stream
+    /// * `method` - A method to transform uniform variables to normal ones
+    pub fn new_on_demand(mut generator: T, method: GaussianMethod) -> GaussianGenerator<T> {
+        let dimensionality = generator.get_dimensionality();
+        generator.reset_dimensionality(1);
+        GaussianGenerator {
+            dimensionality,
+            generator,
+            method,
+            uniforms: vec![0.0; method.number_of_uniforms(dimensionality)],
+            on_demand: true,
+        }
+    }
+
+    /// Returns the next uniform variable of the inner generator of dimensionality 1.
+    fn draw_one(&mut self) -> f64 {
+        let mut uniform = [0.0];
+        self.generator.get_uniforms(&mut uniform);
+        uniform[0]
+    }
+
+    /// Set standard normal variables to `variates` drawing the uniforms one by one.
+    fn get_gaussians_on_demand(&mut self, variates: &mut [f64]) {
+        let number_of_uniforms = self.uniforms.len();
+        let dimensionality = self.dimensionality.min(variates.len());
+        let variates = &mut variates[..dimensionality];
+        let number_of_trial_uniforms = self
+            .method
+            .number_of_trial_uniforms(number_of_uniforms, dimensionality);
+        let method = self.method;
+        let mut drawn = 0;
+        let uniforms = std::iter::from_fn(|| {
+            (drawn < number_of_trial_uniforms).then(|| {
+                drawn += 1;
+                self.draw_one()
+            })
+        });
+        let generated = method.transform(uniforms, variates);
+        // Skips the unused uniforms of the trials and the reserved ones of the generated variables.
+        let first_reserved = number_of_uniforms - dimensionality + generated;
+        self.generator.skip(first_reserved - drawn);
+        for variate in variates.iter_mut().skip(generated) {
+            *variate = uniform_to_gaussian(self.draw_one());
+        }
+    }
+}
+
+impl<T: Random> Random for GaussianGenerator<T> {
+    fn get_dimensionality(&self) -> usize {
+        self.dimensionality
+    }
+
+    /// Set the first uniform variables drawn for a path to `variates`.
+    fn get_uniforms(&mut self, variates: &mut [f64]) {
+        if self.on_demand {
+            for uniform in self.uniforms.iter_mut() {
+                self.generator.get_uniforms(std::slice::from_mut(uniform));
+            }
+        } else {
+            self.generator.get_uniforms(&mut self.uniforms);
+        }
+        let dimensionality = self.dimensionality.min(variates.len());
+        variates[..dimensionality].copy_from_slice(&self.uniforms[..dimensionality]);
+    }
+
+    /// Skips paths by the inner generator, since every path is allocated the same number of uniform variables.
+    fn skip(&mut self, number_of_paths: usize) {
+        if self.on_demand {
+            self.generator.skip(number_of_paths * self.uniforms.len());
+        } else {
+            self.generator.skip(number_of_paths);
+        }
+    }
+
+    fn set_seed(&mut self, seed: u64) {
+        self.generator.set_seed(seed);
+    }
+
+    fn reset(&mut self) {
+        self.generator.reset();
+    }
+
+    /// Set standard normal variables to `variates` by the chosen method.
+    fn get_gaussians(&mut self, variates: &mut [f64]) {
+        if self.on_demand {
+            self.get_gaussians_on_demand(variates);
+            return;
+        }
+        self.generator.get_uniforms(&mut self.uniforms);
+        let dimensionality = self.dimensionality.min(variates.len());
+        let variates = &mut variates[..dimensionality];
+        let uniforms = &self.uniforms;
+        let number_of_trial_uniforms = self
+            .method
+            .number_of_trial_uniforms(uniforms.len(), dimensionality);
+        let generated = self.method.transform(
+            uniforms[..number_of_trial_uniforms].iter().copied(),
+            variates,
+        );
+        // The reserved uniforms at the end are used when the trials run out.
+        let reserved = &uniforms[uniforms.len() - dimensionality..];
+        for (variate, u) in variates.iter_mut().zip(reserved).skip(generated) {
+            *variate = uniform_to_gaussian(*u);
+        }
+    }
+
+    fn reset_dimensionality(&mut self, new_dimensionality: usize) {
+        self.dimensionality = new_dimensionality;
+        let number_of_uniforms = self.method.number_of_uniforms(new_dimensionality);
+        self.uniforms.resize(number_of_uniforms, 0.0);
+        if self.on_demand {
+            self.generator.reset_dimensionality(1);
+        } else {
+            self.generator.reset_dimensionality(number_of_uniforms);
+        }
+    }
+}
+
+/// Fills `variates` by Box-Muller and returns the number of generated variables.
+fn box_muller(mut uniforms: impl Iterator<Item = f64>, variates: &mut [f64]) -> usize {
+    let mut generated = 0;
+    for pair in variates.chunks_mut(2) {
+        let (Some(u1), Some(u2)) = (uniforms.next(), uniforms.next()) else {
+            break;
+        };
+        let radius = (-2.0 * u1.ln()).sqrt();
+        let (sin, cos) = (2.0 * PI * u2).sin_cos();
+        pair[0] = radius * cos;
+        if let Some(second) = pair.get_mut(1) {
+            *second = radius * sin;
+        }
+        generated += pair.len();
+    }
+    generated
+}
+
+/// Fills `variates` by the polar method until `uniforms` run out and returns the number of generated variables.
+fn marsaglia_polar(mut uniforms: impl Iterator<Item = f64>, variates: &mut [f64]) -> usize {
+    let mut generated = 0;
+    while generated < variates.len() {
+        let (Some(u1), Some(u2)) = (uniforms.next(), uniforms.next()) else {
+            break;
+        };
+        let (v1, v2) = (2.0 * u1 - 1.0, 2.0 * u2 - 1.0);
+        let s = v1 * v1 + v2 * v2;
+        if s >= 1.0 || s == 0.0 {
+            continue;
+        }
+        let factor = (-2.0 * s.ln() / s).sqrt();
+        variates[generated] = v1 * factor;
+        if generated + 1 < variates.len() {
+            variates[generated + 1] = v2 * factor;
+        }
+        generated += 2;
+    }
+    generated.min(variates.len())
+}
+
+/// The number of layers of the ziggurat.
+const ZIGGURAT_LAYERS: usize = 128;
+/// The right end of the base layer.
+const ZIGGURAT_R: f64 = 3.442619855899;
+/// The area of each layer under the unnormalized density exp(-x^2/2).
+const ZIGGURAT_V: f64 = 9.91256303526217e-3;
+
+/// Returns the right ends of the layers from the base and the unnormalized densities there.
+/// See \[MarsagliaTsang\].
+fn ziggurat_tables() -> &'static (Vec<f64>, Vec<f64>) {
+    static TABLES: OnceLock<(Vec<f64>, Vec<f64>)> = OnceLock::new();
+    TABLES.get_or_init(|| {
+        let density = |x: f64| (-0.5 * x * x).exp();
+        let mut x = vec![0.0; ZIGGURAT_LAYERS + 1];
+        // The width of a rectangle with the area of the base layer including the tail.
+        x[0] = ZIGGURAT_V / density(ZIGGURAT_R);
+        x[1] = ZIGGURAT_R;
+        for i in 1..ZIGGURAT_LAYERS - 1 {
+            x[i + 1] = (-2.0 * (ZIGGURAT_V / x[i] + density(x[i])).ln()).sqrt();
+        }
+        let f = x.iter().map(|&x| density(x)).collect();
+        (x, f)
+    })
+}
+
+/// Fills `variates` by Ziggurat until `uniforms` run out and returns the number of generated variables.
+fn ziggurat(mut uniforms: impl Iterator<Item = f64>, variates: &mut [f64]) -> usize {
+    let (x, f) = ziggurat_tables();
+    let mut generated = 0;
+    'variates: while generated < variates.len() {
+        let Some(u) = uniforms.next() else {
+            break;
+        };
+        // One uniform gives both the layer and the position within it.
+        let scaled = u * ZIGGURAT_LAYERS as f64;
+        let layer = scaled as usize;
+        let candidate = (2.0 * scaled.fract() - 1.0) * x[layer];
+        if candidate.abs() < x[layer + 1] {
+            variates[generated] = candidate;
+            generated += 1;
+            continue;
+        }
+        if layer == 0 {
+            // The tail beyond `ZIGGURAT_R`.
+            loop {
+                let (Some(u1), Some(u2)) = (uniforms.next(), uniforms.next()) else {
+                    break 'variates;
+                };
+                let tail = -u1.ln() / ZIGGURAT_R;
+                if -2.0 * u2.ln() >= tail * tail {
+                    variates[generated] = (ZIGGURAT_R + tail).copysign(candidate);
+                    generated += 1;
+                    continue 'variates;
+                }
+            }
+        }
+        let Some(u) = uniforms.next() else {
+            break;
+        };
+        if f[layer + 1] + (f[layer] - f[layer + 1]) * u < (-0.5 * candidate * candidate).exp() {
+            variates[generated] = candidate;
+            generated += 1;
+        }
+    }
+    generated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chapter6::normals::cumulative_normal;
+    use crate::chapter6::park_miller::RandomParkMiller;
+
+    /// Returns the mean, the variance and the Kolmogorov-Smirnov statistic of `samples`.
+    fn statistics(mut samples: Vec<f64>) -> (f64, f64, f64) {
+        let n = samples.len() as f64;
+        let mean = samples.iter().sum::<f64>() / n;
+        let variance = samples.iter().map(|x| (x - mean) * (x - mean)).sum::<f64>() / n;
+        samples.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let ks = samples
+            .iter()
+            .enumerate()
+            .map(|(i, &x)| {
+                let c = cumulative_normal(x);
+                (c - i as f64 / n).abs().max(((i + 1) as f64 / n - c).abs())
+            })
+            .fold(0.0, f64::max);
+        (mean, variance, ks)
+    }
+
+    #[test]
+    fn test_distributions() {
+        for method in [
+            GaussianMethod::InverseCumulative,
+            GaussianMethod::BoxMuller,
+            GaussianMethod::MarsagliaPolar,
+            GaussianMethod::Ziggurat,
+        ] {
+            let dimensionality = 5;
+            let mut generator =
+                GaussianGenerator::new(RandomParkMiller::new(dimensionality, 1), method);
+            let mut variates = vec![0.0; dimensionality];
+            let mut samples = vec![];
+            for _ in 0..20000 {
+                generator.get_gaussians(&mut variates);
+                samples.extend_from_slice(&variates);
+            }
+            let (mean, variance, ks) = statistics(samples);
+            // The critical value of the Kolmogorov-Smirnov test at 1% is 1.63 / sqrt(n).
+            assert!(mean.abs() < 0.01, "{method:?}: mean {mean}");
+            assert!(
+                (variance - 1.0).abs() < 0.02,
+                "{method:?}: variance {variance}"
+            );
+            assert!(ks < 1.63 / (100000.0f64).sqrt(), "{method:?}: ks {ks}");
+        }
+    }
+
+    #[test]
+    fn test_ziggurat_tables() {
+        let (x, f) = ziggurat_tables();
+        assert!(x.windows(2).skip(1).all(|w| w[0] > w[1]));
+        assert_eq!(x[ZIGGURAT_LAYERS], 0.0);
+        // Every layer has the same area.
+        for i in 1..ZIGGURAT_LAYERS - 1 {
+            let area = x[i] * (f[i + 1] - f[i]);
+            assert!((area - ZIGGURAT_V).abs() < 1e-12, "layer {i}: {area}");
+        }
+    }
+
+    #[test]
+    fn test_skip() {
+        let mut sequential =
+            GaussianGenerator::new(RandomParkMiller::new(3, 1), GaussianMethod::Ziggurat);
+        let mut skipped = sequential.clone();
+        let mut variates = vec![0.0; 3];
+        for _ in 0..10 {
+            sequential.get_gaussians(&mut variates);
+        }
+        skipped.skip(9);
+        let mut expected = vec![0.0; 3];
+        skipped.get_gaussians(&mut expected);
+        assert_eq!(variates, expected);
+    }
+
+    #[test]
+    fn test_on_demand() {
+        // Drawing on demand skips the unused uniforms, so the variables and the skip agree with `new`.
+        for method in [
+            GaussianMethod::InverseCumulative,
+            GaussianMethod::BoxMuller,
+            GaussianMethod::MarsagliaPolar,
+            GaussianMethod::Ziggurat,
+        ] {
+            let mut drawn = GaussianGenerator::new(RandomParkMiller::new(3, 1), method);
+            let mut on_demand =
+                GaussianGenerator::new_on_demand(RandomParkMiller::new(3, 1), method);
+            let mut skipped = on_demand.clone();
+            let mut expected = vec![0.0; 3];
+            let mut variates = vec![0.0; 3];
+            for _ in 0..10 {
+                drawn.get_gaussians(&mut expected);
+                on_demand.get_gaussians(&mut variates);
+                assert_eq!(variates, expected, "{method:?}");
+            }
+            skipped.skip(9);
+            skipped.get_gaussians(&mut variates);
+            assert_eq!(variates, expected, "{method:?}");
+            drawn.get_uniforms(&mut expected);
+            on_demand.get_uniforms(&mut variates);
+            assert_eq!(variates, expected, "{method:?}");
+        }
+    }
+
+    #[test]
+    fn test_fallback_when_trials_run_out() {
+        // No trial of the polar method accepts outside the unit circle.
+        let mut variates = vec![0.0; 2];
+        assert_eq!(
+            marsaglia_polar([0.99, 0.99, 0.01, 0.99].into_iter(), &mut variates),
+            0
+        );
+        assert_eq!(ziggurat(std::iter::empty(), &mut variates), 0);
+        assert_eq!(box_muller([0.5, 0.25].into_iter(), &mut variates[..1]), 1);
+    }
+}
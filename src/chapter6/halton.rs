@@ -0,0 +1,258 @@
+//! Halton列によるLow Discrepancy Number。
+//! 各次元にはi番目の素数を底とする根基逆関数(radical inverse)を割り当て、n番目の点をnの各桁を小数点の反対側に折り返して求める。
+//! n番目の点は直前の点によらず直接計算できるので、skipはインデックスを進めるだけでよい。
+//!
+//! 次元が大きくなると底の大きな次元どうしが強く相関するので、次の選択肢を用意した。
+//! - leap: 底と互いに素な整数Lについて、L個おきの点だけを用いる。
+//! - scramble: 各底の0以外の桁をPark-Millerから作った置換で並べ替える。0を固定するので点は\[0,1\)に収まる。
+//!
+//! 原点は逆累積正規分布関数で変換できないので、1番目の点から生成する。
+use crate::chapter6::park_miller::RandomParkMiller;
+use crate::chapter6::random2::Random;
+
+#[derive(Clone)]
+pub struct RandomHalton {
+    dimensionality: usize,
+    /// The number of points skipped between consecutive points
+    leap: u64,
+    /// The seed of the digit permutations, or `None` without scrambling
+    seed: Option<u64>,
+    /// The prime bases of each dimension
+    bases: Vec<u64>,
+    /// The digit permutations of each dimension
+    permutations: Vec<Vec<u64>>,
+    /// The index of the last generated point
+    index: u64,
+}
+
+impl RandomHalton {
+    /// Constructor.
+    ///
+    /// # Arguments
+    ///
+    /// * `dimensionality` - The dimensionality of generated random numbers
+    /// * `leap` - The points at indices `leap`, 2 `leap`, ... are generated. 1 gives the original sequence.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `leap` is zero or has a common factor with a base.
+    pub fn new(dimensionality: usize, leap: u64) -> Self {
+        let mut halton = RandomHalton {
+            dimensionality,
+            leap,
+            seed: None,
+            bases: vec![],
+            permutations: vec![],
+            index: 0,
+        };
+        halton.reset_dimensionality(dimensionality);
+        halton
+    }
+
+    /// Creates a generator whose digits are scrambled by random permutations drawn from `seed`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `leap` is zero or has a common factor with a base.
+    pub fn new_scrambled(dimensionality: usize, leap: u64, seed: u64) -> Self {
+        let mut halton = RandomHalton::new(dimensionality, leap);
+        halton.set_seed(seed);
+        halton
+    }
+
+    /// Returns the radical inverse of `n` in the `dimension`-th base.
+    fn radical_inverse(&self, dimension: usize, mut n: u64) -> f64 {
+        let base = self.bases[dimension];
+        let permutation = &self.permutations[dimension];
+        let reciprocal = 1.0 / base as f64;
+        let mut factor = reciprocal;
+        let mut value = 0.0;
+        while n > 0 {
+            value += permutation[(n % base) as usize] as f64 * factor;
+            n /= base;
+            factor *= reciprocal;
+        }
+        value
+    }
+
+    /// Draws the digit permutations, which are the identities without scrambling.
+    fn draw_permutations(&mut self) {
+        let mut uniforms = self.seed.map(|seed| RandomParkMiller::new(1, seed));
+        let mut uniform = [0.0];
+        self.permutations = self
+            .bases
+            .iter()
+            .map(|&base| {
+                let mut permutation: Vec<u64> = (0..base).collect();
+                if let Some(uniforms) = uniforms.as_mut() {
+                    // Fisher-Yates shuffle of the non-zero digits.
+                    for i in (2..base as usize).rev() {
+                        uniforms.get_uniforms(&mut uniform);
+                        let j = 1 + (uniform[0] * i as f64) as usize;
+                        permutation.swap(i, j);
+                    }
+                }
+                permutation
+            })
+            .collect();
+    }
+}
+
+impl Random for RandomHalton {
+    fn get_dimensionality(&self) -> usize {
+        self.dimensionality
+    }
+
+    fn get_uniforms(&mut self, variates: &mut [f64]) {
+        self.index += 1;
+        let n = self.index * self.leap;
+        for (dimension, variate) in variates.iter_mut().enumerate() {
+            *variate = self.radical_inverse(dimension, n);
+        }
+    }
+
+    fn skip(&mut self, number_of_paths: usize) {
+        self.index += number_of_paths as u64;
+    }
+
+    /// Scrambles the digits by permutations drawn from `seed` and restarts the sequence.
+    fn set_seed(&mut self, seed: u64) {
+        self.seed = Some(seed);
+        self.draw_permutations();
+        self.index = 0;
+    }
+
+    fn reset(&mut self) {
+        self.index = 0;
+    }
+
+    /// Updates dimensionality of generated random numbers.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the leap is zero or has a common factor with a base.
+    fn reset_dimensionality(&mut self, new_dimensionality: usize) {
+        self.dimensionality = new_dimensionality;
+        self.bases = primes(new_dimensionality);
+        if self.leap == 0
+            || self
+                .bases
+                .iter()
+                .any(|base| self.leap.is_multiple_of(*base))
+        {
+            panic!(
+                "The leap must be coprime to the bases {bases:?}, but got {leap}.",
+                bases = self.bases,
+                leap = self.leap
+            );
+        }
+        self.draw_permutations();
+        self.index = 0;
+    }
+}
+
+/// Returns the first `number_of_primes` prime numbers.
+fn primes(number_of_primes: usize) -> Vec<u64> {
+    let mut primes: Vec<u64> = Vec::with_capacity(number_of_primes);
+    let mut candidate = 2;
+    while primes.len() < number_of_primes {
+        if primes
+            .iter()
+            .take_while(|&&p| p * p <= candidate)
+            .all(|&p| candidate % p != 0)
+        {
+            primes.push(candidate);
+        }
+        candidate += 1;
+    }
+    primes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chapter4::parameters::ParametersConstant;
+    use crate::chapter4::payoff3::{Payoff, PayoffCall};
+    use crate::chapter4::vanilla3::VanillaOption;
+    use crate::chapter5::mc_statistics::{StatisticsMC, StatisticsMean};
+    use crate::chapter6::simple_mc8::simple_montecarlo6;
+    use crate::chapter9::black_scholes_formulas::black_scholes_call;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_first_points() {
+        let mut generator = RandomHalton::new(2, 1);
+        let mut variates = vec![0.0; 2];
+        let expected = [[0.5, 1.0 / 3.0], [0.25, 2.0 / 3.0], [0.75, 1.0 / 9.0]];
+        for point in expected {
+            generator.get_uniforms(&mut variates);
+            assert_relative_eq!(variates[0], point[0]);
+            assert_relative_eq!(variates[1], point[1]);
+        }
+        assert_eq!(primes(10), [2, 3, 5, 7, 11, 13, 17, 19, 23, 29]);
+    }
+
+    #[test]
+    fn test_skip_and_leap() {
+        let mut sequential = RandomHalton::new(4, 1);
+        let mut leaped = RandomHalton::new(4, 31);
+        let mut variates = vec![0.0; 4];
+        let mut expected = vec![0.0; 4];
+        for _ in 0..5 * 31 {
+            sequential.get_uniforms(&mut expected);
+        }
+        leaped.skip(4);
+        leaped.get_uniforms(&mut variates);
+        assert_eq!(variates, expected);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_leap_with_common_factor() {
+        RandomHalton::new(3, 10);
+    }
+
+    #[test]
+    fn test_scrambled_stratification() {
+        // The first 5^k points of a scrambled dimension in base 5 are a permutation of j / 5^k.
+        let k = 4;
+        let number_of_points = 5usize.pow(k);
+        let mut generator = RandomHalton::new_scrambled(3, 1, 7);
+        assert_ne!(generator.permutations[2], [0, 1, 2, 3, 4]);
+        assert_eq!(generator.permutations[2][0], 0);
+        let mut variates = vec![0.0; 3];
+        let mut counts = vec![0; number_of_points];
+        counts[0] += 1; // The origin.
+        for _ in 1..number_of_points {
+            generator.get_uniforms(&mut variates);
+            counts[(variates[2] * number_of_points as f64 + 1e-9) as usize] += 1;
+        }
+        assert!(counts.iter().all(|&count| count == 1));
+    }
+
+    #[test]
+    fn test_vanilla_against_black_scholes() {
+        let the_payoff = PayoffCall::new(100.0);
+        let the_option = VanillaOption::new(&the_payoff, 1.0);
+        let vol = ParametersConstant::new(0.2);
+        let r = ParametersConstant::new(0.05);
+        let mut gatherer = StatisticsMean::default();
+        let mut generator = RandomHalton::new(1, 1);
+        simple_montecarlo6(
+            &the_option,
+            100.0,
+            &vol,
+            &r,
+            (1 << 16) - 1,
+            &mut gatherer,
+            &mut generator,
+        );
+        let d = ParametersConstant::new(0.0);
+        let expected = black_scholes_call(100.0, 100.0, &r, &d, &vol, 1.0);
+        assert_relative_eq!(
+            gatherer.get_results_so_far()[0][0],
+            expected,
+            epsilon = 1e-2
+        );
+    }
+}
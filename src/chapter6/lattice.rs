@@ -0,0 +1,215 @@
+//! ランク1格子規則によるLow Discrepancy Number。
+//! 点の数Nと生成ベクトルzを決め、n番目の点を{n z / N}とする。N個の点全体で各次元の区間をN等分するので、
+//! パス数をNにしたときに最も精度が良く、Nを超えると同じ点が繰り返される。
+//! n番目の点は直接計算できるので、skipはインデックスを進めるだけでよい。
+//!
+//! 原点を除くと積分則の重みが偏るので、全ての点を1/(2N)だけずらして0と1を避ける。
+//! 独立な複製による誤差推定にはRandomShiftと組み合わせる。
+use crate::chapter6::random2::Random;
+use std::sync::Arc;
+
+/// How the generating vector is obtained.
+#[derive(Clone)]
+enum GeneratingVector {
+    /// z = (1, a, a^2, ...) modulo the number of points.
+    Korobov(u64),
+    /// A given vector, whose length bounds the dimensionality.
+    Explicit(Arc<Vec<u64>>),
+}
+
+#[derive(Clone)]
+pub struct RandomLattice {
+    dimensionality: usize,
+    /// The number of points of the lattice
+    number_of_points: u64,
+    source: GeneratingVector,
+    /// The generating vector for the current dimensionality
+    generating_vector: Vec<u64>,
+    /// The index of the next point
+    index: u64,
+}
+
+impl RandomLattice {
+    /// Constructor.
+    ///
+    /// # Arguments
+    ///
+    /// * `number_of_points` - The number of points of the lattice
+    /// * `generating_vector` - The generating vector, whose length is the dimensionality
+    ///
+    /// # Panics
+    ///
+    /// Panics if `number_of_points` is zero.
+    pub fn new(number_of_points: u64, generating_vector: Vec<u64>) -> Self {
+        let dimensionality = generating_vector.len();
+        RandomLattice::with_source(
+            dimensionality,
+            number_of_points,
+            GeneratingVector::Explicit(Arc::new(generating_vector)),
+        )
+    }
+
+    /// Creates a Korobov lattice, whose generating vector is (1, a, a^2, ...) modulo `number_of_points`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `number_of_points` is zero.
+    pub fn new_korobov(dimensionality: usize, number_of_points: u64, a: u64) -> Self {
+        RandomLattice::with_source(
+            dimensionality,
+            number_of_points,
+            GeneratingVector::Korobov(a),
+        )
+    }
+
+    fn with_source(dimensionality: usize, number_of_points: u64, source: GeneratingVector) -> Self {
+        if number_of_points == 0 {
+            panic!("A lattice must have at least one point.");
+        }
+        let mut lattice = RandomLattice {
+            dimensionality,
+            number_of_points,
+            source,
+            generating_vector: vec![],
+            index: 0,
+        };
+        lattice.reset_dimensionality(dimensionality);
+        lattice
+    }
+}
+
+impl Random for RandomLattice {
+    fn get_dimensionality(&self) -> usize {
+        self.dimensionality
+    }
+
+    fn get_uniforms(&mut self, variates: &mut [f64]) {
+        let n = (self.index % self.number_of_points) as u128;
+        let number_of_points = self.number_of_points as u128;
+        for (variate, &z) in variates.iter_mut().zip(&self.generating_vector) {
+            let numerator = n * z as u128 % number_of_points;
+            *variate = (numerator as f64 + 0.5) / self.number_of_points as f64;
+        }
+        self.index += 1;
+    }
+
+    fn skip(&mut self, number_of_paths: usize) {
+        self.index += number_of_paths as u64;
+    }
+
+    /// A lattice is deterministic, so the seed is ignored.
+    fn set_seed(&mut self, _seed: u64) {}
+
+    fn reset(&mut self) {
+        self.index = 0;
+    }
+
+    /// Updates dimensionality of generated random numbers.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the given generating vector is shorter than `new_dimensionality`.
+    fn reset_dimensionality(&mut self, new_dimensionality: usize) {
+        self.dimensionality = new_dimensionality;
+        self.generating_vector = match &self.source {
+            GeneratingVector::Korobov(a) => {
+                std::iter::successors(Some(1 % self.number_of_points), |z| {
+                    Some((*z as u128 * *a as u128 % self.number_of_points as u128) as u64)
+                })
+                .take(new_dimensionality)
+                .collect()
+            }
+            GeneratingVector::Explicit(generating_vector) => {
+                if generating_vector.len() < new_dimensionality {
+                    panic!(
+                        "The generating vector supports {available} dimensions, but {requested} are requested.",
+                        available = generating_vector.len(),
+                        requested = new_dimensionality
+                    );
+                }
+                generating_vector[..new_dimensionality].to_vec()
+            }
+        };
+        self.index = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chapter4::parameters::ParametersConstant;
+    use crate::chapter4::payoff3::{Payoff, PayoffCall};
+    use crate::chapter4::vanilla3::VanillaOption;
+    use crate::chapter5::mc_statistics::{StatisticsMC, StatisticsMean};
+    use crate::chapter6::simple_mc8::simple_montecarlo6;
+    use crate::chapter9::black_scholes_formulas::black_scholes_call;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_fibonacci_lattice() {
+        // The Fibonacci lattice with 8 points and z = (1, 5).
+        let mut generator = RandomLattice::new(8, vec![1, 5]);
+        let mut variates = vec![0.0; 2];
+        let mut points = vec![];
+        for _ in 0..8 {
+            generator.get_uniforms(&mut variates);
+            points.push(((variates[0] * 8.0) as usize, (variates[1] * 8.0) as usize));
+        }
+        assert_eq!(
+            points,
+            [
+                (0, 0),
+                (1, 5),
+                (2, 2),
+                (3, 7),
+                (4, 4),
+                (5, 1),
+                (6, 6),
+                (7, 3)
+            ]
+        );
+        generator.get_uniforms(&mut variates);
+        assert_eq!(variates, [0.5 / 8.0; 2]);
+    }
+
+    #[test]
+    fn test_korobov_and_skip() {
+        let mut generator = RandomLattice::new_korobov(3, 1021, 76);
+        assert_eq!(generator.generating_vector, [1, 76, 76 * 76 % 1021]);
+        let mut expected = vec![0.0; 3];
+        for _ in 0..10 {
+            generator.get_uniforms(&mut expected);
+        }
+        let mut variates = vec![0.0; 3];
+        generator.reset();
+        generator.skip(9);
+        generator.get_uniforms(&mut variates);
+        assert_eq!(variates, expected);
+    }
+
+    #[test]
+    fn test_vanilla_against_black_scholes() {
+        let the_payoff = PayoffCall::new(100.0);
+        let the_option = VanillaOption::new(&the_payoff, 1.0);
+        let vol = ParametersConstant::new(0.2);
+        let r = ParametersConstant::new(0.05);
+        let mut gatherer = StatisticsMean::default();
+        let mut generator = RandomLattice::new(4096, vec![1]);
+        simple_montecarlo6(
+            &the_option,
+            100.0,
+            &vol,
+            &r,
+            4096,
+            &mut gatherer,
+            &mut generator,
+        );
+        let d = ParametersConstant::new(0.0);
+        let expected = black_scholes_call(100.0, 100.0, &r, &d, &vol, 1.0);
+        assert_relative_eq!(
+            gatherer.get_results_so_far()[0][0],
+            expected,
+            epsilon = 1e-2
+        );
+    }
+}
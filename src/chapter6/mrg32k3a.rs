@@ -0,0 +1,251 @@
+//! L'Ecuyerの複合多重再帰生成器MRG32k3a。周期は約2^191で、Park-Millerの2^31と違い大規模なシミュレーションでも使い切ることはない。
+//! Park-Millerと同様に、MRG32k3a-structは乱数を生成し、RandomMRG32k3a-structはその出力を一様乱数のベクトルに変換する。
+//! 各成分の状態の遷移は3×3行列の積なので、行列の繰り返し二乗法によってO(log n)でskipできる。
+//! これを用いて、乱数列を長さ2^76の互いに重ならない部分列(substream)に分けて使うことができる。
+//!
+//! \[LEcuyer\] L'Ecuyer, P. "Good parameters and implementations for combined multiple recursive random number generators." Oper. Res. 47 (1999): 159-164.
+use crate::chapter6::random2::Random;
+
+/// A 3×3 matrix modulo some integer.
+type Matrix = [[u64; 3]; 3];
+
+/// The combined multiple recursive generator.
+/// See \[LEcuyer\] Figure I.
+#[derive(Clone)]
+struct MRG32k3a {
+    /// The last three values of the first component, from the oldest one
+    x: [u64; 3],
+    /// The last three values of the second component, from the oldest one
+    y: [u64; 3],
+}
+
+impl MRG32k3a {
+    const M1: u64 = 4294967087;
+    const M2: u64 = 4294944443;
+    const A1: Matrix = [[0, 1, 0], [0, 0, 1], [Self::M1 - 810728, 1403580, 0]];
+    const A2: Matrix = [[0, 1, 0], [0, 0, 1], [Self::M2 - 1370589, 0, 527612]];
+    /// The logarithm of the length of a substream in base 2.
+    const SUBSTREAM_BITS: u32 = 76;
+
+    /// Sets all the values of both components to `seed`, where zero values are replaced by 12345.
+    fn new(seed: u64) -> MRG32k3a {
+        let mut generator = MRG32k3a {
+            x: [0; 3],
+            y: [0; 3],
+        };
+        generator.set_seed(seed);
+        generator
+    }
+
+    fn set_seed(&mut self, seed: u64) {
+        let component = |m: u64| match seed % m {
+            0 => 12345,
+            value => value,
+        };
+        self.x = [component(Self::M1); 3];
+        self.y = [component(Self::M2); 3];
+    }
+
+    /// Returns a random integer in \[1, M1\].
+    fn get_one_random_integer(&mut self) -> u64 {
+        self.x = apply(&Self::A1, &self.x, Self::M1);
+        self.y = apply(&Self::A2, &self.y, Self::M2);
+        match (self.x[2] + Self::M1 - self.y[2] % Self::M1) % Self::M1 {
+            0 => Self::M1,
+            z => z,
+        }
+    }
+
+    /// Skips `number_of_draws` random integers in O(log(`number_of_draws`)) time.
+    fn skip(&mut self, number_of_draws: u128) {
+        self.x = apply(
+            &power(&Self::A1, number_of_draws, Self::M1),
+            &self.x,
+            Self::M1,
+        );
+        self.y = apply(
+            &power(&Self::A2, number_of_draws, Self::M2),
+            &self.y,
+            Self::M2,
+        );
+    }
+
+    /// Skips `number_of_substreams` substreams of length 2^76.
+    fn skip_substreams(&mut self, number_of_substreams: u128) {
+        let substream = 1u128 << Self::SUBSTREAM_BITS;
+        let a1 = power(
+            &power(&Self::A1, substream, Self::M1),
+            number_of_substreams,
+            Self::M1,
+        );
+        let a2 = power(
+            &power(&Self::A2, substream, Self::M2),
+            number_of_substreams,
+            Self::M2,
+        );
+        self.x = apply(&a1, &self.x, Self::M1);
+        self.y = apply(&a2, &self.y, Self::M2);
+    }
+}
+
+/// Returns `a` `v` modulo `m`.
+fn apply(a: &Matrix, v: &[u64; 3], m: u64) -> [u64; 3] {
+    std::array::from_fn(|i| {
+        (0..3).fold(0, |acc, j| {
+            ((acc as u128 + a[i][j] as u128 * v[j] as u128) % m as u128) as u64
+        })
+    })
+}
+
+/// Returns `a` `b` modulo `m`.
+fn multiply(a: &Matrix, b: &Matrix, m: u64) -> Matrix {
+    std::array::from_fn(|i| {
+        std::array::from_fn(|j| {
+            (0..3).fold(0, |acc, k| {
+                ((acc as u128 + a[i][k] as u128 * b[k][j] as u128) % m as u128) as u64
+            })
+        })
+    })
+}
+
+/// Returns `a` to the power `exponent` modulo `m`.
+fn power(a: &Matrix, mut exponent: u128, m: u64) -> Matrix {
+    let mut result = [[1, 0, 0], [0, 1, 0], [0, 0, 1]];
+    let mut base = *a;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = multiply(&result, &base, m);
+        }
+        base = multiply(&base, &base, m);
+        exponent >>= 1;
+    }
+    result
+}
+
+#[derive(Clone)]
+pub struct RandomMRG32k3a {
+    dimensionality: usize,
+    generator: MRG32k3a,
+    initial_seed: u64,
+    /// The substream from which the sequence starts
+    substream: u128,
+}
+
+impl RandomMRG32k3a {
+    /// Converts random integers in \[1, M1\] to random numbers in (0,1).
+    const RECIPROCAL: f64 = 1.0 / (MRG32k3a::M1 + 1) as f64;
+
+    pub fn new(dimensionality: usize, seed: u64) -> Self {
+        RandomMRG32k3a {
+            dimensionality,
+            generator: MRG32k3a::new(seed),
+            initial_seed: seed,
+            substream: 0,
+        }
+    }
+
+    /// Restarts the sequence from the `substream`-th substream of length 2^76,
+    /// so that generators with different substreams never overlap.
+    pub fn set_substream(&mut self, substream: u128) {
+        self.substream = substream;
+        self.reset();
+    }
+}
+
+impl Random for RandomMRG32k3a {
+    fn get_dimensionality(&self) -> usize {
+        self.dimensionality
+    }
+
+    /// Set uniform variables to `variates`.
+    fn get_uniforms(&mut self, variates: &mut [f64]) {
+        for variate in variates.iter_mut().take(self.get_dimensionality()) {
+            *variate = self.generator.get_one_random_integer() as f64 * Self::RECIPROCAL;
+        }
+    }
+
+    /// Skips random number generating in O(log(`number_of_paths` * dimensionality)) time.
+    ///
+    /// # Arguments
+    ///
+    /// * `number_of_paths` - The number of paths to skip.
+    fn skip(&mut self, number_of_paths: usize) {
+        self.generator
+            .skip(number_of_paths as u128 * self.get_dimensionality() as u128);
+    }
+
+    /// Set an initial seed.
+    fn set_seed(&mut self, seed: u64) {
+        self.initial_seed = seed;
+        self.reset();
+    }
+
+    fn reset(&mut self) {
+        self.generator.set_seed(self.initial_seed);
+        self.generator.skip_substreams(self.substream);
+    }
+
+    /// Updates dimensionality of generated random numbers.
+    fn reset_dimensionality(&mut self, new_dimensionality: usize) {
+        self.dimensionality = new_dimensionality;
+        self.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_reference_output() {
+        // The first outputs from the seed 12345 in all the components, as in the reference implementation.
+        let mut generator = RandomMRG32k3a::new(3, 12345);
+        let mut variates = vec![0.0; 3];
+        generator.get_uniforms(&mut variates);
+        assert_relative_eq!(variates[0], 0.12701112204657714, max_relative = 1e-15);
+        assert_relative_eq!(variates[1], 0.3185275653967945, max_relative = 1e-15);
+        assert_relative_eq!(variates[2], 0.3091860155832701, max_relative = 1e-15);
+    }
+
+    #[test]
+    fn test_skip() {
+        let mut sequential = RandomMRG32k3a::new(7, 42);
+        let mut variates = vec![0.0; 7];
+        for number_of_paths in [0, 1, 2, 13, 1000] {
+            let mut skipped = sequential.clone();
+            skipped.reset();
+            skipped.skip(number_of_paths);
+            sequential.reset();
+            for _ in 0..number_of_paths {
+                sequential.get_uniforms(&mut variates);
+            }
+            let mut expected = vec![0.0; 7];
+            sequential.get_uniforms(&mut expected);
+            skipped.get_uniforms(&mut variates);
+            assert_eq!(variates, expected);
+        }
+    }
+
+    #[test]
+    fn test_substreams() {
+        let mut generator = MRG32k3a::new(12345);
+        let mut skipped = generator.clone();
+        generator.skip_substreams(3);
+        skipped.skip(3u128 << MRG32k3a::SUBSTREAM_BITS);
+        assert_eq!(generator.x, skipped.x);
+        assert_eq!(generator.y, skipped.y);
+
+        let mut first = RandomMRG32k3a::new(1, 12345);
+        let mut second = first.clone();
+        second.set_substream(1);
+        let mut a = [0.0];
+        let mut b = [0.0];
+        first.get_uniforms(&mut a);
+        second.get_uniforms(&mut b);
+        assert_ne!(a, b);
+        second.set_substream(0);
+        second.get_uniforms(&mut b);
+        assert_eq!(a, b);
+    }
+}
@@ -0,0 +1,199 @@
+//! 多変量正規分布の累積分布関数P(X_1 <= b_1, ..., X_n <= b_n)をGenzの変数分離法で求める。
+//! 共分散行列をCholesky分解して条件付き分布の積に書き直すと、積分領域が単位超立方体\[0,1\]^(n-1)になり、
+//! 被積分関数は滑らかになるので準モンテカルロ法がよく効く。
+//! 点列には乱択化したSobol列を用い、独立な複製の間のばらつきから誤差を推定する。
+//!
+//! \[Genz\] Genz, A. "Numerical computation of multivariate normal probabilities." J. Comput. Graph. Stat. 1 (1992): 141-149.
+use crate::chapter5::mc_statistics::{StatisticsMC, StatisticsVariance};
+use crate::chapter6::normals::{cumulative_normal, inverse_cumulative_normal};
+use crate::chapter6::normals_error::NormalsError;
+use crate::chapter6::normals_error::NormalsError::{DimensionMismatch, NotPositiveDefinite};
+use crate::chapter6::random2::Random;
+use crate::chapter6::randomized_qmc::DigitalShift;
+use crate::chapter6::sobol::RandomSobol;
+
+/// Returns the multivariate cumulative normal function and its standard error.
+///
+/// # Arguments
+///
+/// * `upper` - Upper limits of the variables
+/// * `covariance` - A covariance matrix, whose lower triangle is used
+/// * `number_of_points` - The number of points in each replicate
+/// * `number_of_replicates` - The number of independent randomizations of the points
+///
+/// # Errors
+///
+/// Returns an error if `covariance` is not a positive definite matrix of the dimension of `upper`.
+pub fn multivariate_cumulative_normal(
+    upper: &[f64],
+    covariance: &[Vec<f64>],
+    number_of_points: usize,
+    number_of_replicates: usize,
+) -> Result<(f64, f64), NormalsError> {
+    let dimensionality = upper.len();
+    let cholesky = cholesky(covariance, dimensionality)?;
+    if dimensionality <= 1 {
+        let probability = upper
+            .first()
+            .map_or(1.0, |b| cumulative_normal(b / cholesky[0][0]));
+        return Ok((probability, 0.0));
+    }
+    let mut generator = DigitalShift::new(RandomSobol::new(dimensionality - 1), 1);
+    let mut uniforms = vec![0.0; dimensionality - 1];
+    let mut conditionals = vec![0.0; dimensionality - 1];
+    let mut gatherer = StatisticsVariance::default();
+    for replicate in 0..number_of_replicates {
+        generator.set_seed(replicate as u64 + 1);
+        let mut sum = 0.0;
+        for _ in 0..number_of_points {
+            generator.get_uniforms(&mut uniforms);
+            sum += conditional_product(upper, &cholesky, &uniforms, &mut conditionals);
+        }
+        gatherer.dump_one_result(sum / number_of_points as f64);
+    }
+    let results = gatherer.get_results_so_far();
+    Ok((results[0][0], results[0][2]))
+}
+
+/// Returns the product of the conditional probabilities of each variable given the previous ones,
+/// which are drawn by `uniforms` from their conditional distributions truncated at `upper`.
+fn conditional_product(
+    upper: &[f64],
+    cholesky: &[Vec<f64>],
+    uniforms: &[f64],
+    conditionals: &mut [f64],
+) -> f64 {
+    let mut probability = cumulative_normal(upper[0] / cholesky[0][0]);
+    let mut product = probability;
+    for i in 1..upper.len() {
+        let u = uniforms[i - 1] * probability;
+        if u <= 0.0 {
+            return 0.0;
+        }
+        conditionals[i - 1] = inverse_cumulative_normal(u.min(1.0 - f64::EPSILON / 2.0))
+            .expect("The probability is within (0, 1).");
+        let mean: f64 = cholesky[i][..i]
+            .iter()
+            .zip(conditionals.iter())
+            .map(|(c, y)| c * y)
+            .sum();
+        probability = cumulative_normal((upper[i] - mean) / cholesky[i][i]);
+        product *= probability;
+    }
+    product
+}
+
+/// Returns the lower triangular matrix L with L L^T = `covariance`.
+fn cholesky(covariance: &[Vec<f64>], dimensionality: usize) -> Result<Vec<Vec<f64>>, NormalsError> {
+    if covariance.len() != dimensionality {
+        return Err(DimensionMismatch {
+            expected: dimensionality,
+            actual: covariance.len(),
+        });
+    }
+    if let Some(row) = covariance.iter().find(|row| row.len() != dimensionality) {
+        return Err(DimensionMismatch {
+            expected: dimensionality,
+            actual: row.len(),
+        });
+    }
+    let mut lower = vec![vec![0.0; dimensionality]; dimensionality];
+    for i in 0..dimensionality {
+        for j in 0..=i {
+            let sum: f64 = (0..j).map(|k| lower[i][k] * lower[j][k]).sum();
+            if i == j {
+                let diagonal = covariance[i][i] - sum;
+                if diagonal.is_nan() || diagonal <= 0.0 {
+                    return Err(NotPositiveDefinite);
+                }
+                lower[i][i] = diagonal.sqrt();
+            } else {
+                lower[i][j] = (covariance[i][j] - sum) / lower[j][j];
+            }
+        }
+    }
+    Ok(lower)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chapter6::normals::bivariate_cumulative_normal;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_against_bivariate() {
+        let rho = 0.6;
+        let covariance = vec![vec![4.0, 2.0 * 3.0 * rho], vec![2.0 * 3.0 * rho, 9.0]];
+        let (probability, error) =
+            multivariate_cumulative_normal(&[1.0, -1.5], &covariance, 1024, 8).unwrap();
+        let expected = bivariate_cumulative_normal(0.5, -0.5, rho);
+        assert!(error < 1e-4);
+        assert_relative_eq!(probability, expected, epsilon = 5.0 * error);
+    }
+
+    #[test]
+    fn test_trivariate_orthant() {
+        // The orthant probability of equicorrelated variables with correlation 1/2 is 1/4.
+        let covariance = vec![
+            vec![1.0, 0.5, 0.5],
+            vec![0.5, 1.0, 0.5],
+            vec![0.5, 0.5, 1.0],
+        ];
+        let (probability, error) =
+            multivariate_cumulative_normal(&[0.0; 3], &covariance, 4096, 8).unwrap();
+        assert!(error < 1e-4);
+        assert_relative_eq!(probability, 0.25, epsilon = 5.0 * error + 1e-12);
+    }
+
+    #[test]
+    fn test_independent() {
+        let upper = [0.3, -0.2, 1.1, 0.7];
+        let covariance: Vec<Vec<f64>> = (0..4)
+            .map(|i| (0..4).map(|j| if i == j { 1.0 } else { 0.0 }).collect())
+            .collect();
+        let (probability, _) = multivariate_cumulative_normal(&upper, &covariance, 256, 4).unwrap();
+        let expected: f64 = upper.iter().map(|&b| cumulative_normal(b)).product();
+        assert_relative_eq!(probability, expected, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_infinite_limits() {
+        let covariance = vec![
+            vec![1.0, 0.5, 0.3],
+            vec![0.5, 1.0, 0.4],
+            vec![0.3, 0.4, 1.0],
+        ];
+        let (probability, error) = multivariate_cumulative_normal(
+            &[f64::INFINITY, 0.2, f64::INFINITY],
+            &covariance,
+            1024,
+            8,
+        )
+        .unwrap();
+        assert!(error < 1e-3);
+        assert_relative_eq!(probability, cumulative_normal(0.2), epsilon = 5.0 * error);
+        let (probability, error) =
+            multivariate_cumulative_normal(&[0.2, f64::NEG_INFINITY, 1.0], &covariance, 256, 4)
+                .unwrap();
+        assert_eq!((probability, error), (0.0, 0.0));
+        let (probability, _) =
+            multivariate_cumulative_normal(&[f64::INFINITY; 3], &covariance, 256, 4).unwrap();
+        assert_relative_eq!(probability, 1.0, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            multivariate_cumulative_normal(&[0.0, 0.0], &[vec![1.0, 2.0], vec![2.0, 1.0]], 16, 2),
+            Err(NotPositiveDefinite)
+        );
+        assert_eq!(
+            multivariate_cumulative_normal(&[0.0, 0.0], &[vec![1.0]], 16, 2),
+            Err(DimensionMismatch {
+                expected: 2,
+                actual: 1
+            })
+        );
+    }
+}
@@ -1,17 +1,354 @@
+//! 標準正規分布の密度関数、累積分布関数とその逆関数。
+//! 既定では倍精度の近似を用いる。
+//! - 逆累積分布関数: Wichuraのアルゴリズム AS241 (PPND16)。相対誤差は約1e-16。
+//! - 累積分布関数: 相補誤差関数erfcによる。erfcは|z|<1では正項級数、それ以外では連分数で求め、相対誤差は約1e-15。
+//!
+//! 以前の近似(Beasley-Springer-Moroの逆関数とAbramowitz-Stegunの7桁の多項式)も比較のために`NormalApproximation::Legacy`で選べる。
+//! 定義域外の確率に対してはpanicせずにエラーを返す。
+//!
+//! 二変量正規分布の累積分布関数はGenzによるDrezner-Wesolowskyの方法の改良(BVND)で求め、絶対誤差は約1e-15。
+//! 相関が小さい場合は相関係数についての積分を、大きい場合は完全相関からの差の積分をGauss-Legendre求積で計算する。
+//!
+//! \[Genz\] Genz, A. "Numerical computation of rectangular bivariate and trivariate normal and t probabilities." Stat. Comput. 14 (2004): 251-260.
+//!
+//! \[Wichura\] Wichura, M. J. "Algorithm AS 241: The percentage points of the normal distribution." Appl. Stat. 37 (1988): 477-484.
+use crate::chapter6::normals_error::NormalsError;
+use crate::chapter6::normals_error::NormalsError::ProbabilityOutOfRange;
+use std::f64::consts::PI;
+use std::sync::OnceLock;
+
 const ONE_OVER_ROOT_TWO_PI: f64 = 0.398942280401433;
 
+/// The approximations of the cumulative normal function and its inverse.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum NormalApproximation {
+    /// Beasley-Springer-Moro and Abramowitz-Stegun, which are accurate to about 7 digits.
+    Legacy,
+    /// AS241 and the complementary error function, which are accurate to double precision.
+    #[default]
+    Accurate,
+}
+
 pub fn normal_density(x: f64) -> f64 {
     ONE_OVER_ROOT_TWO_PI * (-x * x / 2.0).exp()
 }
 
+/// Returns the inverse of the cumulative normal function by the accurate approximation.
+///
+/// # Errors
+///
+/// Returns an error if `u` is not within the interval (0, 1).
 #[inline(always)]
-pub fn inverse_cumulative_normal(u: f64) -> f64 {
-    if !(-1.0..=1.0).contains(&u) {
-        panic!(
-            "Parameters of inverse_cumulative must be within the interval [-1, 1], but got {value}.",
-            value = u
-        );
+pub fn inverse_cumulative_normal(u: f64) -> Result<f64, NormalsError> {
+    inverse_cumulative_normal_with(u, NormalApproximation::Accurate)
+}
+
+/// Returns the inverse of the cumulative normal function by `approximation`.
+///
+/// # Errors
+///
+/// Returns an error if `u` is not within the interval (0, 1).
+#[inline(always)]
+pub fn inverse_cumulative_normal_with(
+    u: f64,
+    approximation: NormalApproximation,
+) -> Result<f64, NormalsError> {
+    if !(0.0 < u && u < 1.0) {
+        return Err(ProbabilityOutOfRange(u));
+    }
+    Ok(match approximation {
+        NormalApproximation::Legacy => inverse_cumulative_normal_moro(u),
+        NormalApproximation::Accurate => inverse_cumulative_normal_as241(u),
+    })
+}
+
+/// Returns the cumulative normal function by the accurate approximation.
+pub fn cumulative_normal(x: f64) -> f64 {
+    cumulative_normal_with(x, NormalApproximation::Accurate)
+}
+
+/// Returns the cumulative normal function by `approximation`.
+pub fn cumulative_normal_with(x: f64, approximation: NormalApproximation) -> f64 {
+    match approximation {
+        NormalApproximation::Legacy => cumulative_normal_abramowitz_stegun(x),
+        NormalApproximation::Accurate => 0.5 * erfc(-x * std::f64::consts::FRAC_1_SQRT_2),
+    }
+}
+
+/// Returns the complementary error function with relative error of about 1e-15.
+pub fn erfc(z: f64) -> f64 {
+    /// The boundary between the series and the continued fraction.
+    const SERIES_LIMIT: f64 = 1.0;
+    /// The depth of the continued fraction, which converges to double precision for `z` >= `SERIES_LIMIT`.
+    const CONTINUED_FRACTION_TERMS: usize = 200;
+    if z.is_nan() {
+        return f64::NAN;
+    }
+    if z < 0.0 {
+        return 2.0 - erfc(-z);
+    }
+    if z == f64::INFINITY {
+        return 0.0;
+    }
+    let two_over_root_pi = std::f64::consts::FRAC_2_SQRT_PI;
+    if z < SERIES_LIMIT {
+        // erf(z) = 2 / sqrt(pi) exp(-z^2) sum 2^n z^(2n+1) / (2n+1)!!, whose terms are all positive.
+        let mut term = z;
+        let mut sum = z;
+        let mut n = 0.0;
+        while term > f64::EPSILON * 0.1 * sum {
+            n += 1.0;
+            term *= 2.0 * z * z / (2.0 * n + 1.0);
+            sum += term;
+        }
+        1.0 - two_over_root_pi * exp_minus_square(z) * sum
+    } else {
+        // erfc(z) = exp(-z^2) / sqrt(pi) / (z + (1/2) / (z + 1 / (z + (3/2) / (z + ...)))).
+        let fraction = (1..=CONTINUED_FRACTION_TERMS)
+            .rev()
+            .fold(z, |fraction, k| z + 0.5 * k as f64 / fraction);
+        0.5 * two_over_root_pi * exp_minus_square(z) / fraction
     }
+}
+
+/// Returns exp(-z^2) without the cancellation error of z^2 by splitting `z` at a multiple of 1/16.
+fn exp_minus_square(z: f64) -> f64 {
+    let truncated = (z * 16.0).floor() / 16.0;
+    (-truncated * truncated).exp() * (-(z - truncated) * (z + truncated)).exp()
+}
+
+/// Returns the bivariate cumulative normal function P(X <= `x`, Y <= `y`) of standard normal variables with correlation `rho`.
+///
+/// # Arguments
+///
+/// * `x` - An upper limit of the first variable
+/// * `y` - An upper limit of the second variable
+/// * `rho` - A correlation within \[-1, 1\]
+pub fn bivariate_cumulative_normal(x: f64, y: f64, rho: f64) -> f64 {
+    // The infinite limits reduce to the univariate function, which the integrands can not evaluate.
+    if x == f64::NEG_INFINITY || y == f64::NEG_INFINITY {
+        0.0
+    } else if x == f64::INFINITY {
+        cumulative_normal(y)
+    } else if y == f64::INFINITY {
+        cumulative_normal(x)
+    } else {
+        upper_bivariate_normal(-x, -y, rho)
+    }
+}
+
+/// Returns P(X > `h`, Y > `k`) by BVND.
+/// See \[Genz\] section 2.
+fn upper_bivariate_normal(h: f64, k: f64, rho: f64) -> f64 {
+    let (nodes, weights) = gauss_legendre(if rho.abs() < 0.3 {
+        6
+    } else if rho.abs() < 0.75 {
+        12
+    } else {
+        20
+    });
+    let mut hk = h * k;
+    if rho.abs() < 0.925 {
+        // Integrates the density over the correlation from 0 to `rho` substituted by its arcsine.
+        let half_square = (h * h + k * k) / 2.0;
+        let arcsine = rho.asin();
+        let integral: f64 = nodes
+            .iter()
+            .zip(weights)
+            .map(|(x, w)| {
+                [1.0 - x, 1.0 + x]
+                    .iter()
+                    .map(|t| {
+                        let sine = (arcsine * t / 2.0).sin();
+                        w * ((sine * hk - half_square) / (1.0 - sine * sine)).exp()
+                    })
+                    .sum::<f64>()
+            })
+            .sum();
+        return integral * arcsine / (4.0 * PI) + cumulative_normal(-h) * cumulative_normal(-k);
+    }
+    // Integrates the difference from the perfectly correlated case.
+    let k = if rho < 0.0 {
+        hk = -hk;
+        -k
+    } else {
+        k
+    };
+    let mut bvn = 0.0;
+    if rho.abs() < 1.0 {
+        let a_square = (1.0 - rho) * (1.0 + rho);
+        let mut a = a_square.sqrt();
+        let b_square = (h - k) * (h - k);
+        let c = (4.0 - hk) / 8.0;
+        let d = (12.0 - hk) / 16.0;
+        bvn = a
+            * (-(b_square / a_square + hk) / 2.0).exp()
+            * (1.0 - c * (b_square - a_square) * (1.0 - d * b_square / 5.0) / 3.0
+                + c * d * a_square * a_square / 5.0);
+        if hk > -160.0 {
+            let b = b_square.sqrt();
+            bvn -= (-hk / 2.0).exp()
+                * (2.0 * PI).sqrt()
+                * cumulative_normal(-b / a)
+                * b
+                * (1.0 - c * b_square * (1.0 - d * b_square / 5.0) / 3.0);
+        }
+        a /= 2.0;
+        for (x, w) in nodes.iter().zip(weights) {
+            for sign in [-1.0, 1.0] {
+                let x_square = (a * (sign * x + 1.0)).powi(2);
+                let r_square = (1.0 - x_square).sqrt();
+                bvn += a
+                    * w
+                    * ((-b_square / (2.0 * x_square) - hk / (1.0 + r_square)).exp() / r_square
+                        - (-(b_square / x_square + hk) / 2.0).exp()
+                            * (1.0 + c * x_square * (1.0 + d * x_square)));
+            }
+        }
+        bvn = -bvn / (2.0 * PI);
+    }
+    if rho > 0.0 {
+        bvn + cumulative_normal(-h.max(k))
+    } else if k > h {
+        -bvn + cumulative_normal(k) - cumulative_normal(h)
+    } else {
+        -bvn
+    }
+}
+
+/// Returns the non-negative nodes and the weights of the Gauss-Legendre quadrature with even `number_of_points`.
+///
+/// # Panics
+///
+/// Panics unless `number_of_points` is 6, 12 or 20.
+fn gauss_legendre(number_of_points: usize) -> (&'static [f64], &'static [f64]) {
+    static RULES: [OnceLock<(Vec<f64>, Vec<f64>)>; 3] =
+        [OnceLock::new(), OnceLock::new(), OnceLock::new()];
+    let index = match number_of_points {
+        6 => 0,
+        12 => 1,
+        20 => 2,
+        _ => panic!("The Gauss-Legendre rule with {number_of_points} points is not supported."),
+    };
+    let (nodes, weights) = RULES[index].get_or_init(|| {
+        let n = number_of_points;
+        (1..=n / 2)
+            .map(|i| {
+                // Newton's method for the i-th root of the Legendre polynomial of degree n.
+                let mut x = (PI * (i as f64 - 0.25) / (n as f64 + 0.5)).cos();
+                loop {
+                    let (mut p0, mut p1) = (1.0, x);
+                    for k in 2..=n {
+                        (p0, p1) = (
+                            p1,
+                            ((2 * k - 1) as f64 * x * p1 - (k - 1) as f64 * p0) / k as f64,
+                        );
+                    }
+                    let derivative = n as f64 * (x * p1 - p0) / (x * x - 1.0);
+                    let step = p1 / derivative;
+                    x -= step;
+                    if step.abs() < 1e-15 {
+                        return (x, 2.0 / ((1.0 - x * x) * derivative * derivative));
+                    }
+                }
+            })
+            .unzip()
+    });
+    (nodes, weights)
+}
+
+/// Evaluates the polynomial with `coefficients` from the constant term at `x`.
+fn polynomial(coefficients: &[f64], x: f64) -> f64 {
+    coefficients.iter().rev().fold(0.0, |acc, c| acc * x + c)
+}
+
+/// AS241 PPND16.
+/// See \[Wichura\].
+// The coefficients are kept with the digits published in [Wichura].
+#[allow(clippy::excessive_precision)]
+fn inverse_cumulative_normal_as241(u: f64) -> f64 {
+    const A: [f64; 8] = [
+        3.3871328727963666080e0,
+        1.3314166789178437745e+2,
+        1.9715909503065514427e+3,
+        1.3731693765509461125e+4,
+        4.5921953931549871457e+4,
+        6.7265770927008700853e+4,
+        3.3430575583588128105e+4,
+        2.5090809287301226727e+3,
+    ];
+    const B: [f64; 8] = [
+        1.0,
+        4.2313330701600911252e+1,
+        6.8718700749205790830e+2,
+        5.3941960214247511077e+3,
+        2.1213794301586595867e+4,
+        3.9307895800092710610e+4,
+        2.8729085735721942674e+4,
+        5.2264952788528545610e+3,
+    ];
+    const C: [f64; 8] = [
+        1.42343711074968357734e0,
+        4.63033784615654529590e0,
+        5.76949722146069140550e0,
+        3.64784832476320460504e0,
+        1.27045825245236838258e0,
+        2.41780725177450611770e-1,
+        2.27238449892691845833e-2,
+        7.74545014278341407640e-4,
+    ];
+    const D: [f64; 8] = [
+        1.0,
+        2.05319162663775882187e0,
+        1.67638483018380384940e0,
+        6.89767334985100004550e-1,
+        1.48103976427480074590e-1,
+        1.51986665636164571966e-2,
+        5.47593808499534494600e-4,
+        1.05075007164441684324e-9,
+    ];
+    const E: [f64; 8] = [
+        6.65790464350110377720e0,
+        5.46378491116411436990e0,
+        1.78482653991729133580e0,
+        2.96560571828504891230e-1,
+        2.65321895265761230930e-2,
+        1.24266094738807843860e-3,
+        2.71155556874348757815e-5,
+        2.01033439929228813265e-7,
+    ];
+    const F: [f64; 8] = [
+        1.0,
+        5.99832206555887937690e-1,
+        1.36929880922735805310e-1,
+        1.48753612908506148525e-2,
+        7.86869131145613259100e-4,
+        1.84631831751005468180e-5,
+        1.42151175831644588870e-7,
+        2.04426310338993978564e-15,
+    ];
+    let q = u - 0.5;
+    if q.abs() <= 0.425 {
+        let r = 0.180625 - q * q;
+        return q * polynomial(&A, r) / polynomial(&B, r);
+    }
+    let r = (-u.min(1.0 - u).ln()).sqrt();
+    let x = if r <= 5.0 {
+        let r = r - 1.6;
+        polynomial(&C, r) / polynomial(&D, r)
+    } else {
+        let r = r - 5.0;
+        polynomial(&E, r) / polynomial(&F, r)
+    };
+    if q < 0.0 {
+        -x
+    } else {
+        x
+    }
+}
+
+/// Beasley-Springer-Moro.
+fn inverse_cumulative_normal_moro(u: f64) -> f64 {
     if (0.5..=0.92).contains(&u) {
         const A: [f64; 4] = [
             2.50662823884,
@@ -47,11 +384,12 @@ pub fn inverse_cumulative_normal(u: f64) -> f64 {
                 + r * (C[2]
                     + r * (C[3] + r * (C[4] + r * (C[5] + r * (C[6] + r * C[7] + r * C[8]))))))
     } else {
-        -inverse_cumulative_normal(1.0 - u)
+        -inverse_cumulative_normal_moro(1.0 - u)
     }
 }
 
-pub fn cumulative_normal(x: f64) -> f64 {
+/// Abramowitz-Stegun 26.2.17.
+fn cumulative_normal_abramowitz_stegun(x: f64) -> f64 {
     const A: [f64; 5] = [
         0.319381530,
         -0.356563782,
@@ -63,7 +401,7 @@ pub fn cumulative_normal(x: f64) -> f64 {
     if x < -7.0 {
         result = normal_density(x) / (1.0 + x * x).sqrt();
     } else if x > 7.0 {
-        result = 1.0 - cumulative_normal(-x);
+        result = 1.0 - cumulative_normal_abramowitz_stegun(-x);
     } else {
         let tmp = 1.0 / (1.0 + 0.2316419 * x.abs());
         result = 1.0
@@ -79,6 +417,8 @@ pub fn cumulative_normal(x: f64) -> f64 {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use approx::assert_relative_eq;
+    use NormalApproximation::{Accurate, Legacy};
 
     #[test]
     fn test_normal_density() {
@@ -89,15 +429,146 @@ mod tests {
 
     #[test]
     fn test_inverse_cumulative_normal() {
-        assert_eq!(inverse_cumulative_normal(0.5), 0.0);
-        assert_eq!(inverse_cumulative_normal(0.95), 1.644853553323174);
-        assert_eq!(inverse_cumulative_normal(0.999), 3.090195109132865);
+        assert_eq!(inverse_cumulative_normal_with(0.5, Legacy), Ok(0.0));
+        assert_eq!(
+            inverse_cumulative_normal_with(0.95, Legacy),
+            Ok(1.644853553323174)
+        );
+        assert_eq!(
+            inverse_cumulative_normal_with(0.999, Legacy),
+            Ok(3.090195109132865)
+        );
     }
 
     #[test]
     fn test_cumulative_normal() {
-        assert_eq!(cumulative_normal(0.0), 0.4999999994751917);
-        assert_eq!(cumulative_normal(1.0), 0.8413447404368684);
-        assert_eq!(cumulative_normal(-1.0), 0.15865525956313165);
+        assert_eq!(cumulative_normal_with(0.0, Legacy), 0.4999999994751917);
+        assert_eq!(cumulative_normal_with(1.0, Legacy), 0.8413447404368684);
+        assert_eq!(cumulative_normal_with(-1.0, Legacy), 0.15865525956313165);
+    }
+
+    #[test]
+    fn test_accurate_inverse_cumulative_normal() {
+        assert_eq!(inverse_cumulative_normal(0.5), Ok(0.0));
+        for (u, expected) in [
+            (0.975, 1.959963984540054),
+            (0.95, 1.6448536269514722),
+            (0.999, 3.090232306167813),
+            (1e-10, -6.361340902404056),
+            (1e-300, -37.0470962993612),
+        ] {
+            assert_relative_eq!(
+                inverse_cumulative_normal(u).unwrap(),
+                expected,
+                max_relative = 1e-14
+            );
+        }
+        for u in [0.0, 1.0, -0.5, 1.5, f64::NAN] {
+            assert!(matches!(
+                inverse_cumulative_normal_with(u, Accurate),
+                Err(ProbabilityOutOfRange(_))
+            ));
+        }
+        assert!(inverse_cumulative_normal_with(-0.5, Legacy).is_err());
+    }
+
+    #[test]
+    fn test_accurate_cumulative_normal() {
+        assert_eq!(cumulative_normal(0.0), 0.5);
+        for (x, expected) in [
+            (1.0, 0.8413447460685429),
+            (-1.0, 0.15865525393145707),
+            (-5.0, 2.866515718791946e-7),
+            (-20.0, 2.7536241186063314e-89),
+            (3.0, 0.9986501019683699),
+        ] {
+            assert_relative_eq!(cumulative_normal(x), expected, max_relative = 1e-14);
+        }
+        assert_relative_eq!(erfc(0.5), 0.4795001221869535, max_relative = 1e-14);
+        assert_relative_eq!(erfc(-2.0), 1.9953222650189528, max_relative = 1e-14);
+        assert_eq!(cumulative_normal(f64::INFINITY), 1.0);
+        assert_eq!(cumulative_normal(f64::NEG_INFINITY), 0.0);
+        assert_eq!(erfc(f64::INFINITY), 0.0);
+        assert_eq!(erfc(f64::NEG_INFINITY), 2.0);
+    }
+
+    #[test]
+    fn test_bivariate_cumulative_normal() {
+        // The references are computed by the numerical integration of the conditional distribution.
+        for (x, y, rho, expected) in [
+            (0.3, -0.7, 0.2, 0.1728913004060826),
+            (1.2, 0.4, -0.6, 0.5455316189711513),
+            (-1.0, -2.0, 0.8, 0.020859583933757298),
+            (0.5, 0.5, 0.95, 0.6469071954513625),
+            (-0.3, 1.1, -0.95, 0.24660383926220794),
+            (0.1, 0.2, -0.93, 0.13713977635969574),
+            (3.0, 3.0, -0.5, 0.9973002040113266),
+        ] {
+            assert_relative_eq!(
+                bivariate_cumulative_normal(x, y, rho),
+                expected,
+                epsilon = 1e-10
+            );
+        }
+        // The orthant probability is 1/4 + arcsin(rho) / (2 pi).
+        for rho in [-0.99, -0.5, 0.0, 0.3, 0.8, 0.99] {
+            assert_relative_eq!(
+                bivariate_cumulative_normal(0.0, 0.0, rho),
+                0.25 + rho.asin() / (2.0 * PI),
+                epsilon = 1e-15
+            );
+        }
+        assert_relative_eq!(
+            bivariate_cumulative_normal(0.4, -1.3, 0.0),
+            cumulative_normal(0.4) * cumulative_normal(-1.3),
+            epsilon = 1e-15
+        );
+        assert_relative_eq!(
+            bivariate_cumulative_normal(0.4, -1.3, 1.0),
+            cumulative_normal(-1.3),
+            epsilon = 1e-15
+        );
+        assert_relative_eq!(
+            bivariate_cumulative_normal(0.4, -1.3, -1.0),
+            0.0,
+            epsilon = 1e-15
+        );
+        assert_relative_eq!(
+            bivariate_cumulative_normal(0.4, 1.3, -1.0),
+            cumulative_normal(0.4) - cumulative_normal(-1.3),
+            epsilon = 1e-15
+        );
+        // An infinite limit leaves the marginal distribution of the other variable.
+        for rho in [-1.0, -0.95, 0.5, 1.0] {
+            assert_eq!(
+                bivariate_cumulative_normal(f64::INFINITY, 0.3, rho),
+                cumulative_normal(0.3)
+            );
+            assert_eq!(
+                bivariate_cumulative_normal(-0.7, f64::INFINITY, rho),
+                cumulative_normal(-0.7)
+            );
+            assert_eq!(
+                bivariate_cumulative_normal(f64::NEG_INFINITY, 0.3, rho),
+                0.0
+            );
+            assert_eq!(
+                bivariate_cumulative_normal(0.3, f64::NEG_INFINITY, rho),
+                0.0
+            );
+            assert_eq!(
+                bivariate_cumulative_normal(f64::INFINITY, f64::INFINITY, rho),
+                1.0
+            );
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for i in 1..1000 {
+            let u = i as f64 / 1000.0;
+            let x = inverse_cumulative_normal(u).unwrap();
+            assert_relative_eq!(cumulative_normal(x), u, max_relative = 1e-13);
+        }
     }
 }
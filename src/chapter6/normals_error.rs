@@ -0,0 +1,14 @@
+use std::fmt::Debug;
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum NormalsError {
+    #[error("The probability must be within the interval (0, 1), but got {0}")]
+    ProbabilityOutOfRange(f64),
+    #[error(
+        "The covariance matrix must be {expected}x{expected}, but got a row of length {actual}"
+    )]
+    DimensionMismatch { expected: usize, actual: usize },
+    #[error("The covariance matrix is not positive definite")]
+    NotPositiveDefinite,
+}
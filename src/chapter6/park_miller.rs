@@ -2,6 +2,7 @@
 //! generatorは二つの部分に分かれており、
 //! ParkMiller-structは乱数を生成し、
 //! RandomParkMiller-structはParkMiller-structの出力した乱数を一様乱数のベクトルに変換する。
+//! n個先の乱数はseedにAのn乗をかけてMで割った余りなので、繰り返し二乗法によってO(log n)でskipできる。
 use crate::chapter6::random2::Random;
 
 /// A linear congruential generator.
@@ -46,6 +47,21 @@ impl ParkMiller {
         self.seed = seed as u64;
         self.seed
     }
+
+    /// Skips `number_of_draws` random integers by multiplying the seed by A^`number_of_draws` modulo M.
+    fn skip(&mut self, mut number_of_draws: u64) {
+        let m = ParkMiller::M as u64;
+        let mut multiplier = ParkMiller::A as u64;
+        let mut seed = self.seed % m;
+        while number_of_draws > 0 {
+            if number_of_draws & 1 == 1 {
+                seed = seed * multiplier % m;
+            }
+            multiplier = multiplier * multiplier % m;
+            number_of_draws >>= 1;
+        }
+        self.seed = seed;
+    }
 }
 
 #[derive(Clone)]
@@ -82,16 +98,14 @@ impl Random for RandomParkMiller {
         }
     }
 
-    /// Skips random number generating
+    /// Skips random number generating in O(log(`number_of_paths` * dimensionality)) time.
     ///
     /// # Arguments
     ///
     /// * `number_of_paths` - The number of paths to skip.
     fn skip(&mut self, number_of_paths: usize) {
-        let mut tmp = vec![0.0; self.get_dimensionality()];
-        for _j in 0..number_of_paths {
-            self.get_uniforms(&mut tmp);
-        }
+        self.generator
+            .skip(number_of_paths as u64 * self.get_dimensionality() as u64);
     }
 
     /// Set an initial seed.
@@ -147,7 +161,34 @@ mod tests {
         }
         mean /= n as f64;
         variant /= n as f64;
-        assert_eq!(mean, 0.00047708248676497185);
-        assert_eq!(variant, 0.9987128274353647);
+        assert_eq!(mean, 0.00047707177422212074);
+        assert_eq!(variant, 0.9987154596764867);
+    }
+
+    #[test]
+    fn test_skip() {
+        let mut sequential = RandomParkMiller::new(7, 42);
+        let mut variates = vec![0.0; 7];
+        for number_of_paths in [0, 1, 2, 13, 1000] {
+            let mut skipped = sequential.clone();
+            skipped.reset();
+            skipped.skip(number_of_paths);
+            sequential.reset();
+            for _ in 0..number_of_paths {
+                sequential.get_uniforms(&mut variates);
+            }
+            let mut expected = vec![0.0; 7];
+            sequential.get_uniforms(&mut expected);
+            skipped.get_uniforms(&mut variates);
+            assert_eq!(variates, expected);
+        }
+    }
+
+    #[test]
+    fn test_skip_full_period() {
+        // A is a primitive root modulo M, so the period is M - 1.
+        let mut generator = ParkMiller::new(12345);
+        generator.skip((ParkMiller::M - 1) as u64);
+        assert_eq!(generator.seed, 12345);
     }
 }
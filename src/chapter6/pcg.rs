@@ -0,0 +1,193 @@
+//! O'NeillのPCG32(XSH RR)。64ビットの線形合同法の状態を置換関数で32ビットに変換して出力する。
+//! 周期は2^64で、加算定数を変えることで2^63本の互いに異なる乱数列(stream)を選ぶことができる。
+//! 線形合同法のn回の遷移もまた線形合同法なので、繰り返し二乗法によってO(log n)でskipできる。
+//!
+//! \[ONeill\] O'Neill, M. E. "PCG: A family of simple fast space-efficient statistically good algorithms for random number generation." HMC-CS-2014-0905 (2014).
+use crate::chapter6::random2::Random;
+
+/// The permuted congruential generator.
+/// See \[ONeill\] section 6.3.1.
+#[derive(Clone)]
+struct Pcg32 {
+    state: u64,
+    /// The odd increment, which determines the stream
+    increment: u64,
+}
+
+impl Pcg32 {
+    const MULTIPLIER: u64 = 6364136223846793005;
+
+    /// Same as `pcg32_srandom_r` in the reference implementation.
+    fn new(seed: u64, stream: u64) -> Pcg32 {
+        let mut generator = Pcg32 {
+            state: 0,
+            increment: (stream << 1) | 1,
+        };
+        generator.step();
+        generator.state = generator.state.wrapping_add(seed);
+        generator.step();
+        generator
+    }
+
+    fn step(&mut self) {
+        self.state = self
+            .state
+            .wrapping_mul(Self::MULTIPLIER)
+            .wrapping_add(self.increment);
+    }
+
+    fn get_one_random_integer(&mut self) -> u32 {
+        let old_state = self.state;
+        self.step();
+        let xor_shifted = (((old_state >> 18) ^ old_state) >> 27) as u32;
+        xor_shifted.rotate_right((old_state >> 59) as u32)
+    }
+
+    /// Skips `number_of_draws` random integers in O(log(`number_of_draws`)) time.
+    fn skip(&mut self, mut number_of_draws: u64) {
+        // Accumulates x -> multiplier x + increment composed `number_of_draws` times.
+        let mut multiplier = Self::MULTIPLIER;
+        let mut increment = self.increment;
+        let mut accumulated_multiplier = 1u64;
+        let mut accumulated_increment = 0u64;
+        while number_of_draws > 0 {
+            if number_of_draws & 1 == 1 {
+                accumulated_multiplier = accumulated_multiplier.wrapping_mul(multiplier);
+                accumulated_increment = accumulated_increment
+                    .wrapping_mul(multiplier)
+                    .wrapping_add(increment);
+            }
+            increment = multiplier.wrapping_add(1).wrapping_mul(increment);
+            multiplier = multiplier.wrapping_mul(multiplier);
+            number_of_draws >>= 1;
+        }
+        self.state = accumulated_multiplier
+            .wrapping_mul(self.state)
+            .wrapping_add(accumulated_increment);
+    }
+}
+
+#[derive(Clone)]
+pub struct RandomPcg32 {
+    dimensionality: usize,
+    generator: Pcg32,
+    initial_seed: u64,
+    stream: u64,
+}
+
+impl RandomPcg32 {
+    /// Converts random integers to random numbers in (0,1) by taking the centres of the intervals.
+    const RECIPROCAL: f64 = 1.0 / (1u64 << 32) as f64;
+
+    pub fn new(dimensionality: usize, seed: u64, stream: u64) -> Self {
+        RandomPcg32 {
+            dimensionality,
+            generator: Pcg32::new(seed, stream),
+            initial_seed: seed,
+            stream,
+        }
+    }
+
+    /// Restarts the sequence in `stream`, which never coincides with the other streams.
+    pub fn set_stream(&mut self, stream: u64) {
+        self.stream = stream;
+        self.reset();
+    }
+}
+
+impl Random for RandomPcg32 {
+    fn get_dimensionality(&self) -> usize {
+        self.dimensionality
+    }
+
+    /// Set uniform variables to `variates`.
+    fn get_uniforms(&mut self, variates: &mut [f64]) {
+        for variate in variates.iter_mut().take(self.get_dimensionality()) {
+            *variate = (self.generator.get_one_random_integer() as f64 + 0.5) * Self::RECIPROCAL;
+        }
+    }
+
+    /// Skips random number generating in O(log(`number_of_paths` * dimensionality)) time.
+    ///
+    /// # Arguments
+    ///
+    /// * `number_of_paths` - The number of paths to skip.
+    fn skip(&mut self, number_of_paths: usize) {
+        self.generator
+            .skip(number_of_paths as u64 * self.get_dimensionality() as u64);
+    }
+
+    /// Set an initial seed.
+    fn set_seed(&mut self, seed: u64) {
+        self.initial_seed = seed;
+        self.reset();
+    }
+
+    fn reset(&mut self) {
+        self.generator = Pcg32::new(self.initial_seed, self.stream);
+    }
+
+    /// Updates dimensionality of generated random numbers.
+    fn reset_dimensionality(&mut self, new_dimensionality: usize) {
+        self.dimensionality = new_dimensionality;
+        self.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reference_output() {
+        // The output of pcg32-demo with the seed 42 and the stream 54 in the reference implementation.
+        let mut generator = Pcg32::new(42, 54);
+        let outputs: Vec<u32> = (0..6).map(|_| generator.get_one_random_integer()).collect();
+        assert_eq!(
+            outputs,
+            [0xa15c02b7, 0x7b47f409, 0xba1d3330, 0x83d2f293, 0xbfa4784b, 0xcbed606e]
+        );
+    }
+
+    #[test]
+    fn test_skip() {
+        let mut sequential = RandomPcg32::new(7, 42, 54);
+        let mut variates = vec![0.0; 7];
+        for number_of_paths in [0, 1, 2, 13, 1000] {
+            let mut skipped = sequential.clone();
+            skipped.reset();
+            skipped.skip(number_of_paths);
+            sequential.reset();
+            for _ in 0..number_of_paths {
+                sequential.get_uniforms(&mut variates);
+            }
+            let mut expected = vec![0.0; 7];
+            sequential.get_uniforms(&mut expected);
+            skipped.get_uniforms(&mut variates);
+            assert_eq!(variates, expected);
+        }
+    }
+
+    #[test]
+    fn test_skip_full_period() {
+        let mut generator = Pcg32::new(42, 54);
+        let state = generator.state;
+        generator.skip(u64::MAX);
+        generator.step();
+        assert_eq!(generator.state, state);
+    }
+
+    #[test]
+    fn test_streams() {
+        let mut first = RandomPcg32::new(1, 42, 1);
+        let mut second = RandomPcg32::new(1, 42, 2);
+        let mut a = [0.0];
+        let mut b = [0.0];
+        first.get_uniforms(&mut a);
+        second.get_uniforms(&mut b);
+        assert_ne!(a, b);
+        second.set_stream(1);
+        second.get_uniforms(&mut b);
+        assert_eq!(a, b);
+    }
+}
@@ -0,0 +1,162 @@
+//! Salmonらのカウンターベース生成器Philox-4x32-10。
+//! 乱数は(シード, パスの番号, 次元)だけから定まる純粋な関数の値なので、状態を持つ生成器のように前の乱数を順に生成する必要がない。
+//! そのため各ワーカーは任意のパスの乱数を直接計算でき、skipはパスの番号を進めるだけでO(1)になる。
+//!
+//! カウンターの下位64ビットにパスの番号、次の32ビットに4次元ずつのブロックの番号を入れ、鍵にシードを入れる。
+//! 1回の変換で32ビットの乱数が4つ得られる。
+//!
+//! \[Salmon\] Salmon, J. K., Moraes, M. A., Dror, R. O. and Shaw, D. E. "Parallel random numbers: as easy as 1, 2, 3." SC '11 (2011).
+use crate::chapter6::random2::Random;
+
+/// Returns the Philox-4x32-10 bijection of `counter` with `key`.
+/// See \[Salmon\] section 3.3.
+fn philox4x32(mut counter: [u32; 4], mut key: [u32; 2]) -> [u32; 4] {
+    const MULTIPLIERS: [u64; 2] = [0xD2511F53, 0xCD9E8D57];
+    const WEYL: [u32; 2] = [0x9E3779B9, 0xBB67AE85];
+    const ROUNDS: usize = 10;
+    for round in 0..ROUNDS {
+        if round > 0 {
+            key = [key[0].wrapping_add(WEYL[0]), key[1].wrapping_add(WEYL[1])];
+        }
+        let product0 = MULTIPLIERS[0] * counter[0] as u64;
+        let product1 = MULTIPLIERS[1] * counter[2] as u64;
+        counter = [
+            (product1 >> 32) as u32 ^ counter[1] ^ key[0],
+            product1 as u32,
+            (product0 >> 32) as u32 ^ counter[3] ^ key[1],
+            product0 as u32,
+        ];
+    }
+    counter
+}
+
+#[derive(Clone)]
+pub struct RandomPhilox {
+    dimensionality: usize,
+    seed: u64,
+    /// The index of the next path
+    path_index: u64,
+}
+
+impl RandomPhilox {
+    /// Converts random integers to random numbers in (0,1) by taking the centres of the intervals.
+    const RECIPROCAL: f64 = 1.0 / (1u64 << 32) as f64;
+
+    pub fn new(dimensionality: usize, seed: u64) -> Self {
+        RandomPhilox {
+            dimensionality,
+            seed,
+            path_index: 0,
+        }
+    }
+
+    /// Set uniform variables of the `path_index`-th path to `variates` without changing the state.
+    pub fn get_uniforms_of_path(&self, path_index: u64, variates: &mut [f64]) {
+        let key = [self.seed as u32, (self.seed >> 32) as u32];
+        let dimensionality = self.dimensionality.min(variates.len());
+        for (block, chunk) in variates[..dimensionality].chunks_mut(4).enumerate() {
+            let counter = [
+                path_index as u32,
+                (path_index >> 32) as u32,
+                block as u32,
+                0,
+            ];
+            for (variate, random) in chunk.iter_mut().zip(philox4x32(counter, key)) {
+                *variate = (random as f64 + 0.5) * Self::RECIPROCAL;
+            }
+        }
+    }
+}
+
+impl Random for RandomPhilox {
+    fn get_dimensionality(&self) -> usize {
+        self.dimensionality
+    }
+
+    /// Set uniform variables of the next path to `variates`.
+    fn get_uniforms(&mut self, variates: &mut [f64]) {
+        self.get_uniforms_of_path(self.path_index, variates);
+        self.path_index += 1;
+    }
+
+    /// Skips `number_of_paths` paths in O(1) time.
+    fn skip(&mut self, number_of_paths: usize) {
+        self.path_index += number_of_paths as u64;
+    }
+
+    /// Set a seed, which is the key of the bijection.
+    fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.path_index = 0;
+    }
+
+    fn reset(&mut self) {
+        self.path_index = 0;
+    }
+
+    /// Updates dimensionality of generated random numbers.
+    fn reset_dimensionality(&mut self, new_dimensionality: usize) {
+        self.dimensionality = new_dimensionality;
+        self.path_index = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_known_answers() {
+        // The known answer tests of philox4x32 with 10 rounds in Random123.
+        assert_eq!(
+            philox4x32([0; 4], [0; 2]),
+            [0x6627e8d5, 0xe169c58d, 0xbc57ac4c, 0x9b00dbd8]
+        );
+        assert_eq!(
+            philox4x32([u32::MAX; 4], [u32::MAX; 2]),
+            [0x408f276d, 0x41c83b0e, 0xa20bc7c6, 0x6d5451fd]
+        );
+        assert_eq!(
+            philox4x32(
+                [0x243f6a88, 0x85a308d3, 0x13198a2e, 0x03707344],
+                [0xa4093822, 0x299f31d0]
+            ),
+            [0xd16cfe09, 0x94fdcceb, 0x5001e420, 0x24126ea1]
+        );
+    }
+
+    #[test]
+    fn test_paths_are_pure_functions() {
+        let mut sequential = RandomPhilox::new(6, 42);
+        let mut variates = vec![0.0; 6];
+        for _ in 0..11 {
+            sequential.get_uniforms(&mut variates);
+        }
+        let mut skipped = RandomPhilox::new(6, 42);
+        skipped.skip(10);
+        let mut expected = vec![0.0; 6];
+        skipped.get_uniforms(&mut expected);
+        assert_eq!(variates, expected);
+
+        skipped.get_uniforms_of_path(10, &mut expected);
+        assert_eq!(variates, expected);
+
+        // Each block of four dimensions comes from one counter.
+        let key = [42, 0];
+        let randoms = philox4x32([10, 0, 1, 0], key);
+        assert_eq!(variates[4], (randoms[0] as f64 + 0.5) / 4294967296.0);
+        assert_eq!(variates[5], (randoms[1] as f64 + 0.5) / 4294967296.0);
+    }
+
+    #[test]
+    fn test_distribution() {
+        let n = 100000;
+        let mut generator = RandomPhilox::new(n, 1);
+        let mut v = vec![0.0; n];
+        generator.get_uniforms(&mut v);
+        let mean = v.iter().sum::<f64>() / n as f64;
+        let variance = v.iter().map(|u| (u - 0.5) * (u - 0.5)).sum::<f64>() / n as f64;
+        assert!((mean - 0.5).abs() < 5e-3);
+        assert!((variance - 1.0 / 12.0).abs() < 5e-3);
+    }
+}
@@ -0,0 +1,504 @@
+//! 乱数生成の品質を価格付け以外の方法で確かめるための統計的検定。
+//! どの検定も`Random`の一様乱数をパスごとに次元の順に並べた列を、独立な\[0,1\)上の一様分布の標本とみなして帰無仮説を検定し、p値を返す。
+//! - カイ二乗検定: \[0,1\)を等分した区間ごとの度数を調べる。
+//! - Kolmogorov-Smirnov検定: 経験分布関数と一様分布の分布関数の差の最大値を調べる。
+//! - 系列相関検定: 一定の間隔だけ離れた二つの値の相関を調べる。
+//! - ギャップ検定: 区間に入る値どうしの間隔の長さの分布を調べる。
+//! - 連の検定: 単調増加または単調減少する部分列(連)の数を調べる。
+//! - 等分布検定: 一つのパスの全ての次元の組を、\[0,1\)^dを等分した立方体ごとの度数で調べる。
+//!
+//! 検定はKnuth \[Knuth\]の3.3節に従い、p値はカイ二乗分布、Kolmogorov分布と正規分布による漸近的なものである。
+//! Low Discrepancy Numberは独立でも一様分布の標本でもないので、これらの検定の対象にはならない。
+//!
+//! \[Knuth\] Knuth, D. E. "The Art of Computer Programming, Volume 2: Seminumerical Algorithms." 3rd ed. Addison-Wesley (1997).
+use crate::chapter6::normals::cumulative_normal;
+use crate::chapter6::random2::Random;
+
+/// The result of a statistical test.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TestResult {
+    /// The name of the test
+    pub name: &'static str,
+    /// The test statistic
+    pub statistic: f64,
+    /// The probability of a statistic at least as extreme as `statistic` under the null hypothesis
+    pub p_value: f64,
+}
+
+/// Returns the first `number_of_samples` uniforms generated path by path.
+///
+/// # Panics
+///
+/// Panics if the dimensionality of `generator` is zero.
+fn draw_sequence(generator: &mut impl Random, number_of_samples: usize) -> Vec<f64> {
+    let dimensionality = generator.get_dimensionality();
+    if dimensionality == 0 {
+        panic!("The dimensionality of the generator must be positive.");
+    }
+    let mut path = vec![0.0; dimensionality];
+    let mut sequence = Vec::with_capacity(number_of_samples + dimensionality);
+    while sequence.len() < number_of_samples {
+        generator.get_uniforms(&mut path);
+        sequence.extend_from_slice(&path);
+    }
+    sequence.truncate(number_of_samples);
+    sequence
+}
+
+/// Tests the uniformity by the frequencies in `number_of_bins` intervals of equal length.
+///
+/// # Arguments
+///
+/// * `generator` - The generator to be tested
+/// * `number_of_samples` - The number of uniforms drawn from `generator`
+/// * `number_of_bins` - The number of intervals
+///
+/// # Panics
+///
+/// Panics if `number_of_bins` is less than 2 or the expected frequency of an interval is less than 5.
+pub fn chi_square_test(
+    generator: &mut impl Random,
+    number_of_samples: usize,
+    number_of_bins: usize,
+) -> TestResult {
+    check_frequencies(number_of_samples, number_of_bins);
+    let mut counts = vec![0; number_of_bins];
+    for u in draw_sequence(generator, number_of_samples) {
+        counts[bin(u, number_of_bins)] += 1;
+    }
+    let probabilities = vec![1.0 / number_of_bins as f64; number_of_bins];
+    let statistic = chi_square_statistic(&counts, &probabilities, number_of_samples);
+    TestResult {
+        name: "chi-square",
+        statistic,
+        p_value: chi_square_p_value(statistic, number_of_bins - 1),
+    }
+}
+
+/// Tests the uniformity by the Kolmogorov-Smirnov statistic.
+///
+/// # Panics
+///
+/// Panics if `number_of_samples` is zero.
+pub fn kolmogorov_smirnov_test(
+    generator: &mut impl Random,
+    number_of_samples: usize,
+) -> TestResult {
+    if number_of_samples == 0 {
+        panic!("The number of samples must be positive.");
+    }
+    let mut sequence = draw_sequence(generator, number_of_samples);
+    sequence.sort_by(f64::total_cmp);
+    let n = number_of_samples as f64;
+    let statistic = sequence
+        .iter()
+        .enumerate()
+        .map(|(i, &u)| ((i + 1) as f64 / n - u).max(u - i as f64 / n))
+        .fold(0.0, f64::max);
+    // The correction of Stephens for the finite number of samples.
+    let root_n = n.sqrt();
+    TestResult {
+        name: "Kolmogorov-Smirnov",
+        statistic,
+        p_value: kolmogorov_p_value((root_n + 0.12 + 0.11 / root_n) * statistic),
+    }
+}
+
+/// Tests the independence by the correlation of the uniforms `lag` apart in the sequence.
+///
+/// The statistic is the sample correlation scaled to be asymptotically standard normal,
+/// and the p-value is two-sided.
+///
+/// # Panics
+///
+/// Panics if `lag` is zero or `number_of_samples` is not greater than `lag`.
+pub fn serial_correlation_test(
+    generator: &mut impl Random,
+    number_of_samples: usize,
+    lag: usize,
+) -> TestResult {
+    if lag == 0 || number_of_samples <= lag {
+        panic!(
+            "The lag must be positive and less than the number of samples {number_of_samples}, but got {lag}."
+        );
+    }
+    let sequence = draw_sequence(generator, number_of_samples);
+    let number_of_pairs = number_of_samples - lag;
+    // Each product of centred uniforms has the mean 0 and the variance 1/144.
+    let sum: f64 = sequence
+        .iter()
+        .zip(&sequence[lag..])
+        .map(|(x, y)| (x - 0.5) * (y - 0.5))
+        .sum();
+    let statistic = 12.0 * sum / (number_of_pairs as f64).sqrt();
+    TestResult {
+        name: "serial correlation",
+        statistic,
+        p_value: two_sided_normal_p_value(statistic),
+    }
+}
+
+/// Tests the independence by the lengths of the gaps between uniforms within `[lower, upper)`.
+///
+/// The gaps of lengths `0, 1, ..., max_gap - 1` and at least `max_gap` are counted.
+///
+/// # Arguments
+///
+/// * `generator` - The generator to be tested
+/// * `number_of_samples` - The number of uniforms drawn from `generator`
+/// * `lower` - The lower end of the interval
+/// * `upper` - The upper end of the interval
+/// * `max_gap` - The length from which the gaps are counted together
+///
+/// # Panics
+///
+/// Panics if `0 <= lower < upper <= 1` does not hold, `max_gap` is zero or a sequence has no gaps.
+pub fn gap_test(
+    generator: &mut impl Random,
+    number_of_samples: usize,
+    lower: f64,
+    upper: f64,
+    max_gap: usize,
+) -> TestResult {
+    if !(0.0 <= lower && lower < upper && upper <= 1.0) || max_gap == 0 {
+        panic!(
+            "The interval must be within [0, 1] and the maximum gap must be positive, but got [{lower}, {upper}) and {max_gap}."
+        );
+    }
+    let mut counts = vec![0; max_gap + 1];
+    let mut gap = None;
+    for u in draw_sequence(generator, number_of_samples) {
+        if lower <= u && u < upper {
+            if let Some(length) = gap {
+                counts[usize::min(length, max_gap)] += 1;
+            }
+            gap = Some(0);
+        } else if let Some(length) = gap.as_mut() {
+            *length += 1;
+        }
+    }
+    let number_of_gaps: usize = counts.iter().sum();
+    if number_of_gaps == 0 {
+        panic!("No gaps are found in {number_of_samples} samples.");
+    }
+    let p = upper - lower;
+    let mut probabilities: Vec<f64> = (0..max_gap)
+        .map(|length| p * (1.0 - p).powi(length as i32))
+        .collect();
+    probabilities.push((1.0 - p).powi(max_gap as i32));
+    let statistic = chi_square_statistic(&counts, &probabilities, number_of_gaps);
+    TestResult {
+        name: "gap",
+        statistic,
+        p_value: chi_square_p_value(statistic, max_gap),
+    }
+}
+
+/// Tests the independence by the number of runs up and down.
+///
+/// The number of runs is standardised by its mean `(2n - 1) / 3` and variance `(16n - 29) / 90`,
+/// and the p-value is two-sided.
+///
+/// # Panics
+///
+/// Panics if `number_of_samples` is less than 3.
+pub fn runs_test(generator: &mut impl Random, number_of_samples: usize) -> TestResult {
+    if number_of_samples < 3 {
+        panic!("The runs test needs at least 3 samples, but got {number_of_samples}.");
+    }
+    let sequence = draw_sequence(generator, number_of_samples);
+    let directions: Vec<bool> = sequence.windows(2).map(|w| w[1] > w[0]).collect();
+    let number_of_runs = 1 + directions.windows(2).filter(|w| w[0] != w[1]).count();
+    let n = number_of_samples as f64;
+    let statistic =
+        (number_of_runs as f64 - (2.0 * n - 1.0) / 3.0) / ((16.0 * n - 29.0) / 90.0).sqrt();
+    TestResult {
+        name: "runs up and down",
+        statistic,
+        p_value: two_sided_normal_p_value(statistic),
+    }
+}
+
+/// Tests the joint uniformity of the dimensions of paths by the frequencies in cubes of equal size.
+///
+/// Each dimension is divided into `bins_per_dimension` intervals.
+///
+/// # Arguments
+///
+/// * `generator` - The generator to be tested
+/// * `number_of_paths` - The number of paths drawn from `generator`
+/// * `bins_per_dimension` - The number of intervals in each dimension
+///
+/// # Panics
+///
+/// Panics if the number of cubes is less than 2 or the expected frequency of a cube is less than 5.
+pub fn equidistribution_test(
+    generator: &mut impl Random,
+    number_of_paths: usize,
+    bins_per_dimension: usize,
+) -> TestResult {
+    let dimensionality = generator.get_dimensionality();
+    let number_of_cubes = u32::try_from(dimensionality)
+        .ok()
+        .and_then(|d| bins_per_dimension.checked_pow(d))
+        .unwrap_or(usize::MAX);
+    check_frequencies(number_of_paths, number_of_cubes);
+    let mut counts = vec![0; number_of_cubes];
+    let mut path = vec![0.0; dimensionality];
+    for _ in 0..number_of_paths {
+        generator.get_uniforms(&mut path);
+        let cube = path.iter().fold(0, |cube, &u| {
+            cube * bins_per_dimension + bin(u, bins_per_dimension)
+        });
+        counts[cube] += 1;
+    }
+    let probabilities = vec![1.0 / number_of_cubes as f64; number_of_cubes];
+    let statistic = chi_square_statistic(&counts, &probabilities, number_of_paths);
+    TestResult {
+        name: "equidistribution",
+        statistic,
+        p_value: chi_square_p_value(statistic, number_of_cubes - 1),
+    }
+}
+
+/// Runs all the tests with default parameters, each on the next part of the sequence of `generator`.
+///
+/// The equidistribution test runs on a copy of `generator` reset to at most 3 dimensions divided into 8 intervals each,
+/// which needs `number_of_samples` of at least 2560 times the dimensionality.
+///
+/// # Panics
+///
+/// Panics if `number_of_samples` is too small for a test.
+pub fn run_all_tests<T: Random>(generator: &mut T, number_of_samples: usize) -> Vec<TestResult> {
+    let dimensionality = generator.get_dimensionality();
+    let mut results = vec![
+        chi_square_test(generator, number_of_samples, 100),
+        kolmogorov_smirnov_test(generator, number_of_samples),
+        serial_correlation_test(generator, number_of_samples, 1),
+        gap_test(generator, number_of_samples, 0.0, 0.5, 10),
+        runs_test(generator, number_of_samples),
+    ];
+    let mut projected = generator.clone();
+    projected.reset_dimensionality(dimensionality.min(3));
+    let bins = 8;
+    results.push(equidistribution_test(
+        &mut projected,
+        number_of_samples / dimensionality,
+        bins,
+    ));
+    results
+}
+
+/// Checks that each of `number_of_bins` bins is expected to have at least 5 of `number_of_samples`.
+fn check_frequencies(number_of_samples: usize, number_of_bins: usize) {
+    if number_of_bins < 2 || number_of_samples < 5 * number_of_bins {
+        panic!(
+            "The expected frequency of each of {number_of_bins} bins must be at least 5, but got {number_of_samples} samples."
+        );
+    }
+}
+
+/// Returns the index of the interval of `u` in `[0, 1)` divided into `number_of_bins`.
+fn bin(u: f64, number_of_bins: usize) -> usize {
+    ((u * number_of_bins as f64) as usize).min(number_of_bins - 1)
+}
+
+/// Returns Pearson's chi-square statistic of `counts` against `probabilities`.
+fn chi_square_statistic(counts: &[usize], probabilities: &[f64], total: usize) -> f64 {
+    counts
+        .iter()
+        .zip(probabilities)
+        .map(|(&count, &p)| {
+            let expected = p * total as f64;
+            (count as f64 - expected).powi(2) / expected
+        })
+        .sum()
+}
+
+/// Returns the probability that the chi-square distribution with `degrees_of_freedom` exceeds `statistic`.
+fn chi_square_p_value(statistic: f64, degrees_of_freedom: usize) -> f64 {
+    regularized_upper_gamma(0.5 * degrees_of_freedom as f64, 0.5 * statistic)
+}
+
+/// Returns the probability that the absolute value of a standard normal variable exceeds `|statistic|`.
+fn two_sided_normal_p_value(statistic: f64) -> f64 {
+    2.0 * cumulative_normal(-statistic.abs())
+}
+
+/// Returns the probability that the Kolmogorov distribution exceeds `lambda`.
+fn kolmogorov_p_value(lambda: f64) -> f64 {
+    if lambda < 0.2 {
+        return 1.0;
+    }
+    let mut sum = 0.0;
+    let mut sign = 2.0;
+    for k in 1..=100 {
+        let term = sign * (-2.0 * (k * k) as f64 * lambda * lambda).exp();
+        sum += term;
+        if term.abs() <= f64::EPSILON * sum.abs() {
+            break;
+        }
+        sign = -sign;
+    }
+    sum.clamp(0.0, 1.0)
+}
+
+/// Returns the logarithm of the gamma function for `x > 0` by the approximation of Lanczos.
+fn log_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 6] = [
+        76.180_091_729_471_46,
+        -86.505_320_329_416_77,
+        24.014_098_240_830_91,
+        -1.231_739_572_450_155,
+        0.120_865_097_386_617_9e-2,
+        -0.539_523_938_495_3e-5,
+    ];
+    let mut y = x;
+    let tmp = x + 5.5;
+    let tmp = tmp - (x + 0.5) * tmp.ln();
+    let mut series = 1.000_000_000_190_015;
+    for c in COEFFICIENTS {
+        y += 1.0;
+        series += c / y;
+    }
+    -tmp + (2.506_628_274_631_000_5 * series / x).ln()
+}
+
+/// Returns the regularised upper incomplete gamma function `Q(a, x)`.
+///
+/// The series is used for `x < a + 1` and the continued fraction otherwise.
+fn regularized_upper_gamma(a: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 1.0;
+    }
+    let log_prefactor = -x + a * x.ln() - log_gamma(a);
+    if x < a + 1.0 {
+        let mut term = 1.0 / a;
+        let mut sum = term;
+        let mut denominator = a;
+        for _ in 0..1000 {
+            denominator += 1.0;
+            term *= x / denominator;
+            sum += term;
+            if term.abs() < sum.abs() * f64::EPSILON {
+                break;
+            }
+        }
+        1.0 - sum * log_prefactor.exp()
+    } else {
+        // The modified Lentz method.
+        let tiny = f64::MIN_POSITIVE / f64::EPSILON;
+        let mut b = x + 1.0 - a;
+        let mut c = 1.0 / tiny;
+        let mut d = 1.0 / b;
+        let mut h = d;
+        for i in 1..1000 {
+            let an = -(i as f64) * (i as f64 - a);
+            b += 2.0;
+            d = an * d + b;
+            if d.abs() < tiny {
+                d = tiny;
+            }
+            c = b + an / c;
+            if c.abs() < tiny {
+                c = tiny;
+            }
+            d = 1.0 / d;
+            let delta = d * c;
+            h *= delta;
+            if (delta - 1.0).abs() < f64::EPSILON {
+                break;
+            }
+        }
+        log_prefactor.exp() * h
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chapter6::mrg32k3a::RandomMRG32k3a;
+    use crate::chapter6::park_miller::RandomParkMiller;
+    use crate::chapter6::pcg::RandomPcg32;
+    use crate::chapter6::philox::RandomPhilox;
+    use approx::assert_relative_eq;
+
+    /// A Weyl sequence, which is uniform but far from independent.
+    #[derive(Clone)]
+    struct Weyl {
+        dimensionality: usize,
+        value: f64,
+    }
+
+    impl Random for Weyl {
+        fn get_dimensionality(&self) -> usize {
+            self.dimensionality
+        }
+        fn get_uniforms(&mut self, variates: &mut [f64]) {
+            for variate in variates {
+                self.value = (self.value + 0.618_033_988_749_894_9) % 1.0;
+                *variate = self.value;
+            }
+        }
+        fn skip(&mut self, _number_of_paths: usize) {}
+        fn set_seed(&mut self, _seed: u64) {}
+        fn reset(&mut self) {
+            self.value = 0.0;
+        }
+        fn reset_dimensionality(&mut self, new_dimensionality: usize) {
+            self.dimensionality = new_dimensionality;
+        }
+    }
+
+    #[test]
+    fn test_p_values() {
+        // The chi-square distribution with 2 degrees of freedom is exponential with mean 2.
+        for x in [0.1, 1.0, 5.0, 30.0] {
+            assert_relative_eq!(
+                chi_square_p_value(x, 2),
+                (-0.5 * x).exp(),
+                max_relative = 1e-9
+            );
+        }
+        // The median of the chi-square distribution with 10 degrees of freedom.
+        assert_relative_eq!(chi_square_p_value(9.341818, 10), 0.5, epsilon = 1e-6);
+        // The critical value of the Kolmogorov distribution at 5%.
+        assert_relative_eq!(kolmogorov_p_value(1.358099), 0.05, epsilon = 1e-6);
+        assert_relative_eq!(two_sided_normal_p_value(-1.959964), 0.05, epsilon = 1e-6);
+        assert_relative_eq!(log_gamma(5.0), 24.0_f64.ln(), epsilon = 1e-10);
+    }
+
+    #[test]
+    fn test_pseudo_random_generators() {
+        let number_of_samples = 100_000;
+        let mut suites = vec![
+            run_all_tests(&mut RandomParkMiller::new(4, 1), number_of_samples),
+            run_all_tests(&mut RandomMRG32k3a::new(4, 12345), number_of_samples),
+            run_all_tests(&mut RandomPcg32::new(4, 42, 54), number_of_samples),
+            run_all_tests(&mut RandomPhilox::new(4, 1), number_of_samples),
+        ];
+        for result in suites.drain(..).flatten() {
+            assert!(result.p_value > 1e-3, "{result:?}");
+        }
+    }
+
+    #[test]
+    fn test_detects_dependence() {
+        let mut weyl = Weyl {
+            dimensionality: 2,
+            value: 0.0,
+        };
+        let results = run_all_tests(&mut weyl, 100_000);
+        let kolmogorov_smirnov = results[1];
+        assert!(kolmogorov_smirnov.p_value > 0.5, "{kolmogorov_smirnov:?}");
+        for result in [results[2], results[4], results[5]] {
+            assert!(result.p_value < 1e-6, "{result:?}");
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_too_few_samples() {
+        chi_square_test(&mut RandomParkMiller::new(1, 1), 100, 100);
+    }
+}
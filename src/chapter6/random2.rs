@@ -9,6 +9,7 @@
 //! 1. 疑似乱数ではなく、Loq Discrepancy Numberを使用することもできる。
 //!
 //! 累積関数の逆関数を通して一様乱数を正規乱数に変換するため、[[0,1]]区間から0,1は除いてサンプリングする。
+//! 各生成器の一様乱数は(0,1)に収まるが、丸めなどで端点に達しても正規乱数が得られるように、変換の前に区間の内側に寄せる。
 
 use crate::chapter6::normals::inverse_cumulative_normal;
 pub trait Random: Send + Sync + Clone {
@@ -17,11 +18,39 @@ pub trait Random: Send + Sync + Clone {
     fn skip(&mut self, number_of_paths: usize);
     fn set_seed(&mut self, seed: u64);
     fn reset(&mut self);
+    /// Set standard normal variables to `variates` by the inverse of the cumulative normal function.
+    ///
+    /// Uniforms outside the interval (0, 1) are clamped into it, and NaN is passed through.
     fn get_gaussians(&mut self, variates: &mut [f64]) {
         self.get_uniforms(variates);
         for variate in variates {
-            *variate = inverse_cumulative_normal(*variate);
+            *variate = uniform_to_gaussian(*variate);
         }
     }
     fn reset_dimensionality(&mut self, new_dimensionality: usize);
 }
+
+/// The smallest uniform passed to the inverse of the cumulative normal function.
+const LOWEST_UNIFORM: f64 = f64::MIN_POSITIVE;
+/// The largest uniform passed to the inverse of the cumulative normal function.
+const HIGHEST_UNIFORM: f64 = 1.0 - f64::EPSILON / 2.0;
+
+/// Returns the standard normal variable of `uniform` clamped into the interval (0, 1).
+pub(crate) fn uniform_to_gaussian(uniform: f64) -> f64 {
+    inverse_cumulative_normal(uniform.clamp(LOWEST_UNIFORM, HIGHEST_UNIFORM)).unwrap_or(f64::NAN)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_uniform_to_gaussian() {
+        assert!(uniform_to_gaussian(0.0).is_finite());
+        assert!(uniform_to_gaussian(0.0) < -37.0);
+        assert!(uniform_to_gaussian(1.0).is_finite());
+        assert!(uniform_to_gaussian(1.0) > 8.0);
+        assert_eq!(uniform_to_gaussian(0.5), 0.0);
+        assert!(uniform_to_gaussian(f64::NAN).is_nan());
+    }
+}
@@ -0,0 +1,185 @@
+//! Low Discrepancy Numberは決定的なので、そのままでは価格の標準誤差を推定できない。
+//! そこで点列全体を同じ乱数でずらし、一様性を保ったまま独立な複製を作る。
+//! 各複製の価格の平均は真の価格の不偏推定量になり、複製間のばらつきから信頼区間が得られる。
+//!
+//! - `DigitalShift` はSobol列の各点と乱数とのビットごとの排他的論理和をとる。2進のネットとしての構造が保たれる。
+//!   ずらした原点は0にならないので原点から生成し、最初の2^m点がネットになるようにする。
+//! - `RandomShift` は任意の点列に乱数を足して小数部分をとる(Cranley-Patterson回転)。Halton列などの2進でない点列にも使える。
+//!
+//! 小さなシードから始まるPark-Millerの最初の乱数は互いに強く相関するので、ずらす量は固定のシードによる一つの乱数列から取り、
+//! set_seedに与えたシードの番号の点を用いる。これによってシードごとに独立な複製になる。
+use crate::chapter6::park_miller::RandomParkMiller;
+use crate::chapter6::random2::Random;
+use crate::chapter6::sobol::{RandomSobol, BITS, RECIPROCAL};
+
+/// The seed of the sequence from which shifts are drawn.
+const SHIFT_SEED: u64 = 1;
+
+/// Returns the shifts in \[0,1\) of each dimension, which is the `seed`-th point of a pseudo-random sequence.
+fn draw_shifts(dimensionality: usize, seed: u64) -> Vec<f64> {
+    let mut shifts = vec![0.0; dimensionality];
+    if dimensionality > 0 {
+        let mut generator = RandomParkMiller::new(dimensionality, SHIFT_SEED);
+        generator.skip(seed as usize);
+        generator.get_uniforms(&mut shifts);
+    }
+    shifts
+}
+
+/// A Sobol sequence randomized by a digital shift.
+#[derive(Clone)]
+pub struct DigitalShift {
+    generator: RandomSobol,
+    seed: u64,
+    shifts: Vec<u32>,
+}
+
+impl DigitalShift {
+    pub fn new(generator: RandomSobol, seed: u64) -> Self {
+        let mut digital_shift = DigitalShift {
+            generator,
+            seed,
+            shifts: vec![],
+        };
+        digital_shift.set_seed(seed);
+        digital_shift
+    }
+}
+
+impl Random for DigitalShift {
+    fn get_dimensionality(&self) -> usize {
+        self.generator.get_dimensionality()
+    }
+
+    /// Set the next shifted point to `variates`, starting from the shifted origin.
+    ///
+    /// The centre of each interval of width 2^-32 is taken so that neither 0 nor 1 is returned.
+    fn get_uniforms(&mut self, variates: &mut [f64]) {
+        for ((variate, state), shift) in variates
+            .iter_mut()
+            .zip(self.generator.current_integers())
+            .zip(&self.shifts)
+        {
+            *variate = ((state ^ shift) as f64 + 0.5) * RECIPROCAL;
+        }
+        self.generator.next_integers();
+    }
+
+    fn skip(&mut self, number_of_paths: usize) {
+        self.generator.skip(number_of_paths);
+    }
+
+    /// Draws new shifts from `seed` and restarts the sequence.
+    fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.shifts = draw_shifts(self.get_dimensionality(), seed)
+            .into_iter()
+            .map(|shift| (shift * (1u64 << BITS) as f64) as u32)
+            .collect();
+        self.generator.reset();
+    }
+
+    fn reset(&mut self) {
+        self.generator.reset();
+    }
+
+    fn reset_dimensionality(&mut self, new_dimensionality: usize) {
+        self.generator.reset_dimensionality(new_dimensionality);
+        self.set_seed(self.seed);
+    }
+}
+
+/// Any sequence randomized by a shift modulo 1, implemented in the decorator pattern.
+#[derive(Clone)]
+pub struct RandomShift<T: Random> {
+    generator: T,
+    seed: u64,
+    shifts: Vec<f64>,
+}
+
+impl<T: Random> RandomShift<T> {
+    pub fn new(generator: T, seed: u64) -> RandomShift<T> {
+        let mut random_shift = RandomShift {
+            generator,
+            seed,
+            shifts: vec![],
+        };
+        random_shift.set_seed(seed);
+        random_shift
+    }
+}
+
+impl<T: Random> Random for RandomShift<T> {
+    fn get_dimensionality(&self) -> usize {
+        self.generator.get_dimensionality()
+    }
+
+    fn get_uniforms(&mut self, variates: &mut [f64]) {
+        self.generator.get_uniforms(variates);
+        for (variate, shift) in variates.iter_mut().zip(&self.shifts) {
+            *variate = (*variate + shift).fract();
+            // The sum may be rounded to exactly 1, which is excluded as well as 0.
+            if *variate == 0.0 {
+                *variate = f64::EPSILON;
+            }
+        }
+    }
+
+    fn skip(&mut self, number_of_paths: usize) {
+        self.generator.skip(number_of_paths);
+    }
+
+    /// Draws new shifts from `seed` and restarts the sequence.
+    fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.shifts = draw_shifts(self.get_dimensionality(), seed);
+        self.generator.reset();
+    }
+
+    fn reset(&mut self) {
+        self.generator.reset();
+    }
+
+    fn reset_dimensionality(&mut self, new_dimensionality: usize) {
+        self.generator.reset_dimensionality(new_dimensionality);
+        self.set_seed(self.seed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_digital_shift_preserves_stratification() {
+        let k = 8;
+        let mut generator = DigitalShift::new(RandomSobol::new(3), 42);
+        let mut variates = vec![0.0; 3];
+        let mut counts = vec![vec![0; 1 << k]; 3];
+        for _ in 0..1 << k {
+            generator.get_uniforms(&mut variates);
+            for (count, variate) in counts.iter_mut().zip(&variates) {
+                count[(variate * (1 << k) as f64) as usize] += 1;
+            }
+        }
+        assert!(counts.iter().flatten().all(|&count| count == 1));
+    }
+
+    #[test]
+    fn test_seed_changes_points() {
+        let mut first = RandomShift::new(RandomSobol::new(2), 1);
+        let mut second = first.clone();
+        second.set_seed(2);
+        let mut a = vec![0.0; 2];
+        let mut b = vec![0.0; 2];
+        first.get_uniforms(&mut a);
+        second.get_uniforms(&mut b);
+        assert_ne!(a, b);
+        assert!(a.iter().chain(&b).all(|u| 0.0 < *u && *u < 1.0));
+
+        first.get_uniforms(&mut a);
+        first.set_seed(2);
+        first.get_uniforms(&mut a);
+        assert_eq!(a, b);
+    }
+}
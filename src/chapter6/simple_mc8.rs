@@ -32,3 +32,41 @@ pub fn simple_montecarlo6<T: Payoff>(
         gatherer.dump_one_result(this_payoff * discounting);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chapter4::parameters::ParametersConstant;
+    use crate::chapter4::payoff3::PayoffCall;
+    use crate::chapter5::mc_statistics::StatisticsMean;
+    use crate::chapter6::anti_thetic::AntiThetic;
+    use crate::chapter6::park_miller::RandomParkMiller;
+    use crate::chapter9::black_scholes_formulas::black_scholes_call;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_against_black_scholes() {
+        let the_payoff = PayoffCall::new(100.0);
+        let the_option = VanillaOption::new(&the_payoff, 1.0);
+        let vol = ParametersConstant::new(0.2);
+        let r = ParametersConstant::new(0.05);
+        let mut gatherer = StatisticsMean::default();
+        let mut generator = AntiThetic::new(RandomParkMiller::new(1, 1));
+        simple_montecarlo6(
+            &the_option,
+            100.0,
+            &vol,
+            &r,
+            100000,
+            &mut gatherer,
+            &mut generator,
+        );
+        let expected =
+            black_scholes_call(100.0, 100.0, &r, &ParametersConstant::new(0.0), &vol, 1.0);
+        assert_relative_eq!(
+            gatherer.get_results_so_far()[0][0],
+            expected,
+            epsilon = 5e-2
+        );
+    }
+}
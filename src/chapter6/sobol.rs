@@ -0,0 +1,469 @@
+//! Sobol列によるLow Discrepancy Numberの生成。
+//! 各次元は有限体GF(2)上の原始多項式と初期方向数から定まる方向数によって生成され、
+//! n番目の点はnのグレイコードで立っているビットに対応する方向数の排他的論理和になる。
+//! したがって直前の点から一つの方向数との排他的論理和で次の点が得られ、skipもグレイコードから直接O(1)で計算できる。
+//!
+//! 原始多項式は次数の昇順、同じ次数の中では係数を表す整数の昇順に並べる。これは\[JoeKuo\]と同じ順序である。
+//! 初期方向数は\[JoeKuo\]の形式のファイルから読み込むことができ、
+//! 読み込まない場合は奇数かつ2^k未満という条件を満たすようにPark-Millerの一様乱数から定める。
+//! 原点は逆累積正規分布関数で変換できないので、1番目の点から生成する。
+//!
+//! \[JoeKuo\] Joe, S. and Kuo, F. Y. "Constructing Sobol sequences with better two-dimensional projections." SIAM J. Sci. Comput. 30 (2008): 2635-2654.
+use crate::chapter6::park_miller::RandomParkMiller;
+use crate::chapter6::random2::Random;
+use crate::chapter6::sobol_error::SobolError;
+use crate::chapter6::sobol_error::SobolError::{DimensionTooLarge, ParseError};
+use std::sync::Arc;
+
+/// The number of bits of the generated integers.
+pub(crate) const BITS: usize = 32;
+/// Converts generated integers to random numbers in \[0,1\).
+pub(crate) const RECIPROCAL: f64 = 1.0 / (1u64 << BITS) as f64;
+/// The seed for the default initial direction numbers.
+const INITIAL_DIRECTION_SEED: u64 = 1;
+
+/// A primitive polynomial x^s + c_1 x^(s-1) + ... + c_(s-1) x + 1 and the initial direction numbers for a dimension.
+#[derive(Debug, Clone, PartialEq)]
+struct DirectionEntry {
+    /// The degree s
+    degree: usize,
+    /// The coefficients c_1, ..., c_(s-1) as the bits of an integer from the most significant one
+    coefficients: u32,
+    /// The initial direction numbers m_1, ..., m_s
+    initial: Vec<u32>,
+}
+
+impl DirectionEntry {
+    /// Returns the direction numbers v_k = m_k / 2^k scaled by 2^32.
+    fn directions(&self) -> [u32; BITS] {
+        let s = self.degree;
+        let mut v = [0u32; BITS];
+        for k in 0..BITS {
+            v[k] = if k < s {
+                self.initial[k] << (BITS - 1 - k)
+            } else {
+                let mut value = v[k - s] ^ (v[k - s] >> s);
+                for i in 1..s {
+                    if (self.coefficients >> (s - 1 - i)) & 1 == 1 {
+                        value ^= v[k - i];
+                    }
+                }
+                value
+            };
+        }
+        v
+    }
+}
+
+/// Where the initial direction numbers come from.
+#[derive(Clone)]
+enum DirectionSource {
+    /// Primitive polynomials are enumerated and the initial direction numbers are drawn by Park-Miller.
+    Default,
+    /// Entries loaded from a file in the format of \[JoeKuo\], excluding the first dimension.
+    Loaded(Arc<Vec<DirectionEntry>>),
+}
+
+impl DirectionSource {
+    /// Returns the direction numbers of the first `dimensionality` dimensions.
+    fn directions(&self, dimensionality: usize) -> Result<Vec<[u32; BITS]>, SobolError> {
+        let entries = match self {
+            DirectionSource::Default => default_entries(dimensionality.saturating_sub(1)),
+            DirectionSource::Loaded(entries) => {
+                if dimensionality > entries.len() + 1 {
+                    return Err(DimensionTooLarge {
+                        requested: dimensionality,
+                        available: entries.len() + 1,
+                    });
+                }
+                entries[..dimensionality.saturating_sub(1)].to_vec()
+            }
+        };
+        // The first dimension is the van der Corput sequence in base 2.
+        let first = std::array::from_fn(|k| 1u32 << (BITS - 1 - k));
+        Ok(std::iter::once(first)
+            .chain(entries.iter().map(DirectionEntry::directions))
+            .take(dimensionality)
+            .collect())
+    }
+}
+
+#[derive(Clone)]
+pub struct RandomSobol {
+    dimensionality: usize,
+    source: DirectionSource,
+    /// The direction numbers of each dimension
+    directions: Vec<[u32; BITS]>,
+    /// The index of the last generated point
+    index: u64,
+    /// The last generated point as integers
+    state: Vec<u32>,
+}
+
+impl RandomSobol {
+    /// Creates a generator with the default initial direction numbers, which supports any dimensionality.
+    ///
+    /// The default numbers are not those of \[JoeKuo\], whose table is not bundled with this crate.
+    /// Use `from_joe_kuo` with the file new-joe-kuo-6.21201 for their better two-dimensional projections.
+    pub fn new(dimensionality: usize) -> Self {
+        let source = DirectionSource::Default;
+        let directions = source.directions(dimensionality).unwrap();
+        RandomSobol {
+            dimensionality,
+            source,
+            directions,
+            index: 0,
+            state: vec![0; dimensionality],
+        }
+    }
+
+    /// Creates a generator with the initial direction numbers in the format of \[JoeKuo\],
+    /// e.g. the contents of the file new-joe-kuo-6.21201.
+    ///
+    /// Each line consists of the dimension d, the degree s, the coefficients a and the initial direction numbers m_1, ..., m_s.
+    /// The header line and empty lines are ignored.
+    pub fn from_joe_kuo(
+        dimensionality: usize,
+        direction_numbers: &str,
+    ) -> Result<Self, SobolError> {
+        let entries = parse_joe_kuo(direction_numbers)?;
+        let source = DirectionSource::Loaded(Arc::new(entries));
+        let directions = source.directions(dimensionality)?;
+        Ok(RandomSobol {
+            dimensionality,
+            source,
+            directions,
+            index: 0,
+            state: vec![0; dimensionality],
+        })
+    }
+
+    /// Advances to the next point and returns it as integers scaled by 2^32.
+    ///
+    /// # Panics
+    ///
+    /// Panics if 2^32 - 1 points have been generated.
+    pub(crate) fn next_integers(&mut self) -> &[u32] {
+        self.index += 1;
+        let bit = self.index.trailing_zeros() as usize;
+        if bit >= BITS {
+            panic!("The Sobol sequence is exhausted after 2^{BITS} - 1 points.");
+        }
+        for (state, directions) in self.state.iter_mut().zip(&self.directions) {
+            *state ^= directions[bit];
+        }
+        &self.state
+    }
+
+    /// Returns the current point as integers scaled by 2^32, which is the origin before any point is generated.
+    pub(crate) fn current_integers(&self) -> &[u32] {
+        &self.state
+    }
+}
+
+impl Random for RandomSobol {
+    fn get_dimensionality(&self) -> usize {
+        self.dimensionality
+    }
+
+    /// Set the next point of the sequence to `variates`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if 2^32 - 1 points have been generated.
+    fn get_uniforms(&mut self, variates: &mut [f64]) {
+        for (variate, state) in variates.iter_mut().zip(self.next_integers()) {
+            *variate = *state as f64 * RECIPROCAL;
+        }
+    }
+
+    /// Skips `number_of_paths` points by the Gray code of the index in O(dimensionality) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if more than 2^32 - 1 points would have been generated.
+    fn skip(&mut self, number_of_paths: usize) {
+        self.index = self
+            .index
+            .checked_add(number_of_paths as u64)
+            .filter(|&index| index < 1 << BITS)
+            .unwrap_or_else(|| {
+                panic!("The Sobol sequence is exhausted after 2^{BITS} - 1 points.")
+            });
+        let gray = self.index ^ (self.index >> 1);
+        for (state, directions) in self.state.iter_mut().zip(&self.directions) {
+            *state = (0..BITS)
+                .filter(|k| (gray >> k) & 1 == 1)
+                .fold(0, |acc, k| acc ^ directions[k]);
+        }
+    }
+
+    /// The Sobol sequence is deterministic, so the seed is ignored.
+    fn set_seed(&mut self, _seed: u64) {}
+
+    fn reset(&mut self) {
+        self.index = 0;
+        self.state.iter_mut().for_each(|state| *state = 0);
+    }
+
+    /// Updates dimensionality of generated random numbers.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the loaded direction numbers do not support `new_dimensionality`.
+    fn reset_dimensionality(&mut self, new_dimensionality: usize) {
+        self.dimensionality = new_dimensionality;
+        self.directions = self
+            .source
+            .directions(new_dimensionality)
+            .unwrap_or_else(|error| panic!("{error}"));
+        self.state = vec![0; new_dimensionality];
+        self.index = 0;
+    }
+}
+
+/// Returns the product of polynomials `a` and `b` over GF(2) modulo `modulus` of degree `degree`.
+fn multiply_mod(a: u64, b: u64, modulus: u64, degree: usize) -> u64 {
+    let mut result = 0;
+    let mut a = a;
+    let mut b = b;
+    while b > 0 {
+        if b & 1 == 1 {
+            result ^= a;
+        }
+        b >>= 1;
+        a <<= 1;
+        if (a >> degree) & 1 == 1 {
+            a ^= modulus;
+        }
+    }
+    result
+}
+
+/// Returns x^`exponent` modulo `modulus` of degree `degree` over GF(2).
+fn power_of_x_mod(exponent: u64, modulus: u64, degree: usize) -> u64 {
+    let mut result = 1;
+    let mut base = if degree == 1 { 2 ^ modulus } else { 2 };
+    let mut exponent = exponent;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = multiply_mod(result, base, modulus, degree);
+        }
+        base = multiply_mod(base, base, modulus, degree);
+        exponent >>= 1;
+    }
+    result
+}
+
+/// Returns the distinct prime factors of `n`.
+fn prime_factors(mut n: u64) -> Vec<u64> {
+    let mut factors = Vec::new();
+    let mut p = 2;
+    while p * p <= n {
+        if n.is_multiple_of(p) {
+            factors.push(p);
+            while n.is_multiple_of(p) {
+                n /= p;
+            }
+        }
+        p += 1;
+    }
+    if n > 1 {
+        factors.push(n);
+    }
+    factors
+}
+
+/// Returns whether `polynomial` of degree `degree` is primitive over GF(2), i.e. the order of x is 2^degree - 1.
+fn is_primitive(polynomial: u64, degree: usize) -> bool {
+    let order = (1u64 << degree) - 1;
+    power_of_x_mod(order, polynomial, degree) == 1
+        && prime_factors(order)
+            .into_iter()
+            .all(|p| power_of_x_mod(order / p, polynomial, degree) != 1)
+}
+
+/// Returns the default entries for the dimensions from the second one.
+fn default_entries(number_of_entries: usize) -> Vec<DirectionEntry> {
+    let mut uniforms = RandomParkMiller::new(1, INITIAL_DIRECTION_SEED);
+    let mut uniform = [0.0];
+    let mut entries = Vec::with_capacity(number_of_entries);
+    let mut degree = 1;
+    while entries.len() < number_of_entries {
+        if degree >= BITS {
+            panic!("The Sobol sequence supports primitive polynomials of degree less than {BITS}.");
+        }
+        for coefficients in 0..(1u32 << (degree - 1)) {
+            if entries.len() == number_of_entries {
+                break;
+            }
+            let polynomial = (1u64 << degree) | ((coefficients as u64) << 1) | 1;
+            if !is_primitive(polynomial, degree) {
+                continue;
+            }
+            let initial = (1..=degree)
+                .map(|k| {
+                    uniforms.get_uniforms(&mut uniform);
+                    2 * (uniform[0] * (1u64 << (k - 1)) as f64) as u32 + 1
+                })
+                .collect();
+            entries.push(DirectionEntry {
+                degree,
+                coefficients,
+                initial,
+            });
+        }
+        degree += 1;
+    }
+    entries
+}
+
+/// Parses direction numbers in the format of \[JoeKuo\].
+fn parse_joe_kuo(direction_numbers: &str) -> Result<Vec<DirectionEntry>, SobolError> {
+    let mut entries = Vec::new();
+    for (line_number, line) in direction_numbers.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with(|c: char| c.is_alphabetic()) {
+            continue;
+        }
+        let error = |message: &str| ParseError {
+            line: line_number + 1,
+            message: message.to_string(),
+        };
+        let numbers = line
+            .split_whitespace()
+            .map(|token| token.parse::<u32>())
+            .collect::<Result<Vec<u32>, _>>()
+            .map_err(|e| error(&e.to_string()))?;
+        if numbers.len() < 3 {
+            return Err(error("expected d, s, a and m_i"));
+        }
+        let degree = numbers[1] as usize;
+        let initial = numbers[3..].to_vec();
+        if degree == 0 || degree >= BITS || initial.len() != degree {
+            return Err(error("the number of m_i must equal to s"));
+        }
+        if initial
+            .iter()
+            .enumerate()
+            .any(|(k, m)| m % 2 == 0 || *m >= 1 << (k + 1))
+        {
+            return Err(error("each m_k must be odd and less than 2^k"));
+        }
+        entries.push(DirectionEntry {
+            degree,
+            coefficients: numbers[2],
+            initial,
+        });
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_points() {
+        let mut generator = RandomSobol::new(2);
+        let mut variates = vec![0.0; 2];
+        let expected = [[0.5, 0.5], [0.75, 0.25], [0.25, 0.75], [0.375, 0.375]];
+        for point in expected {
+            generator.get_uniforms(&mut variates);
+            assert_eq!(variates, point);
+        }
+    }
+
+    #[test]
+    fn test_primitive_polynomials() {
+        // The number of primitive polynomials of degree s is φ(2^s - 1) / s.
+        let entries = default_entries(1110);
+        let count = |s| entries.iter().filter(|e| e.degree == s).count();
+        assert_eq!(
+            (1..=13).map(count).collect::<Vec<_>>(),
+            [1, 1, 2, 2, 6, 6, 18, 16, 48, 60, 176, 144, 630]
+        );
+        assert_eq!(entries[2].coefficients, 1);
+        assert_eq!(entries[3].coefficients, 2);
+    }
+
+    #[test]
+    fn test_stratification() {
+        // Every one-dimensional projection of the first 2^k points is a permutation of j / 2^k.
+        let k = 10;
+        let dimensionality = 1000;
+        let mut generator = RandomSobol::new(dimensionality);
+        let mut variates = vec![0.0; dimensionality];
+        let mut counts = vec![vec![0; 1 << k]; dimensionality];
+        for count in counts.iter_mut() {
+            count[0] += 1; // The origin.
+        }
+        for _ in 1..(1 << k) {
+            generator.get_uniforms(&mut variates);
+            for (count, variate) in counts.iter_mut().zip(&variates) {
+                count[(variate * (1 << k) as f64) as usize] += 1;
+            }
+        }
+        assert!(counts.iter().flatten().all(|&count| count == 1));
+    }
+
+    #[test]
+    fn test_skip_and_reset() {
+        let mut sequential = RandomSobol::new(5);
+        let mut variates = vec![0.0; 5];
+        for _ in 0..37 {
+            sequential.get_uniforms(&mut variates);
+        }
+        let mut skipped = RandomSobol::new(5);
+        skipped.skip(36);
+        let mut expected = vec![0.0; 5];
+        skipped.get_uniforms(&mut expected);
+        assert_eq!(variates, expected);
+
+        skipped.reset();
+        skipped.get_uniforms(&mut variates);
+        assert_eq!(variates, vec![0.5; 5]);
+
+        skipped.reset_dimensionality(3);
+        skipped.get_uniforms(&mut variates[..3]);
+        assert_eq!(variates[..3], [0.5; 3]);
+    }
+
+    #[test]
+    fn test_skip_to_last_point() {
+        let mut generator = RandomSobol::new(2);
+        generator.skip((1 << BITS) - 2);
+        let mut variates = vec![0.0; 2];
+        generator.get_uniforms(&mut variates);
+        // The Gray code of 2^32 - 1 is 2^31, which selects the last direction number.
+        assert_eq!(variates[0], RECIPROCAL);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_skip_past_last_point() {
+        RandomSobol::new(2).skip(1 << BITS);
+    }
+
+    #[test]
+    fn test_from_joe_kuo() {
+        let direction_numbers = "d s a m_i\n2 1 0 1\n3 2 1 1 3\n4 3 1 1 3 1\n";
+        let mut generator = RandomSobol::from_joe_kuo(4, direction_numbers).unwrap();
+        let mut variates = vec![0.0; 4];
+        generator.get_uniforms(&mut variates);
+        generator.get_uniforms(&mut variates);
+        // v_2 is m_2 / 4 = 0.75 in the second to fourth dimensions.
+        assert_eq!(variates, [0.75, 0.25, 0.25, 0.25]);
+        assert_eq!(
+            RandomSobol::from_joe_kuo(5, direction_numbers).err(),
+            Some(DimensionTooLarge {
+                requested: 5,
+                available: 4
+            })
+        );
+        assert!(matches!(
+            RandomSobol::from_joe_kuo(2, "2 1 0 2\n"),
+            Err(ParseError { line: 1, .. })
+        ));
+    }
+}
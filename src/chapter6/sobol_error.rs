@@ -0,0 +1,10 @@
+use std::fmt::Debug;
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum SobolError {
+    #[error("Failed to parse the direction numbers at line {line}: {message}")]
+    ParseError { line: usize, message: String },
+    #[error("The direction numbers support {available} dimensions, but {requested} are requested")]
+    DimensionTooLarge { requested: usize, available: usize },
+}
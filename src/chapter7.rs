@@ -1,4 +1,5 @@
 pub mod exotic_bs_engine;
 pub mod exotic_engine;
+pub mod path_construction;
 pub mod path_dependent;
 pub mod path_dependent_asian;
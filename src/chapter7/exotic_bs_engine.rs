@@ -1,8 +1,17 @@
 use crate::chapter4::parameters::Parameters;
 use crate::chapter6::random2::Random;
 use crate::chapter7::exotic_engine::ExoticEngine;
+use crate::chapter7::path_construction::{BrownianBridge, PathConstruction, PrincipalComponents};
 use crate::chapter7::path_dependent::PathDependent;
 
+/// Builds the diffusion part of the logarithm of the stock price.
+#[derive(Clone)]
+enum PathBuilder {
+    Incremental,
+    BrownianBridge(BrownianBridge),
+    PrincipalComponents(PrincipalComponents),
+}
+
 #[derive(Clone)]
 pub struct ExoticBSEngine<R: Random> {
     /// A random number generator
@@ -17,6 +26,11 @@ pub struct ExoticBSEngine<R: Random> {
     number_of_times: usize,
     /// Gaussian random variables generated by `self.the_generator`
     variates: Vec<f64>,
+    path_builder: PathBuilder,
+    /// The sums of `drifts` up to each time
+    cumulative_drifts: Vec<f64>,
+    /// The diffusion part of the logarithm of the stock price on a path
+    brownian_path: Vec<f64>,
 }
 
 impl<R: Random> ExoticBSEngine<R> {
@@ -30,12 +44,36 @@ impl<R: Random> ExoticBSEngine<R> {
     /// * `the_generator` - A random number generator
     /// * `spot` - A spot value of a stock
     pub fn new(
+        look_at_times: &[f64],
+        r: &impl Parameters,
+        d: impl Parameters,
+        vol: impl Parameters,
+        the_generator: R,
+        spot: f64,
+    ) -> ExoticBSEngine<R> {
+        ExoticBSEngine::new_with_path_construction(
+            look_at_times,
+            r,
+            d,
+            vol,
+            the_generator,
+            spot,
+            PathConstruction::Incremental,
+        )
+    }
+
+    /// Creates an engine which builds paths by `path_construction`.
+    ///
+    /// The Brownian bridge or the principal components put the large movements of paths in the first variates,
+    /// which suits low discrepancy numbers.
+    pub fn new_with_path_construction(
         look_at_times: &[f64],
         r: &impl Parameters,
         d: impl Parameters,
         vol: impl Parameters,
         mut the_generator: R,
         spot: f64,
+        path_construction: PathConstruction,
     ) -> ExoticBSEngine<R> {
         let number_of_times = look_at_times.len();
 
@@ -55,6 +93,29 @@ impl<R: Random> ExoticBSEngine<R> {
             standard_deviations[j] = this_variance.sqrt();
         }
         let variates = vec![0.0; number_of_times];
+        let cumulative_variances: Vec<f64> = standard_deviations
+            .iter()
+            .scan(0.0, |variance, sd| {
+                *variance += sd * sd;
+                Some(*variance)
+            })
+            .collect();
+        let path_builder = match path_construction {
+            PathConstruction::Incremental => PathBuilder::Incremental,
+            PathConstruction::BrownianBridge => {
+                PathBuilder::BrownianBridge(BrownianBridge::new(&cumulative_variances))
+            }
+            PathConstruction::PrincipalComponents => {
+                PathBuilder::PrincipalComponents(PrincipalComponents::new(&cumulative_variances))
+            }
+        };
+        let cumulative_drifts = drifts
+            .iter()
+            .scan(0.0, |drift, d| {
+                *drift += d;
+                Some(*drift)
+            })
+            .collect();
         ExoticBSEngine {
             the_generator,
             drifts,
@@ -62,6 +123,9 @@ impl<R: Random> ExoticBSEngine<R> {
             log_spot: spot.ln(),
             number_of_times,
             variates,
+            path_builder,
+            cumulative_drifts,
+            brownian_path: vec![0.0; number_of_times],
         }
     }
 }
@@ -74,14 +138,33 @@ impl<T: PathDependent, R: Random> ExoticEngine<T> for ExoticBSEngine<R> {
     /// * `spot_values` - A container to store spot values
     fn get_one_path(&mut self, spot_values: &mut [f64]) {
         self.the_generator.get_gaussians(&mut self.variates);
-        let mut current_log_spot = self.log_spot;
-        for (j, spot_value) in spot_values
+        match &self.path_builder {
+            PathBuilder::Incremental => {
+                let mut current_log_spot = self.log_spot;
+                for (j, spot_value) in spot_values
+                    .iter_mut()
+                    .enumerate()
+                    .take(self.number_of_times)
+                {
+                    current_log_spot +=
+                        self.drifts[j] + self.standard_deviations[j] * self.variates[j];
+                    *spot_value = current_log_spot.exp();
+                }
+                return;
+            }
+            PathBuilder::BrownianBridge(bridge) => {
+                bridge.build(&self.variates, &mut self.brownian_path)
+            }
+            PathBuilder::PrincipalComponents(components) => {
+                components.build(&self.variates, &mut self.brownian_path)
+            }
+        }
+        for ((spot_value, drift), diffusion) in spot_values
             .iter_mut()
-            .enumerate()
-            .take(self.number_of_times)
+            .zip(&self.cumulative_drifts)
+            .zip(&self.brownian_path)
         {
-            current_log_spot += self.drifts[j] + self.standard_deviations[j] * self.variates[j];
-            *spot_value = current_log_spot.exp();
+            *spot_value = (self.log_spot + drift + diffusion).exp();
         }
     }
 
@@ -93,3 +176,147 @@ impl<T: PathDependent, R: Random> ExoticEngine<T> for ExoticBSEngine<R> {
         self.the_generator.skip(number_of_paths);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chapter4::parameters::ParametersConstant;
+    use crate::chapter4::payoff3::{Payoff, PayoffCall};
+    use crate::chapter5::mc_statistics::{StatisticsMC, StatisticsMean, StatisticsVariance};
+    use crate::chapter6::anti_thetic::AntiThetic;
+    use crate::chapter6::park_miller::RandomParkMiller;
+    use crate::chapter6::randomized_qmc::DigitalShift;
+    use crate::chapter6::sobol::RandomSobol;
+    use crate::chapter7::exotic_engine::ExoticEngineData;
+    use crate::chapter7::path_dependent_asian::PathDependentAsian;
+    use crate::chapter9::black_scholes_formulas::black_scholes_call;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_single_date_against_black_scholes() {
+        let times = vec![1.0];
+        let r = ParametersConstant::new(0.05);
+        let d = ParametersConstant::new(0.01);
+        let vol = ParametersConstant::new(0.2);
+        let the_payoff = PayoffCall::new(100.0);
+        let generator = AntiThetic::new(RandomParkMiller::new(1, 1));
+        let mut the_engine = ExoticBSEngine::new(&times, &r, d, vol, generator, 100.0);
+        let the_option = PathDependentAsian::new(times, 1.0, &the_payoff);
+        let data = ExoticEngineData::new(&the_option, &r);
+        let mut gatherer = StatisticsMean::default();
+        the_engine.do_simulation(&data, &mut gatherer, 100000);
+        let expected = black_scholes_call(100.0, 100.0, &r, &d, &vol, 1.0);
+        assert_relative_eq!(
+            gatherer.get_results_so_far()[0][0],
+            expected,
+            epsilon = 1e-1
+        );
+    }
+
+    #[test]
+    fn test_sobol_single_date_against_black_scholes() {
+        let times = vec![1.0];
+        let r = ParametersConstant::new(0.05);
+        let d = ParametersConstant::new(0.01);
+        let vol = ParametersConstant::new(0.2);
+        let the_payoff = PayoffCall::new(100.0);
+        let mut the_engine = ExoticBSEngine::new(&times, &r, d, vol, RandomSobol::new(1), 100.0);
+        let the_option = PathDependentAsian::new(times, 1.0, &the_payoff);
+        let data = ExoticEngineData::new(&the_option, &r);
+        let mut gatherer = StatisticsMean::default();
+        the_engine.do_simulation(&data, &mut gatherer, (1 << 16) - 1);
+        let expected = black_scholes_call(100.0, 100.0, &r, &d, &vol, 1.0);
+        // Far fewer paths than pseudo-random numbers give a more accurate price.
+        assert_relative_eq!(
+            gatherer.get_results_so_far()[0][0],
+            expected,
+            epsilon = 1e-2
+        );
+    }
+
+    #[test]
+    fn test_replicated_sobol_confidence_interval() {
+        let times = vec![1.0];
+        let r = ParametersConstant::new(0.05);
+        let d = ParametersConstant::new(0.01);
+        let vol = ParametersConstant::new(0.2);
+        let the_payoff = PayoffCall::new(100.0);
+        let generator = DigitalShift::new(RandomSobol::new(1), 1);
+        let mut the_engine = ExoticBSEngine::new(&times, &r, d, vol, generator, 100.0);
+        let the_option = PathDependentAsian::new(times, 1.0, &the_payoff);
+        let data = ExoticEngineData::new(&the_option, &r);
+        let mut gatherer = StatisticsVariance::default();
+        the_engine.do_replicated_simulation(&data, &mut gatherer, 4096, 16);
+        let results = gatherer.get_results_so_far();
+        let (price, standard_error, lower, upper) =
+            (results[0][0], results[0][2], results[0][3], results[0][4]);
+        let expected = black_scholes_call(100.0, 100.0, &r, &d, &vol, 1.0);
+        assert!(standard_error > 0.0);
+        // The standard error of 16 * 4096 pseudo-random paths is about 0.06.
+        assert!(standard_error < 1e-2);
+        assert!(lower < price && price < upper);
+        assert_relative_eq!(price, expected, epsilon = 5.0 * standard_error + 1e-4);
+        // The engine is restored, so the replicates are the same again
+        // up to the order of the parallel summation.
+        let mut gatherer = StatisticsVariance::default();
+        the_engine.do_replicated_simulation(&data, &mut gatherer, 4096, 16);
+        for (actual, expected) in gatherer.get_results_so_far()[0].iter().zip(&results[0]) {
+            assert_relative_eq!(actual, expected, max_relative = 1e-12);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_replicated_simulation_needs_two_replicates() {
+        let times = vec![1.0];
+        let r = ParametersConstant::new(0.05);
+        let the_payoff = PayoffCall::new(100.0);
+        let generator = DigitalShift::new(RandomSobol::new(1), 1);
+        let mut the_engine = ExoticBSEngine::new(
+            &times,
+            &r,
+            ParametersConstant::new(0.01),
+            ParametersConstant::new(0.2),
+            generator,
+            100.0,
+        );
+        let the_option = PathDependentAsian::new(times, 1.0, &the_payoff);
+        let data = ExoticEngineData::new(&the_option, &r);
+        the_engine.do_replicated_simulation(&data, &mut StatisticsVariance::default(), 1024, 1);
+    }
+
+    #[test]
+    fn test_path_constructions_reduce_qmc_error() {
+        let times: Vec<f64> = (1..=64).map(|i| (i as f64 / 64.0).powi(2)).collect();
+        let r = ParametersConstant::new(0.05);
+        let the_payoff = PayoffCall::new(100.0);
+        let the_option = PathDependentAsian::new(times.clone(), 1.0, &the_payoff);
+        let data = ExoticEngineData::new(&the_option, &r);
+        let mut results = vec![];
+        for path_construction in [
+            PathConstruction::Incremental,
+            PathConstruction::BrownianBridge,
+            PathConstruction::PrincipalComponents,
+        ] {
+            let generator = DigitalShift::new(RandomSobol::new(times.len()), 1);
+            let mut the_engine = ExoticBSEngine::new_with_path_construction(
+                &times,
+                &r,
+                ParametersConstant::new(0.01),
+                ParametersConstant::new(0.2),
+                generator,
+                100.0,
+                path_construction,
+            );
+            let mut gatherer = StatisticsVariance::default();
+            the_engine.do_replicated_simulation(&data, &mut gatherer, 1024, 16);
+            let row = gatherer.get_results_so_far()[0].clone();
+            results.push((row[0], row[2]));
+        }
+        let (incremental, bridge, components) = (results[0], results[1], results[2]);
+        assert!(bridge.1 < 0.5 * incremental.1);
+        assert!(components.1 < 0.5 * incremental.1);
+        assert_relative_eq!(bridge.0, incremental.0, epsilon = 5.0 * incremental.1);
+        assert_relative_eq!(components.0, bridge.0, epsilon = 5.0 * bridge.1);
+    }
+}
@@ -1,13 +1,24 @@
 //! キャッシュフローを格納するVectorを1回のシミュレーションごとに作るのはコンストラクタとデストラクタの呼び出しに時間がかかるので、
 //! mutableなメンバ変数にしている。
+//! 並列化の際は、スレッド間で一つのgathererをMutexで共有すると毎パスごとにロックを取ることになるので、
+//! 各ワーカーが空のgathererに統計データを取り込み、最後に結合する。
+//! また、スレッドごとに乱数列をずらすと結果がスレッド数に依存してしまうので、
+//! パスをスレッド数によらない固定長のブロックに分け、各ブロックではエンジンのクローンをブロックの先頭のパスまでskipさせる。
+//! これによってi番目のパスは常に同じ乱数から生成される。
+//! 乱択化したLow Discrepancy Numberを用いる場合は、シードを変えた独立な複製ごとに価格を求め、
+//! その価格をgathererに渡すことで複製間のばらつきから標準誤差と信頼区間を得る。
 use crate::chapter4::parameters::Parameters;
-use crate::chapter5::mc_statistics::StatisticsMC;
+use crate::chapter5::mc_statistics::{MergeableStatisticsMC, StatisticsMC, StatisticsMean};
 use crate::chapter7::path_dependent::CashFlow;
 use crate::chapter7::path_dependent::PathDependent;
-use rayon::current_thread_index;
 use rayon::iter::IntoParallelIterator;
 use rayon::iter::ParallelIterator;
-use std::sync::{Arc, Mutex};
+use std::ops::Range;
+
+/// The number of paths simulated by each clone of an engine.
+const PATHS_PER_BLOCK: usize = 1024;
+/// The number of blocks whose results are buffered at once by `do_simulation_reproducible`.
+const BLOCKS_PER_BATCH: usize = 256;
 
 pub struct ExoticEngineData<'a, T: PathDependent + ?Sized> {
     /// A path dependent product such as Asian option
@@ -48,7 +59,43 @@ pub trait ExoticEngine<T: PathDependent + ?Sized>: Clone {
 
     fn skip(&mut self, number_of_paths: usize);
 
+    /// Simulates `number_of_paths` paths in parallel and gathers the results in `the_gatherer`.
+    ///
+    /// Each path gets the same variates regardless of the number of threads,
+    /// but the order of merging the gatherers, and hence the rounding, may depend on it.
     fn do_simulation(
+        &mut self,
+        data: &ExoticEngineData<T>,
+        the_gatherer: &mut impl MergeableStatisticsMC,
+        number_of_paths: usize,
+    ) where
+        Self: Sync,
+        Self: Send,
+    {
+        let empty_gatherer = the_gatherer.empty();
+        let merged_gatherer = (0..number_of_paths.div_ceil(PATHS_PER_BLOCK))
+            .into_par_iter()
+            .map(|block| {
+                let mut gatherer = empty_gatherer.empty();
+                simulate_block(self, data, block_range(block, number_of_paths), |value| {
+                    gatherer.dump_one_result(value)
+                });
+                gatherer
+            })
+            .reduce_with(|mut gatherer, other| {
+                gatherer.merge(other);
+                gatherer
+            });
+        if let Some(merged_gatherer) = merged_gatherer {
+            the_gatherer.merge(merged_gatherer);
+        }
+    }
+
+    /// Simulates `number_of_paths` paths in parallel and gathers the results in `the_gatherer` in the order of paths.
+    ///
+    /// The results are bit-identical to those of the serial simulation regardless of the number of threads,
+    /// and any gatherer such as `ConvergenceTable` records them exactly as in the serial simulation.
+    fn do_simulation_reproducible(
         &mut self,
         data: &ExoticEngineData<T>,
         the_gatherer: &mut impl StatisticsMC,
@@ -57,27 +104,146 @@ pub trait ExoticEngine<T: PathDependent + ?Sized>: Clone {
         Self: Sync,
         Self: Send,
     {
-        let length_of_times = data.the_product.get_look_at_times().len();
-        let max_number_of_cash_flows = data.the_product.max_number_of_cash_flows();
-        let the_gatherer_ptr = Arc::new(Mutex::new(the_gatherer));
-        (0..number_of_paths).into_par_iter().for_each_init(
-            || {
-                (
-                    vec![0.0; length_of_times],
-                    vec![CashFlow::default(); max_number_of_cash_flows],
-                    {
-                        let num_skip = current_thread_index().unwrap() * length_of_times;
-                        let mut ret = self.clone();
-                        ret.skip(num_skip);
-                        ret
-                    },
-                )
-            },
-            |(spot_values, these_cash_flows, cloned_self), _| {
-                cloned_self.get_one_path(spot_values);
-                let this_value = data.do_one_path(spot_values, these_cash_flows);
-                (*the_gatherer_ptr.lock().unwrap()).dump_one_result(this_value);
-            },
-        );
+        let number_of_blocks = number_of_paths.div_ceil(PATHS_PER_BLOCK);
+        for first_block in (0..number_of_blocks).step_by(BLOCKS_PER_BATCH) {
+            let last_block = (first_block + BLOCKS_PER_BATCH).min(number_of_blocks);
+            let values: Vec<Vec<f64>> = (first_block..last_block)
+                .into_par_iter()
+                .map(|block| {
+                    let mut values = Vec::with_capacity(PATHS_PER_BLOCK);
+                    simulate_block(self, data, block_range(block, number_of_paths), |value| {
+                        values.push(value)
+                    });
+                    values
+                })
+                .collect();
+            for value in values.into_iter().flatten() {
+                the_gatherer.dump_one_result(value);
+            }
+        }
+    }
+
+    /// Simulates `number_of_replicates` independent randomizations of `number_of_paths` paths
+    /// and gathers the price of each replicate in `the_gatherer`.
+    ///
+    /// The seed of the `i`-th replicate is `i + 1`, so the generator should be randomized by the seed,
+    /// e.g. `DigitalShift` or `RandomShift`.
+    /// Gathering by `StatisticsVariance` gives the price with the standard error and the confidence interval.
+    /// The engine is restored to the state before the simulation afterwards.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `number_of_replicates` is less than 2, from which the error can not be estimated.
+    fn do_replicated_simulation(
+        &mut self,
+        data: &ExoticEngineData<T>,
+        the_gatherer: &mut impl StatisticsMC,
+        number_of_paths: usize,
+        number_of_replicates: usize,
+    ) where
+        Self: Sync,
+        Self: Send,
+    {
+        if number_of_replicates < 2 {
+            panic!("At least 2 replicates are required, but got {number_of_replicates}.");
+        }
+        let original = self.clone();
+        for replicate in 0..number_of_replicates {
+            self.set_seed(replicate as u64 + 1);
+            let mut replicate_gatherer = StatisticsMean::default();
+            self.do_simulation(data, &mut replicate_gatherer, number_of_paths);
+            the_gatherer.dump_one_result(replicate_gatherer.get_results_so_far()[0][0]);
+        }
+        *self = original;
+    }
+}
+
+/// Returns the range of paths in `block`.
+fn block_range(block: usize, number_of_paths: usize) -> Range<usize> {
+    block * PATHS_PER_BLOCK..((block + 1) * PATHS_PER_BLOCK).min(number_of_paths)
+}
+
+/// Simulates `paths` by a clone of `engine` skipped to the first path and passes the discounted values to `dump`.
+fn simulate_block<T: PathDependent + ?Sized, E: ExoticEngine<T>>(
+    engine: &E,
+    data: &ExoticEngineData<T>,
+    paths: Range<usize>,
+    mut dump: impl FnMut(f64),
+) {
+    let mut engine = engine.clone();
+    engine.skip(paths.start);
+    let mut spot_values = vec![0.0; data.the_product.get_look_at_times().len()];
+    let mut these_cash_flows =
+        vec![CashFlow::default(); data.the_product.max_number_of_cash_flows()];
+    for _ in paths {
+        engine.get_one_path(&mut spot_values);
+        dump(data.do_one_path(&spot_values, &mut these_cash_flows));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chapter4::parameters::ParametersConstant;
+    use crate::chapter4::payoff3::{Payoff, PayoffCall};
+    use crate::chapter5::convergence_table::ConvergenceTable;
+    use crate::chapter5::mc_statistics::StatisticsMean;
+    use crate::chapter6::anti_thetic::AntiThetic;
+    use crate::chapter6::park_miller::RandomParkMiller;
+    use crate::chapter7::exotic_bs_engine::ExoticBSEngine;
+    use crate::chapter7::path_dependent_asian::PathDependentAsian;
+    use approx::assert_relative_eq;
+
+    const NUMBER_OF_PATHS: usize = 2500;
+
+    fn engine(times: &[f64]) -> ExoticBSEngine<AntiThetic<RandomParkMiller>> {
+        let r = ParametersConstant::new(0.05);
+        let d = ParametersConstant::new(0.01);
+        let vol = ParametersConstant::new(0.2);
+        let generator = AntiThetic::new(RandomParkMiller::new(times.len(), 1));
+        ExoticBSEngine::new(times, &r, d, vol, generator, 100.0)
+    }
+
+    #[test]
+    fn test_reproducible_simulation() {
+        let times = vec![0.25, 0.5, 0.75, 1.0];
+        let the_payoff = PayoffCall::new(100.0);
+        let the_option = PathDependentAsian::new(times.clone(), 1.0, &the_payoff);
+        let data = ExoticEngineData::new(&the_option, &ParametersConstant::new(0.05));
+
+        let mut serial_engine = engine(&times);
+        let mut serial_gatherer = ConvergenceTable::new(StatisticsMean::default());
+        let mut spot_values = vec![0.0; times.len()];
+        let mut cash_flows = vec![CashFlow::default(); 1];
+        for _ in 0..NUMBER_OF_PATHS {
+            ExoticEngine::<PathDependentAsian<PayoffCall>>::get_one_path(
+                &mut serial_engine,
+                &mut spot_values,
+            );
+            serial_gatherer.dump_one_result(data.do_one_path(&spot_values, &mut cash_flows));
+        }
+        let expected = serial_gatherer.get_results_so_far();
+
+        for number_of_threads in [1, 2, 3] {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(number_of_threads)
+                .build()
+                .unwrap();
+            let mut the_engine = engine(&times);
+            let mut gatherer = ConvergenceTable::new(StatisticsMean::default());
+            pool.install(|| {
+                the_engine.do_simulation_reproducible(&data, &mut gatherer, NUMBER_OF_PATHS)
+            });
+            assert_eq!(gatherer.get_results_so_far(), expected);
+
+            let mut the_engine = engine(&times);
+            let mut gatherer = StatisticsMean::default();
+            pool.install(|| the_engine.do_simulation(&data, &mut gatherer, NUMBER_OF_PATHS));
+            assert_relative_eq!(
+                gatherer.get_results_so_far()[0][0],
+                expected.last().unwrap()[0],
+                max_relative = 1e-12
+            );
+        }
     }
 }
@@ -0,0 +1,263 @@
+//! 正規乱数からブラウン運動の経路を作る方法。
+//! 時刻を分散の累積τ_j = ∫_0^{t_j} σ(s)^2 dsで測ると対数株価の拡散部分は標準ブラウン運動W(τ_j)になるので、
+//! 不等間隔の観測時刻やボラティリティの期間構造があっても同じ方法で扱える。ドリフトは別に加える。
+//! - Incremental: 前の時刻からの増分を順に作る。
+//! - BrownianBridge: 最初の乱数で満期の値を決め、次の乱数から区間の中点を順に条件付き分布から埋める。
+//! - PrincipalComponents: 共分散行列min(τ_i, τ_j)の固有値の大きい順に固有ベクトルを重ねる。準備にO(n^3)、各パスにO(n^2)かかる。
+//!
+//! 後の二つは経路の大きな動きを最初の数次元に集めるので、Low Discrepancy Numberの一様性の良い最初の次元を有効に使える。
+//! どの方法も一つのパスに使う正規乱数の数は観測時刻の数に等しく、パス単位のskipはそのまま使える。
+
+/// The methods to construct Brownian paths from standard normal variables.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum PathConstruction {
+    #[default]
+    Incremental,
+    BrownianBridge,
+    PrincipalComponents,
+}
+
+/// One step of a Brownian bridge, which fills `index` from the values at `left` and `right`.
+#[derive(Clone)]
+struct BridgeStep {
+    index: usize,
+    /// The index of the left end, or `None` for the origin
+    left: Option<usize>,
+    right: usize,
+    left_weight: f64,
+    right_weight: f64,
+    standard_deviation: f64,
+}
+
+/// A Brownian bridge over the given cumulative variances.
+#[derive(Clone)]
+pub struct BrownianBridge {
+    /// The standard deviation of the last value
+    final_standard_deviation: f64,
+    steps: Vec<BridgeStep>,
+}
+
+impl BrownianBridge {
+    /// Constructor.
+    ///
+    /// # Arguments
+    ///
+    /// * `cumulative_variances` - Non-decreasing variances of the Brownian motion at each time
+    pub fn new(cumulative_variances: &[f64]) -> Self {
+        let n = cumulative_variances.len();
+        let variance_at = |index: Option<usize>| index.map_or(0.0, |i| cumulative_variances[i]);
+        let mut steps = Vec::with_capacity(n.saturating_sub(1));
+        // Intervals of indices whose ends are known, where `None` is the origin.
+        let mut intervals = std::collections::VecDeque::new();
+        if n > 0 {
+            intervals.push_back((None, n - 1));
+        }
+        while let Some((left, right)) = intervals.pop_front() {
+            let first = left.map_or(0, |l| l + 1);
+            if first >= right {
+                continue;
+            }
+            let index = (first + right - 1) / 2;
+            let (left_variance, variance, right_variance) = (
+                variance_at(left),
+                cumulative_variances[index],
+                cumulative_variances[right],
+            );
+            let length = right_variance - left_variance;
+            let (left_weight, right_weight, standard_deviation) = if length > 0.0 {
+                (
+                    (right_variance - variance) / length,
+                    (variance - left_variance) / length,
+                    ((variance - left_variance) * (right_variance - variance) / length).sqrt(),
+                )
+            } else {
+                (1.0, 0.0, 0.0)
+            };
+            steps.push(BridgeStep {
+                index,
+                left,
+                right,
+                left_weight,
+                right_weight,
+                standard_deviation,
+            });
+            intervals.push_back((left, index));
+            intervals.push_back((Some(index), right));
+        }
+        BrownianBridge {
+            final_standard_deviation: cumulative_variances.last().map_or(0.0, |v| v.sqrt()),
+            steps,
+        }
+    }
+
+    /// Stores the Brownian path built from `variates` in `path`.
+    pub fn build(&self, variates: &[f64], path: &mut [f64]) {
+        let Some(last) = path.len().checked_sub(1) else {
+            return;
+        };
+        path[last] = self.final_standard_deviation * variates[0];
+        for (step, variate) in self.steps.iter().zip(&variates[1..]) {
+            let left_value = step.left.map_or(0.0, |l| path[l]);
+            path[step.index] = step.left_weight * left_value
+                + step.right_weight * path[step.right]
+                + step.standard_deviation * variate;
+        }
+    }
+}
+
+/// The principal component construction over the given cumulative variances.
+#[derive(Clone)]
+pub struct PrincipalComponents {
+    /// Eigenvectors scaled by the square roots of the eigenvalues, in decreasing order of the eigenvalues
+    loadings: Vec<Vec<f64>>,
+}
+
+impl PrincipalComponents {
+    /// Constructor.
+    ///
+    /// # Arguments
+    ///
+    /// * `cumulative_variances` - Non-decreasing variances of the Brownian motion at each time
+    pub fn new(cumulative_variances: &[f64]) -> Self {
+        let covariance: Vec<Vec<f64>> = cumulative_variances
+            .iter()
+            .map(|&a| cumulative_variances.iter().map(|&b| a.min(b)).collect())
+            .collect();
+        let (eigenvalues, eigenvectors) = symmetric_eigen(covariance);
+        let mut order: Vec<usize> = (0..eigenvalues.len()).collect();
+        order.sort_by(|&a, &b| eigenvalues[b].total_cmp(&eigenvalues[a]));
+        let loadings = order
+            .into_iter()
+            .map(|k| {
+                let scale = eigenvalues[k].max(0.0).sqrt();
+                eigenvectors.iter().map(|row| row[k] * scale).collect()
+            })
+            .collect();
+        PrincipalComponents { loadings }
+    }
+
+    /// Stores the Brownian path built from `variates` in `path`.
+    pub fn build(&self, variates: &[f64], path: &mut [f64]) {
+        path.iter_mut().for_each(|value| *value = 0.0);
+        for (loading, variate) in self.loadings.iter().zip(variates) {
+            for (value, l) in path.iter_mut().zip(loading) {
+                *value += l * variate;
+            }
+        }
+    }
+}
+
+/// Returns the eigenvalues and the eigenvectors as columns of a symmetric matrix by the cyclic Jacobi method.
+fn symmetric_eigen(mut a: Vec<Vec<f64>>) -> (Vec<f64>, Vec<Vec<f64>>) {
+    const MAX_SWEEPS: usize = 100;
+    let n = a.len();
+    let mut v: Vec<Vec<f64>> = (0..n)
+        .map(|i| (0..n).map(|j| if i == j { 1.0 } else { 0.0 }).collect())
+        .collect();
+    let norm: f64 = a.iter().flatten().map(|x| x * x).sum();
+    for _ in 0..MAX_SWEEPS {
+        let off_diagonal: f64 = (0..n)
+            .flat_map(|i| (0..n).filter(move |&j| j != i).map(move |j| (i, j)))
+            .map(|(i, j)| a[i][j] * a[i][j])
+            .sum();
+        if off_diagonal <= f64::EPSILON * f64::EPSILON * norm {
+            break;
+        }
+        for p in 0..n {
+            for q in p + 1..n {
+                if a[p][q] == 0.0 {
+                    continue;
+                }
+                // The rotation which annihilates a[p][q].
+                let theta = (a[q][q] - a[p][p]) / (2.0 * a[p][q]);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                let c = 1.0 / (t * t + 1.0).sqrt();
+                let s = t * c;
+                for row in a.iter_mut() {
+                    let (akp, akq) = (row[p], row[q]);
+                    row[p] = c * akp - s * akq;
+                    row[q] = s * akp + c * akq;
+                }
+                let (upper, lower) = a.split_at_mut(q);
+                for (apk, aqk) in upper[p].iter_mut().zip(lower[0].iter_mut()) {
+                    let (x, y) = (*apk, *aqk);
+                    *apk = c * x - s * y;
+                    *aqk = s * x + c * y;
+                }
+                for row in v.iter_mut() {
+                    let (vp, vq) = (row[p], row[q]);
+                    row[p] = c * vp - s * vq;
+                    row[q] = s * vp + c * vq;
+                }
+            }
+        }
+    }
+    ((0..n).map(|i| a[i][i]).collect(), v)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    /// Returns the covariance matrix of the path linear in standard normal variables built by `build`.
+    fn covariance(n: usize, build: impl Fn(&[f64], &mut [f64])) -> Vec<Vec<f64>> {
+        let columns: Vec<Vec<f64>> = (0..n)
+            .map(|k| {
+                let unit: Vec<f64> = (0..n).map(|i| if i == k { 1.0 } else { 0.0 }).collect();
+                let mut path = vec![0.0; n];
+                build(&unit, &mut path);
+                path
+            })
+            .collect();
+        (0..n)
+            .map(|i| {
+                (0..n)
+                    .map(|j| columns.iter().map(|c| c[i] * c[j]).sum())
+                    .collect()
+            })
+            .collect()
+    }
+
+    const VARIANCES: [f64; 7] = [0.01, 0.05, 0.05, 0.2, 0.21, 0.5, 1.3];
+
+    #[test]
+    fn test_brownian_bridge_covariance() {
+        let bridge = BrownianBridge::new(&VARIANCES);
+        assert_eq!(bridge.steps.len(), VARIANCES.len() - 1);
+        let c = covariance(VARIANCES.len(), |z, w| bridge.build(z, w));
+        for i in 0..VARIANCES.len() {
+            for j in 0..VARIANCES.len() {
+                assert_relative_eq!(c[i][j], VARIANCES[i].min(VARIANCES[j]), epsilon = 1e-14);
+            }
+        }
+    }
+
+    #[test]
+    fn test_principal_components_covariance() {
+        let components = PrincipalComponents::new(&VARIANCES);
+        let c = covariance(VARIANCES.len(), |z, w| components.build(z, w));
+        for i in 0..VARIANCES.len() {
+            for j in 0..VARIANCES.len() {
+                assert_relative_eq!(c[i][j], VARIANCES[i].min(VARIANCES[j]), epsilon = 1e-12);
+            }
+        }
+        // The first component explains the most variance.
+        let explained: Vec<f64> = components
+            .loadings
+            .iter()
+            .map(|l| l.iter().map(|x| x * x).sum())
+            .collect();
+        assert!(explained.windows(2).all(|w| w[0] >= w[1]));
+    }
+
+    #[test]
+    fn test_brownian_bridge_first_variate_gives_final_value() {
+        let bridge = BrownianBridge::new(&[0.25, 0.5, 0.75, 1.0]);
+        let mut path = vec![0.0; 4];
+        bridge.build(&[2.0, 0.0, 0.0, 0.0], &mut path);
+        for (value, expected) in path.iter().zip([0.5, 1.0, 1.5, 2.0]) {
+            assert_relative_eq!(*value, expected, epsilon = 1e-15);
+        }
+    }
+}
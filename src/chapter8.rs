@@ -0,0 +1,4 @@
+pub mod binomial_tree;
+pub mod tree_american;
+pub mod tree_european;
+pub mod tree_products;
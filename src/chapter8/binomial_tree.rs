@@ -0,0 +1,177 @@
+//! 対数株価が各ステップで同じ確率1/2で上下に動く再結合型の二項ツリー。
+//! ツリーの構築は商品に依存しないので、コンストラクタで一度だけ行い、
+//! 同じ満期の複数の商品をget_the_priceで繰り返し評価できるようにした。
+//! 各ノードにはスポット値と商品の価値のペアを保持し、価値は評価のたびに上書きする。
+use crate::chapter4::parameters::Parameters;
+use crate::chapter8::tree_products::TreeProduct;
+
+pub struct SimpleBinomialTree {
+    /// The number of time steps
+    steps: usize,
+    /// The final time of the tree
+    time: f64,
+    /// Pairs of a spot value and a product value at each node
+    the_tree: Vec<Vec<(f64, f64)>>,
+    /// Discount factors from each step to the previous one
+    discounts: Vec<f64>,
+}
+
+impl SimpleBinomialTree {
+    /// Constructor.
+    ///
+    /// # Arguments
+    ///
+    /// * `spot` - A spot value of a stock
+    /// * `r` - An interest rate
+    /// * `d` - A dividend
+    /// * `volatility` - A volatility
+    /// * `steps` - The number of time steps
+    /// * `time` - The final time of the tree
+    ///
+    /// # Panics
+    ///
+    /// Panics if `steps` is 0.
+    pub fn new(
+        spot: f64,
+        r: &impl Parameters,
+        d: &impl Parameters,
+        volatility: f64,
+        steps: usize,
+        time: f64,
+    ) -> SimpleBinomialTree {
+        if steps == 0 {
+            panic!("The number of time steps must be positive, but got {steps}.");
+        }
+        let initial_log_spot = spot.ln();
+        let sd = volatility * (time / steps as f64).sqrt();
+        let the_tree = (0..=steps)
+            .map(|i| {
+                let this_time = i as f64 * time / steps as f64;
+                let moved_log_spot = initial_log_spot + r.integral(0.0, this_time)
+                    - d.integral(0.0, this_time)
+                    - 0.5 * volatility * volatility * this_time;
+                (0..=i)
+                    .map(|k| {
+                        let j = 2.0 * k as f64 - i as f64;
+                        ((moved_log_spot + j * sd).exp(), 0.0)
+                    })
+                    .collect()
+            })
+            .collect();
+        let discounts = (0..steps)
+            .map(|l| {
+                let time1 = l as f64 * time / steps as f64;
+                let time2 = (l + 1) as f64 * time / steps as f64;
+                (-r.integral(time1, time2)).exp()
+            })
+            .collect();
+        SimpleBinomialTree {
+            steps,
+            time,
+            the_tree,
+            discounts,
+        }
+    }
+
+    /// Returns the price of `the_product` by backward induction.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the final time of `the_product` differs from that of the tree.
+    pub fn get_the_price(&mut self, the_product: &impl TreeProduct) -> f64 {
+        if the_product.get_final_time() != self.time {
+            panic!(
+                "The final time of the product must equal to that of the tree {time}, but got {final_time}.",
+                time = self.time,
+                final_time = the_product.get_final_time()
+            );
+        }
+        for node in self.the_tree[self.steps].iter_mut() {
+            node.1 = the_product.final_payoff(node.0);
+        }
+        for index in (0..self.steps).rev() {
+            let this_time = index as f64 * self.time / self.steps as f64;
+            let (current, next) = self.the_tree.split_at_mut(index + 1);
+            for (k, node) in current[index].iter_mut().enumerate() {
+                let future_discounted_value =
+                    0.5 * self.discounts[index] * (next[0][k].1 + next[0][k + 1].1);
+                node.1 = the_product.pre_final_value(node.0, this_time, future_discounted_value);
+            }
+        }
+        self.the_tree[0][0].1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chapter4::parameters::ParametersConstant;
+    use crate::chapter4::payoff3::{Payoff, PayoffCall, PayoffPut};
+    use crate::chapter8::tree_american::TreeAmerican;
+    use crate::chapter8::tree_european::TreeEuropean;
+    use crate::chapter9::black_scholes_formulas::black_scholes_call;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_put_call_parity() {
+        let (spot, strike, expiry) = (100.0, 95.0, 1.0);
+        let r = ParametersConstant::new(0.05);
+        let d = ParametersConstant::new(0.02);
+        let mut tree = SimpleBinomialTree::new(spot, &r, &d, 0.2, 500, expiry);
+        let call = PayoffCall::new(strike);
+        let put = PayoffPut::new(strike);
+        let call_price = tree.get_the_price(&TreeEuropean::new(expiry, &call));
+        let put_price = tree.get_the_price(&TreeEuropean::new(expiry, &put));
+        let forward = spot * (-0.02 * expiry).exp() - strike * (-0.05 * expiry).exp();
+        assert_relative_eq!(call_price - put_price, forward, epsilon = 1e-3);
+    }
+
+    #[test]
+    fn test_european_call() {
+        let r = ParametersConstant::new(0.05);
+        let d = ParametersConstant::new(0.0);
+        let mut tree = SimpleBinomialTree::new(100.0, &r, &d, 0.2, 1000, 1.0);
+        let call = PayoffCall::new(100.0);
+        let price = tree.get_the_price(&TreeEuropean::new(1.0, &call));
+        let vol = ParametersConstant::new(0.2);
+        let expected = black_scholes_call(100.0, 100.0, &r, &d, &vol, 1.0);
+        assert_relative_eq!(price, expected, epsilon = 1e-2);
+    }
+
+    #[test]
+    fn test_american() {
+        let r = ParametersConstant::new(0.05);
+        let d = ParametersConstant::new(0.0);
+        let mut tree = SimpleBinomialTree::new(100.0, &r, &d, 0.2, 500, 1.0);
+        let call = PayoffCall::new(100.0);
+        let put = PayoffPut::new(100.0);
+
+        // Early exercise of a call is never optimal without dividends.
+        let european_call = tree.get_the_price(&TreeEuropean::new(1.0, &call));
+        let american_call = tree.get_the_price(&TreeAmerican::new(1.0, &call));
+        assert_relative_eq!(european_call, american_call, epsilon = 1e-12);
+
+        let european_put = tree.get_the_price(&TreeEuropean::new(1.0, &put));
+        let american_put = tree.get_the_price(&TreeAmerican::new(1.0, &put));
+        assert!(american_put > european_put);
+        assert!(american_put >= put.calculate(100.0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_final_time_mismatch() {
+        let r = ParametersConstant::new(0.05);
+        let d = ParametersConstant::new(0.0);
+        let mut tree = SimpleBinomialTree::new(100.0, &r, &d, 0.2, 10, 1.0);
+        let call = PayoffCall::new(100.0);
+        tree.get_the_price(&TreeEuropean::new(2.0, &call));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_zero_steps() {
+        let r = ParametersConstant::new(0.05);
+        let d = ParametersConstant::new(0.0);
+        SimpleBinomialTree::new(100.0, &r, &d, 0.2, 0, 1.0);
+    }
+}
@@ -0,0 +1,32 @@
+//! 各ノードで行使することができるので、各ノードの価値は割り引かれた将来価値と行使価値の大きい方になる。
+use crate::chapter4::payoff3::Payoff;
+use crate::chapter8::tree_products::TreeProduct;
+
+/// An American option priced on a tree.
+pub struct TreeAmerican<'a, T: Payoff + ?Sized> {
+    final_time: f64,
+    the_payoff: &'a T,
+}
+
+impl<'a, T: Payoff + ?Sized> TreeAmerican<'a, T> {
+    pub fn new(final_time: f64, the_payoff: &'a T) -> Self {
+        TreeAmerican {
+            final_time,
+            the_payoff,
+        }
+    }
+}
+
+impl<'a, T: Payoff + ?Sized> TreeProduct for TreeAmerican<'a, T> {
+    fn get_final_time(&self) -> f64 {
+        self.final_time
+    }
+
+    fn final_payoff(&self, spot: f64) -> f64 {
+        self.the_payoff.calculate(spot)
+    }
+
+    fn pre_final_value(&self, spot: f64, _time: f64, discounted_future_value: f64) -> f64 {
+        self.the_payoff.calculate(spot).max(discounted_future_value)
+    }
+}
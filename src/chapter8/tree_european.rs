@@ -0,0 +1,32 @@
+//! 満期以前には行使できないので、各ノードの価値は割り引かれた将来価値そのものになる。
+use crate::chapter4::payoff3::Payoff;
+use crate::chapter8::tree_products::TreeProduct;
+
+/// A European option priced on a tree.
+pub struct TreeEuropean<'a, T: Payoff + ?Sized> {
+    final_time: f64,
+    the_payoff: &'a T,
+}
+
+impl<'a, T: Payoff + ?Sized> TreeEuropean<'a, T> {
+    pub fn new(final_time: f64, the_payoff: &'a T) -> Self {
+        TreeEuropean {
+            final_time,
+            the_payoff,
+        }
+    }
+}
+
+impl<'a, T: Payoff + ?Sized> TreeProduct for TreeEuropean<'a, T> {
+    fn get_final_time(&self) -> f64 {
+        self.final_time
+    }
+
+    fn final_payoff(&self, spot: f64) -> f64 {
+        self.the_payoff.calculate(spot)
+    }
+
+    fn pre_final_value(&self, _spot: f64, _time: f64, discounted_future_value: f64) -> f64 {
+        discounted_future_value
+    }
+}
@@ -0,0 +1,27 @@
+//! 二項ツリーで評価する商品のインターフェース。
+//! ツリーの各ノードでは、満期においてはペイオフを、それ以前においては割り引かれた将来価値から現在価値を求める必要がある。
+//! ヨーロピアンなら割り引かれた将来価値をそのまま、アメリカンなら行使価値との大きい方を返す、というように、
+//! 商品ごとに異なるのはこの二つの処理だけなので、これらをtraitのメソッドとして定義する。
+//! ツリーの構築やバックワードインダクションはエンジン側(binomial_tree.rs)に任せる。
+
+/// A product which can be priced on a recombining tree.
+pub trait TreeProduct: Send + Sync {
+    /// Returns the time at which the product expires.
+    fn get_final_time(&self) -> f64;
+
+    /// Returns the value of the product at the final time.
+    ///
+    /// # Arguments
+    ///
+    /// * `spot` - A spot value at the final time
+    fn final_payoff(&self, spot: f64) -> f64;
+
+    /// Returns the value of the product at a node before the final time.
+    ///
+    /// # Arguments
+    ///
+    /// * `spot` - A spot value at the node
+    /// * `time` - The time of the node
+    /// * `discounted_future_value` - The expected value of the product at the next step, discounted to `time`
+    fn pre_final_value(&self, spot: f64, time: f64, discounted_future_value: f64) -> f64;
+}
@@ -1 +1,10 @@
-mod bisection;
+pub mod bisection;
+pub mod black_scholes_formulas;
+pub mod brent;
+pub mod implied_volatility;
+pub mod implied_volatility_error;
+pub mod newton_raphson;
+pub mod ridders;
+pub mod secant;
+pub mod solver;
+pub mod solver_error;
@@ -1,28 +1,89 @@
 //! クラスごとにメソッド関数としてbisectionを定義するとvtableを参照する分遅いなど、いろいろ不便なのでテンプレートによって実装する。
-#[allow(unused)]
-pub fn bisection<T: Fn(f64) -> f64>(
-    target: f64,
+//! 目標値が区間の両端の関数値で挟まれていない場合や、収束しない場合に無限ループに陥らないよう、
+//! 事前に区間を検証し、反復回数に上限を設けてResultを返す。
+use crate::chapter9::solver::{check_bracket, evaluate, Solver, SolverSettings};
+use crate::chapter9::solver_error::SolverError;
+use crate::chapter9::solver_error::SolverError::NotConverged;
+
+/// The bisection method, which halves the bracketing interval at each iteration.
+#[derive(Debug, Clone, Copy)]
+pub struct Bisection {
+    /// The lower end of the initial interval
     low: f64,
+    /// The upper end of the initial interval
     high: f64,
-    tolerance: f64,
-    the_function: T,
-) -> f64 {
-    let mut low = low;
-    let mut high = high;
-    let mut x = 0.5 * (low + high);
-    let mut y = the_function(x);
-    loop {
-        if y < target {
-            low = x;
+    settings: SolverSettings,
+}
+
+impl Bisection {
+    pub fn new(low: f64, high: f64, settings: SolverSettings) -> Self {
+        Bisection {
+            low,
+            high,
+            settings,
+        }
+    }
+}
+
+impl Solver for Bisection {
+    fn solve<T: Fn(f64) -> f64>(&self, target: f64, the_function: T) -> Result<f64, SolverError> {
+        let mut low = self.low;
+        let mut high = self.high;
+        let y_low = evaluate(&the_function, target, low)?;
+        let y_high = evaluate(&the_function, target, high)?;
+        if y_low.abs() <= self.settings.function_tolerance {
+            return Ok(low);
         }
-        if y > target {
-            high = x;
+        if y_high.abs() <= self.settings.function_tolerance {
+            return Ok(high);
         }
-        x = 0.5 * (low + high);
-        y = the_function(x);
-        if (y - target).abs() <= tolerance {
-            break;
+        check_bracket(target, low, high, y_low, y_high)?;
+        let increasing = y_low < 0.0;
+        for _ in 0..self.settings.max_iterations {
+            let x = 0.5 * (low + high);
+            let y = evaluate(&the_function, target, x)?;
+            if y.abs() <= self.settings.function_tolerance
+                || 0.5 * (high - low).abs() <= self.settings.x_tolerance
+            {
+                return Ok(x);
+            }
+            if (y < 0.0) == increasing {
+                low = x;
+            } else {
+                high = x;
+            }
         }
+        Err(NotConverged(self.settings.max_iterations))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chapter9::solver_error::SolverError::BracketError;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_bisection() {
+        let solver = Bisection::new(0.0, 2.0, SolverSettings::default());
+        let x = solver.solve(2.0, |x| x * x).unwrap();
+        assert_relative_eq!(x, 2.0_f64.sqrt(), epsilon = 1e-10);
+        let x = solver.solve(-2.0, |x| -x * x).unwrap();
+        assert_relative_eq!(x, 2.0_f64.sqrt(), epsilon = 1e-10);
+    }
+
+    #[test]
+    fn test_bisection_errors() {
+        let solver = Bisection::new(0.0, 2.0, SolverSettings::default());
+        assert_eq!(
+            solver.solve(5.0, |x| x * x),
+            Err(BracketError {
+                target: 5.0,
+                low: 0.0,
+                high: 2.0
+            })
+        );
+        let solver = Bisection::new(0.0, 2.0, SolverSettings::new(1e-12, 0.0, 3));
+        assert_eq!(solver.solve(2.0, |x| x * x), Err(NotConverged(3)));
     }
-    x
 }
@@ -0,0 +1,443 @@
+//! Black-Scholesモデルにおけるヨーロピアンオプションの解析解とグリークス。
+//! モンテカルロ法やツリーによる数値解の検証に用いる。
+//!
+//! 金利r、配当d、ボラティリティvolはParametersとして受け取り、満期までの積分値だけを用いる。
+//! したがって期間構造を持つ場合でも価格は正確であり、
+//! グリークスは満期までの平均値(r、d)と二乗平均平方根(vol)を定数とみなしたときの感応度として定義する。
+//!
+//! 満期が0またはボラティリティが0で分散が0の場合は、d1とd2をフォワードのマネーネスの符号に応じた無限大(アット・ザ・マネーでは0)とし、
+//! 価格は割り引いたフォワードの本質的価値、グリークスは分散を0に近づけたときの極限を返す。
+use crate::chapter4::parameters::Parameters;
+use crate::chapter6::normals::{cumulative_normal, normal_density};
+
+/// Quantities shared by the formulas.
+struct BlackScholesInputs {
+    /// Discount factor of the interest rate up to the expiry
+    discount: f64,
+    /// Discount factor of the dividend up to the expiry
+    dividend_discount: f64,
+    /// Standard deviation of the logarithm of the spot at the expiry
+    standard_deviation: f64,
+    /// d1, which is infinite with the sign of the forward moneyness if the standard deviation is 0
+    d1: f64,
+    d2: f64,
+}
+
+impl BlackScholesInputs {
+    fn new(
+        spot: f64,
+        strike: f64,
+        r: &impl Parameters,
+        d: &impl Parameters,
+        vol: &impl Parameters,
+        expiry: f64,
+    ) -> Self {
+        let r_integral = r.integral(0.0, expiry);
+        let d_integral = d.integral(0.0, expiry);
+        let variance = vol.integral_square(0.0, expiry);
+        let standard_deviation = variance.sqrt();
+        let log_moneyness = (spot / strike).ln() + r_integral - d_integral;
+        let d1 = if standard_deviation > 0.0 {
+            (log_moneyness + 0.5 * variance) / standard_deviation
+        } else if log_moneyness == 0.0 {
+            0.0
+        } else {
+            log_moneyness.signum() * f64::INFINITY
+        };
+        BlackScholesInputs {
+            discount: (-r_integral).exp(),
+            dividend_discount: (-d_integral).exp(),
+            standard_deviation,
+            d1,
+            d2: d1 - standard_deviation,
+        }
+    }
+
+    /// Returns the part of the theta from the decay of the volatility, which is common to calls and puts.
+    ///
+    /// At the money with zero variance, it is negative infinity at the expiry if the volatility is positive, and 0 otherwise.
+    fn volatility_theta(&self, spot: f64, vol: &impl Parameters, expiry: f64) -> f64 {
+        if self.standard_deviation > 0.0 {
+            -spot * self.dividend_discount * normal_density(self.d1) * self.standard_deviation
+                / (2.0 * expiry)
+        } else if self.d1 == 0.0 && vol.root_mean_square(0.0, expiry) > 0.0 {
+            f64::NEG_INFINITY
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Returns the price of a European call option.
+///
+/// # Arguments
+///
+/// * `spot` - A spot value of a stock
+/// * `strike` - A strike
+/// * `r` - An interest rate
+/// * `d` - A dividend
+/// * `vol` - A volatility
+/// * `expiry` - An expiry
+pub fn black_scholes_call(
+    spot: f64,
+    strike: f64,
+    r: &impl Parameters,
+    d: &impl Parameters,
+    vol: &impl Parameters,
+    expiry: f64,
+) -> f64 {
+    let inputs = BlackScholesInputs::new(spot, strike, r, d, vol, expiry);
+    spot * inputs.dividend_discount * cumulative_normal(inputs.d1)
+        - strike * inputs.discount * cumulative_normal(inputs.d2)
+}
+
+/// Returns the price of a European put option.
+pub fn black_scholes_put(
+    spot: f64,
+    strike: f64,
+    r: &impl Parameters,
+    d: &impl Parameters,
+    vol: &impl Parameters,
+    expiry: f64,
+) -> f64 {
+    let inputs = BlackScholesInputs::new(spot, strike, r, d, vol, expiry);
+    strike * inputs.discount * cumulative_normal(-inputs.d2)
+        - spot * inputs.dividend_discount * cumulative_normal(-inputs.d1)
+}
+
+/// Returns the sensitivity of a call price to the spot.
+pub fn black_scholes_call_delta(
+    spot: f64,
+    strike: f64,
+    r: &impl Parameters,
+    d: &impl Parameters,
+    vol: &impl Parameters,
+    expiry: f64,
+) -> f64 {
+    let inputs = BlackScholesInputs::new(spot, strike, r, d, vol, expiry);
+    inputs.dividend_discount * cumulative_normal(inputs.d1)
+}
+
+/// Returns the sensitivity of a put price to the spot.
+pub fn black_scholes_put_delta(
+    spot: f64,
+    strike: f64,
+    r: &impl Parameters,
+    d: &impl Parameters,
+    vol: &impl Parameters,
+    expiry: f64,
+) -> f64 {
+    let inputs = BlackScholesInputs::new(spot, strike, r, d, vol, expiry);
+    -inputs.dividend_discount * cumulative_normal(-inputs.d1)
+}
+
+/// Returns the second order sensitivity to the spot, which is common to calls and puts.
+pub fn black_scholes_gamma(
+    spot: f64,
+    strike: f64,
+    r: &impl Parameters,
+    d: &impl Parameters,
+    vol: &impl Parameters,
+    expiry: f64,
+) -> f64 {
+    let inputs = BlackScholesInputs::new(spot, strike, r, d, vol, expiry);
+    if inputs.standard_deviation > 0.0 {
+        inputs.dividend_discount * normal_density(inputs.d1) / (spot * inputs.standard_deviation)
+    } else if inputs.d1 == 0.0 {
+        f64::INFINITY
+    } else {
+        0.0
+    }
+}
+
+/// Returns the sensitivity to the root mean square of the volatility, which is common to calls and puts.
+pub fn black_scholes_vega(
+    spot: f64,
+    strike: f64,
+    r: &impl Parameters,
+    d: &impl Parameters,
+    vol: &impl Parameters,
+    expiry: f64,
+) -> f64 {
+    let inputs = BlackScholesInputs::new(spot, strike, r, d, vol, expiry);
+    spot * inputs.dividend_discount * normal_density(inputs.d1) * expiry.sqrt()
+}
+
+/// Returns the sensitivity of a call price to the passage of time.
+pub fn black_scholes_call_theta(
+    spot: f64,
+    strike: f64,
+    r: &impl Parameters,
+    d: &impl Parameters,
+    vol: &impl Parameters,
+    expiry: f64,
+) -> f64 {
+    let inputs = BlackScholesInputs::new(spot, strike, r, d, vol, expiry);
+    let r_mean = r.mean(0.0, expiry);
+    let d_mean = d.mean(0.0, expiry);
+    inputs.volatility_theta(spot, vol, expiry)
+        - r_mean * strike * inputs.discount * cumulative_normal(inputs.d2)
+        + d_mean * spot * inputs.dividend_discount * cumulative_normal(inputs.d1)
+}
+
+/// Returns the sensitivity of a put price to the passage of time.
+pub fn black_scholes_put_theta(
+    spot: f64,
+    strike: f64,
+    r: &impl Parameters,
+    d: &impl Parameters,
+    vol: &impl Parameters,
+    expiry: f64,
+) -> f64 {
+    let inputs = BlackScholesInputs::new(spot, strike, r, d, vol, expiry);
+    let r_mean = r.mean(0.0, expiry);
+    let d_mean = d.mean(0.0, expiry);
+    inputs.volatility_theta(spot, vol, expiry)
+        + r_mean * strike * inputs.discount * cumulative_normal(-inputs.d2)
+        - d_mean * spot * inputs.dividend_discount * cumulative_normal(-inputs.d1)
+}
+
+/// Returns the sensitivity of a call price to the mean of the interest rate.
+pub fn black_scholes_call_rho(
+    spot: f64,
+    strike: f64,
+    r: &impl Parameters,
+    d: &impl Parameters,
+    vol: &impl Parameters,
+    expiry: f64,
+) -> f64 {
+    let inputs = BlackScholesInputs::new(spot, strike, r, d, vol, expiry);
+    strike * expiry * inputs.discount * cumulative_normal(inputs.d2)
+}
+
+/// Returns the sensitivity of a put price to the mean of the interest rate.
+pub fn black_scholes_put_rho(
+    spot: f64,
+    strike: f64,
+    r: &impl Parameters,
+    d: &impl Parameters,
+    vol: &impl Parameters,
+    expiry: f64,
+) -> f64 {
+    let inputs = BlackScholesInputs::new(spot, strike, r, d, vol, expiry);
+    -strike * expiry * inputs.discount * cumulative_normal(-inputs.d2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chapter4::parameters::ParametersConstant;
+    use approx::assert_relative_eq;
+
+    const SPOT: f64 = 100.0;
+    const STRIKE: f64 = 100.0;
+    const EXPIRY: f64 = 1.0;
+    const BUMP: f64 = 1e-4;
+
+    fn call(spot: f64, r: f64, d: f64, vol: f64, expiry: f64) -> f64 {
+        black_scholes_call(
+            spot,
+            STRIKE,
+            &ParametersConstant::new(r),
+            &ParametersConstant::new(d),
+            &ParametersConstant::new(vol),
+            expiry,
+        )
+    }
+
+    fn put(spot: f64, r: f64, d: f64, vol: f64, expiry: f64) -> f64 {
+        black_scholes_put(
+            spot,
+            STRIKE,
+            &ParametersConstant::new(r),
+            &ParametersConstant::new(d),
+            &ParametersConstant::new(vol),
+            expiry,
+        )
+    }
+
+    #[test]
+    fn test_prices() {
+        assert_relative_eq!(
+            call(SPOT, 0.05, 0.0, 0.2, EXPIRY),
+            10.450583572185565,
+            epsilon = 1e-4
+        );
+        assert_relative_eq!(
+            put(SPOT, 0.05, 0.0, 0.2, EXPIRY),
+            5.573526022256971,
+            epsilon = 1e-4
+        );
+    }
+
+    #[test]
+    fn test_put_call_parity() {
+        let (r, d) = (0.03, 0.01);
+        let forward = SPOT * (-d * EXPIRY).exp() - STRIKE * (-r * EXPIRY).exp();
+        assert_relative_eq!(
+            call(SPOT, r, d, 0.3, EXPIRY) - put(SPOT, r, d, 0.3, EXPIRY),
+            forward,
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
+    fn test_greeks() {
+        let (r, d, vol) = (0.03, 0.01, 0.25);
+        let r_param = ParametersConstant::new(r);
+        let d_param = ParametersConstant::new(d);
+        let vol_param = ParametersConstant::new(vol);
+        let args = (&r_param, &d_param, &vol_param);
+        let central = |f: &dyn Fn(f64) -> f64, x: f64| (f(x + BUMP) - f(x - BUMP)) / (2.0 * BUMP);
+
+        let call_delta = central(&|s| call(s, r, d, vol, EXPIRY), SPOT);
+        let put_delta = central(&|s| put(s, r, d, vol, EXPIRY), SPOT);
+        assert_relative_eq!(
+            black_scholes_call_delta(SPOT, STRIKE, args.0, args.1, args.2, EXPIRY),
+            call_delta,
+            max_relative = 1e-4
+        );
+        assert_relative_eq!(
+            black_scholes_put_delta(SPOT, STRIKE, args.0, args.1, args.2, EXPIRY),
+            put_delta,
+            max_relative = 1e-4
+        );
+
+        let gamma = central(
+            &|s| black_scholes_call_delta(s, STRIKE, args.0, args.1, args.2, EXPIRY),
+            SPOT,
+        );
+        assert_relative_eq!(
+            black_scholes_gamma(SPOT, STRIKE, args.0, args.1, args.2, EXPIRY),
+            gamma,
+            max_relative = 1e-4
+        );
+
+        let vega = central(&|v| call(SPOT, r, d, v, EXPIRY), vol);
+        assert_relative_eq!(
+            black_scholes_vega(SPOT, STRIKE, args.0, args.1, args.2, EXPIRY),
+            vega,
+            max_relative = 1e-4
+        );
+
+        let call_theta = -central(&|t| call(SPOT, r, d, vol, t), EXPIRY);
+        let put_theta = -central(&|t| put(SPOT, r, d, vol, t), EXPIRY);
+        assert_relative_eq!(
+            black_scholes_call_theta(SPOT, STRIKE, args.0, args.1, args.2, EXPIRY),
+            call_theta,
+            max_relative = 1e-4
+        );
+        assert_relative_eq!(
+            black_scholes_put_theta(SPOT, STRIKE, args.0, args.1, args.2, EXPIRY),
+            put_theta,
+            max_relative = 1e-4
+        );
+
+        let call_rho = central(&|x| call(SPOT, x, d, vol, EXPIRY), r);
+        let put_rho = central(&|x| put(SPOT, x, d, vol, EXPIRY), r);
+        assert_relative_eq!(
+            black_scholes_call_rho(SPOT, STRIKE, args.0, args.1, args.2, EXPIRY),
+            call_rho,
+            max_relative = 1e-4
+        );
+        assert_relative_eq!(
+            black_scholes_put_rho(SPOT, STRIKE, args.0, args.1, args.2, EXPIRY),
+            put_rho,
+            max_relative = 1e-4
+        );
+    }
+
+    #[test]
+    fn test_zero_variance() {
+        let (r, d) = (0.05, 0.02);
+        let r_param = ParametersConstant::new(r);
+        let d_param = ParametersConstant::new(d);
+        let zero_vol = ParametersConstant::new(0.0);
+        let vol_param = ParametersConstant::new(0.2);
+        for (vol, expiry) in [(&zero_vol, EXPIRY), (&vol_param, 0.0)] {
+            let args = (&r_param, &d_param, vol);
+            let discount = (-r * expiry).exp();
+            let dividend_discount = (-d * expiry).exp();
+            // The call is in the money and the put out of the money at the forward of the spot 110.
+            let spot = 110.0;
+            let forward = spot * dividend_discount - STRIKE * discount;
+            assert_relative_eq!(
+                black_scholes_call(spot, STRIKE, args.0, args.1, args.2, expiry),
+                forward,
+                epsilon = 1e-12
+            );
+            assert_eq!(
+                black_scholes_put(spot, STRIKE, args.0, args.1, args.2, expiry),
+                0.0
+            );
+            assert_eq!(
+                black_scholes_call_delta(spot, STRIKE, args.0, args.1, args.2, expiry),
+                dividend_discount
+            );
+            assert_eq!(
+                black_scholes_put_delta(spot, STRIKE, args.0, args.1, args.2, expiry),
+                0.0
+            );
+            assert_eq!(
+                black_scholes_gamma(spot, STRIKE, args.0, args.1, args.2, expiry),
+                0.0
+            );
+            assert_eq!(
+                black_scholes_vega(spot, STRIKE, args.0, args.1, args.2, expiry),
+                0.0
+            );
+            assert_relative_eq!(
+                black_scholes_call_theta(spot, STRIKE, args.0, args.1, args.2, expiry),
+                -r * STRIKE * discount + d * spot * dividend_discount,
+                epsilon = 1e-12
+            );
+            assert_eq!(
+                black_scholes_put_theta(spot, STRIKE, args.0, args.1, args.2, expiry),
+                0.0
+            );
+            assert_relative_eq!(
+                black_scholes_call_rho(spot, STRIKE, args.0, args.1, args.2, expiry),
+                STRIKE * expiry * discount,
+                epsilon = 1e-12
+            );
+            assert_eq!(
+                black_scholes_put_rho(spot, STRIKE, args.0, args.1, args.2, expiry),
+                0.0
+            );
+            // The put is in the money at the spot 90.
+            let spot = 90.0;
+            assert_eq!(
+                black_scholes_call(spot, STRIKE, args.0, args.1, args.2, expiry),
+                0.0
+            );
+            assert_relative_eq!(
+                black_scholes_put(spot, STRIKE, args.0, args.1, args.2, expiry),
+                STRIKE * discount - spot * dividend_discount,
+                epsilon = 1e-12
+            );
+            assert_eq!(
+                black_scholes_put_delta(spot, STRIKE, args.0, args.1, args.2, expiry),
+                -dividend_discount
+            );
+        }
+
+        // At the money at the expiry, the gamma and the theta diverge.
+        let args = (&r_param, &d_param, &vol_param);
+        assert_eq!(
+            black_scholes_call(SPOT, STRIKE, args.0, args.1, args.2, 0.0),
+            0.0
+        );
+        assert_eq!(
+            black_scholes_call_delta(SPOT, STRIKE, args.0, args.1, args.2, 0.0),
+            0.5
+        );
+        assert_eq!(
+            black_scholes_gamma(SPOT, STRIKE, args.0, args.1, args.2, 0.0),
+            f64::INFINITY
+        );
+        assert_eq!(
+            black_scholes_call_theta(SPOT, STRIKE, args.0, args.1, args.2, 0.0),
+            f64::NEG_INFINITY
+        );
+    }
+}
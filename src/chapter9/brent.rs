@@ -0,0 +1,146 @@
+//! 逆二次補間と二分法を組み合わせた方法。
+//! 補間による更新が区間の中に収まり十分に区間を縮める場合にはそれを採用し、そうでない場合は二分法に切り替えるため、
+//! 二分法と同様に必ず収束し、かつ滑らかな関数に対しては超一次収束する。
+//! See \[NumericalRecipes\] Section 9.3.
+//!
+//! \[NumericalRecipes\] Press, W. H. et al. "Numerical Recipes in C: The Art of Scientific Computing." Cambridge University Press (1992).
+use crate::chapter9::solver::{check_bracket, evaluate, Solver, SolverSettings};
+use crate::chapter9::solver_error::SolverError;
+use crate::chapter9::solver_error::SolverError::NotConverged;
+
+/// Brent's method.
+#[derive(Debug, Clone, Copy)]
+pub struct Brent {
+    /// The lower end of the initial interval
+    low: f64,
+    /// The upper end of the initial interval
+    high: f64,
+    settings: SolverSettings,
+}
+
+impl Brent {
+    pub fn new(low: f64, high: f64, settings: SolverSettings) -> Self {
+        Brent {
+            low,
+            high,
+            settings,
+        }
+    }
+}
+
+impl Solver for Brent {
+    fn solve<T: Fn(f64) -> f64>(&self, target: f64, the_function: T) -> Result<f64, SolverError> {
+        let mut a = self.low;
+        let mut b = self.high;
+        let mut fa = evaluate(&the_function, target, a)?;
+        let mut fb = evaluate(&the_function, target, b)?;
+        if fa.abs() <= self.settings.function_tolerance {
+            return Ok(a);
+        }
+        if fb.abs() <= self.settings.function_tolerance {
+            return Ok(b);
+        }
+        check_bracket(target, a, b, fa, fb)?;
+        let mut c = b;
+        let mut fc = fb;
+        let mut d = b - a;
+        let mut e = d;
+        for _ in 0..self.settings.max_iterations {
+            if fb.signum() == fc.signum() {
+                // The root lies between a and b.
+                c = a;
+                fc = fa;
+                d = b - a;
+                e = d;
+            }
+            if fc.abs() < fb.abs() {
+                // b is the best estimate so far.
+                a = b;
+                b = c;
+                c = a;
+                fa = fb;
+                fb = fc;
+                fc = fa;
+            }
+            let tolerance = 2.0 * f64::EPSILON * b.abs() + 0.5 * self.settings.x_tolerance;
+            let xm = 0.5 * (c - b);
+            if xm.abs() <= tolerance || fb.abs() <= self.settings.function_tolerance {
+                return Ok(b);
+            }
+            if e.abs() >= tolerance && fa.abs() > fb.abs() {
+                let s = fb / fa;
+                let (mut p, mut q) = if a == c {
+                    // The secant method.
+                    (2.0 * xm * s, 1.0 - s)
+                } else {
+                    // The inverse quadratic interpolation.
+                    let q = fa / fc;
+                    let r = fb / fc;
+                    (
+                        s * (2.0 * xm * q * (q - r) - (b - a) * (r - 1.0)),
+                        (q - 1.0) * (r - 1.0) * (s - 1.0),
+                    )
+                };
+                if p > 0.0 {
+                    q = -q;
+                }
+                p = p.abs();
+                let min1 = 3.0 * xm * q - (tolerance * q).abs();
+                let min2 = (e * q).abs();
+                if 2.0 * p < min1.min(min2) {
+                    e = d;
+                    d = p / q;
+                } else {
+                    d = xm;
+                    e = d;
+                }
+            } else {
+                d = xm;
+                e = d;
+            }
+            a = b;
+            fa = fb;
+            if d.abs() > tolerance {
+                b += d;
+            } else {
+                b += tolerance.copysign(xm);
+            }
+            fb = evaluate(&the_function, target, b)?;
+        }
+        Err(NotConverged(self.settings.max_iterations))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chapter9::solver_error::SolverError::BracketError;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_brent() {
+        let solver = Brent::new(0.0, 2.0, SolverSettings::default());
+        let x = solver.solve(2.0, |x| x * x).unwrap();
+        assert_relative_eq!(x, 2.0_f64.sqrt(), epsilon = 1e-10);
+        let x = solver.solve(0.0, |x| x.cos() - x).unwrap();
+        assert_relative_eq!(x, 0.7390851332151607, epsilon = 1e-10);
+        // A function with a flat region, which slows down the interpolation.
+        let x = solver.solve(0.0, |x| (x - 1.0).powi(3)).unwrap();
+        assert_relative_eq!(x, 1.0, epsilon = 1e-3);
+    }
+
+    #[test]
+    fn test_brent_errors() {
+        let solver = Brent::new(0.0, 2.0, SolverSettings::default());
+        assert_eq!(
+            solver.solve(5.0, |x| x * x),
+            Err(BracketError {
+                target: 5.0,
+                low: 0.0,
+                high: 2.0
+            })
+        );
+        let solver = Brent::new(0.0, 2.0, SolverSettings::new(0.0, 0.0, 2));
+        assert_eq!(solver.solve(2.0, |x| x * x), Err(NotConverged(2)));
+    }
+}
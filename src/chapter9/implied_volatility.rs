@@ -0,0 +1,202 @@
+//! Black-Scholes価格を逆算してインプライドボラティリティを求める。
+//! ボラティリティ以外のパラメータを保持する構造体を定義し、価格とvegaをボラティリティの関数として与えることで、
+//! chapter9の任意のSolverをそのまま利用できるようにした。
+//! ペイオフの種類はPayoffFactoryと同じidで指定する。
+use crate::chapter4::parameters::{Parameters, ParametersConstant};
+use crate::chapter4::payoff3::{Payoff, PayoffCall, PayoffPut};
+use crate::chapter9::bisection::Bisection;
+use crate::chapter9::black_scholes_formulas::{
+    black_scholes_call, black_scholes_put, black_scholes_vega,
+};
+use crate::chapter9::implied_volatility_error::ImpliedVolatilityError;
+use crate::chapter9::implied_volatility_error::ImpliedVolatilityError::UnsupportedPayoff;
+use crate::chapter9::newton_raphson::NewtonRaphson;
+use crate::chapter9::solver::{Solver, SolverSettings, SolverWithDerivative};
+
+enum VanillaType {
+    Call,
+    Put,
+}
+
+/// A vanilla option whose Black-Scholes price is regarded as a function of the volatility.
+pub struct BlackScholesVanilla<'a, R: Parameters, D: Parameters> {
+    vanilla_type: VanillaType,
+    r: &'a R,
+    d: &'a D,
+    expiry: f64,
+    spot: f64,
+    strike: f64,
+}
+
+impl<'a, R: Parameters, D: Parameters> BlackScholesVanilla<'a, R, D> {
+    /// Constructor.
+    ///
+    /// # Arguments
+    ///
+    /// * `payoff_id` - The id of the payoff registered in `PayoffFactory`
+    /// * `r` - An interest rate
+    /// * `d` - A dividend
+    /// * `expiry` - An expiry
+    /// * `spot` - A spot value of a stock
+    /// * `strike` - A strike
+    pub fn new(
+        payoff_id: &str,
+        r: &'a R,
+        d: &'a D,
+        expiry: f64,
+        spot: f64,
+        strike: f64,
+    ) -> Result<Self, ImpliedVolatilityError> {
+        let vanilla_type = if payoff_id == PayoffCall::name() {
+            VanillaType::Call
+        } else if payoff_id == PayoffPut::name() {
+            VanillaType::Put
+        } else {
+            return Err(UnsupportedPayoff(payoff_id.to_string()));
+        };
+        Ok(BlackScholesVanilla {
+            vanilla_type,
+            r,
+            d,
+            expiry,
+            spot,
+            strike,
+        })
+    }
+
+    /// Returns the Black-Scholes price for a constant volatility `vol`.
+    pub fn price(&self, vol: f64) -> f64 {
+        let vol = ParametersConstant::new(vol);
+        match self.vanilla_type {
+            VanillaType::Call => {
+                black_scholes_call(self.spot, self.strike, self.r, self.d, &vol, self.expiry)
+            }
+            VanillaType::Put => {
+                black_scholes_put(self.spot, self.strike, self.r, self.d, &vol, self.expiry)
+            }
+        }
+    }
+
+    /// Returns the Black-Scholes vega for a constant volatility `vol`.
+    pub fn vega(&self, vol: f64) -> f64 {
+        let vol = ParametersConstant::new(vol);
+        black_scholes_vega(self.spot, self.strike, self.r, self.d, &vol, self.expiry)
+    }
+
+    /// Returns the implied volatility of `price` by `solver`.
+    ///
+    /// # Arguments
+    ///
+    /// * `price` - A price of the option
+    /// * `solver` - A root-finding algorithm
+    pub fn implied_volatility(
+        &self,
+        price: f64,
+        solver: &impl Solver,
+    ) -> Result<f64, ImpliedVolatilityError> {
+        Ok(solver.solve(price, |vol| self.price(vol))?)
+    }
+
+    /// Returns the implied volatility of `price` by `solver` with the vega as the derivative.
+    ///
+    /// # Arguments
+    ///
+    /// * `price` - A price of the option
+    /// * `solver` - A root-finding algorithm which may use the derivative
+    pub fn implied_volatility_with_vega(
+        &self,
+        price: f64,
+        solver: &impl SolverWithDerivative,
+    ) -> Result<f64, ImpliedVolatilityError> {
+        Ok(solver.solve_with_derivative(price, |vol| (self.price(vol), self.vega(vol)))?)
+    }
+
+    /// Returns the implied volatility of `price` by the bisection method.
+    ///
+    /// # Arguments
+    ///
+    /// * `price` - A price of the option
+    /// * `low` - The lower end of the volatility interval
+    /// * `high` - The upper end of the volatility interval
+    /// * `settings` - Tolerances and the maximum number of iterations
+    pub fn implied_volatility_by_bisection(
+        &self,
+        price: f64,
+        low: f64,
+        high: f64,
+        settings: SolverSettings,
+    ) -> Result<f64, ImpliedVolatilityError> {
+        self.implied_volatility(price, &Bisection::new(low, high, settings))
+    }
+
+    /// Returns the implied volatility of `price` by the Newton-Raphson method with the vega as the derivative.
+    ///
+    /// # Arguments
+    ///
+    /// * `price` - A price of the option
+    /// * `start` - An initial guess of the volatility
+    /// * `settings` - Tolerances and the maximum number of iterations
+    pub fn implied_volatility_by_newton_raphson(
+        &self,
+        price: f64,
+        start: f64,
+        settings: SolverSettings,
+    ) -> Result<f64, ImpliedVolatilityError> {
+        self.implied_volatility_with_vega(price, &NewtonRaphson::new(start, settings))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chapter9::brent::Brent;
+    use crate::chapter9::solver_error::SolverError;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_implied_volatility() {
+        let r = ParametersConstant::new(0.05);
+        let d = ParametersConstant::new(0.02);
+        for payoff_id in ["call", "put"] {
+            let option = BlackScholesVanilla::new(payoff_id, &r, &d, 1.5, 100.0, 110.0).unwrap();
+            let price = option.price(0.3);
+            let vol = option
+                .implied_volatility_by_bisection(price, 0.01, 2.0, SolverSettings::default())
+                .unwrap();
+            assert_relative_eq!(vol, 0.3, epsilon = 1e-8);
+            let vol = option
+                .implied_volatility_by_newton_raphson(price, 0.2, SolverSettings::default())
+                .unwrap();
+            assert_relative_eq!(vol, 0.3, epsilon = 1e-8);
+            let vol = option
+                .implied_volatility(price, &Brent::new(0.01, 2.0, SolverSettings::default()))
+                .unwrap();
+            assert_relative_eq!(vol, 0.3, epsilon = 1e-8);
+            let vol = option
+                .implied_volatility_with_vega(
+                    price,
+                    &Brent::new(0.01, 2.0, SolverSettings::default()),
+                )
+                .unwrap();
+            assert_relative_eq!(vol, 0.3, epsilon = 1e-8);
+        }
+    }
+
+    #[test]
+    fn test_implied_volatility_errors() {
+        let r = ParametersConstant::new(0.05);
+        let d = ParametersConstant::new(0.0);
+        assert!(matches!(
+            BlackScholesVanilla::new("digital", &r, &d, 1.0, 100.0, 100.0),
+            Err(UnsupportedPayoff(_))
+        ));
+        let option = BlackScholesVanilla::new("call", &r, &d, 1.0, 100.0, 100.0).unwrap();
+        // A call price can not exceed the spot.
+        assert!(matches!(
+            option.implied_volatility_by_bisection(150.0, 0.01, 2.0, SolverSettings::default()),
+            Err(ImpliedVolatilityError::SolverError(
+                SolverError::BracketError { .. }
+            ))
+        ));
+    }
+}
@@ -0,0 +1,11 @@
+use crate::chapter9::solver_error::SolverError;
+use std::fmt::Debug;
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum ImpliedVolatilityError {
+    #[error("The payoff {0} has no Black-Scholes formula")]
+    UnsupportedPayoff(String),
+    #[error(transparent)]
+    SolverError(#[from] SolverError),
+}
@@ -0,0 +1,110 @@
+//! 導関数が必要になるが、解の近くでは二次収束するため、bisectionよりも少ない反復回数で収束する。
+//! 関数と別の関数の導関数を組み合わせると黙って収束しなくなるので、関数の値と導関数の値を組で返す一つの関数を受け取る。
+//! そのため関数だけを受け取るSolverトレイトではなく、SolverWithDerivativeトレイトを実装する。
+use crate::chapter9::solver::{SolverSettings, SolverWithDerivative};
+use crate::chapter9::solver_error::SolverError;
+use crate::chapter9::solver_error::SolverError::{NotConverged, NotFinite, ZeroDerivative};
+
+/// The Newton-Raphson method, which moves along the tangent line at each iteration.
+#[derive(Debug, Clone, Copy)]
+pub struct NewtonRaphson {
+    /// An initial guess
+    start: f64,
+    settings: SolverSettings,
+}
+
+impl NewtonRaphson {
+    pub fn new(start: f64, settings: SolverSettings) -> Self {
+        NewtonRaphson { start, settings }
+    }
+}
+
+impl SolverWithDerivative for NewtonRaphson {
+    fn solve_with_derivative<T: Fn(f64) -> (f64, f64)>(
+        &self,
+        target: f64,
+        the_function_and_derivative: T,
+    ) -> Result<f64, SolverError> {
+        let mut x = self.start;
+        for _ in 0..self.settings.max_iterations {
+            let (value, d) = the_function_and_derivative(x);
+            let y = value - target;
+            if !y.is_finite() {
+                return Err(NotFinite(x));
+            }
+            if y.abs() <= self.settings.function_tolerance {
+                return Ok(x);
+            }
+            if d == 0.0 {
+                return Err(ZeroDerivative(x));
+            }
+            let step = y / d;
+            if !step.is_finite() {
+                return Err(NotFinite(x));
+            }
+            x -= step;
+            if step.abs() <= self.settings.x_tolerance {
+                return Ok(x);
+            }
+        }
+        Err(NotConverged(self.settings.max_iterations))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chapter9::bisection::Bisection;
+    use crate::chapter9::brent::Brent;
+    use crate::chapter9::ridders::Ridders;
+    use crate::chapter9::secant::Secant;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_newton_raphson() {
+        let solver = NewtonRaphson::new(1.0, SolverSettings::default());
+        let x = solver
+            .solve_with_derivative(2.0, |x| (x * x, 2.0 * x))
+            .unwrap();
+        assert_relative_eq!(x, 2.0_f64.sqrt(), epsilon = 1e-10);
+    }
+
+    #[test]
+    fn test_solver_with_derivative() {
+        // Newton-Raphson and the solvers ignoring the derivative are interchangeable through the trait.
+        fn cube_root_of_two(solver: &impl SolverWithDerivative) -> f64 {
+            solver
+                .solve_with_derivative(2.0, |x| (x * x * x, 3.0 * x * x))
+                .unwrap()
+        }
+        let settings = SolverSettings::default();
+        for x in [
+            cube_root_of_two(&NewtonRaphson::new(1.0, settings)),
+            cube_root_of_two(&Bisection::new(0.0, 2.0, settings)),
+            cube_root_of_two(&Secant::new(1.0, 2.0, settings)),
+            cube_root_of_two(&Brent::new(0.0, 2.0, settings)),
+            cube_root_of_two(&Ridders::new(0.0, 2.0, settings)),
+        ] {
+            assert_relative_eq!(x, 2.0_f64.cbrt(), epsilon = 1e-10);
+        }
+    }
+
+    #[test]
+    fn test_newton_raphson_errors() {
+        let solver = NewtonRaphson::new(0.0, SolverSettings::default());
+        assert_eq!(
+            solver.solve_with_derivative(2.0, |x| (x * x, 2.0 * x)),
+            Err(ZeroDerivative(0.0))
+        );
+        let solver = NewtonRaphson::new(3.0, SolverSettings::new(1e-12, 0.0, 20));
+        assert_eq!(
+            solver.solve_with_derivative(-1.0, |x| (x * x, 2.0 * x)),
+            Err(NotConverged(20))
+        );
+        let solver = NewtonRaphson::new(1.0, SolverSettings::default());
+        assert_eq!(
+            solver.solve_with_derivative(0.0, |x| (1.0 / (x - 1.0), 1.0)),
+            Err(NotFinite(1.0))
+        );
+    }
+}
@@ -0,0 +1,111 @@
+//! 区間の中点での関数値を用いて関数を指数関数で補正し、補正後の関数に対して偽位置法を適用する方法。
+//! 更新後の点は常に区間の中に収まるため必ず収束し、滑らかな関数に対しては二次収束する。
+//! See \[NumericalRecipes\] Section 9.2.
+//!
+//! \[NumericalRecipes\] Press, W. H. et al. "Numerical Recipes in C: The Art of Scientific Computing." Cambridge University Press (1992).
+use crate::chapter9::solver::{check_bracket, evaluate, Solver, SolverSettings};
+use crate::chapter9::solver_error::SolverError;
+use crate::chapter9::solver_error::SolverError::NotConverged;
+
+/// Ridders' method.
+#[derive(Debug, Clone, Copy)]
+pub struct Ridders {
+    /// The lower end of the initial interval
+    low: f64,
+    /// The upper end of the initial interval
+    high: f64,
+    settings: SolverSettings,
+}
+
+impl Ridders {
+    pub fn new(low: f64, high: f64, settings: SolverSettings) -> Self {
+        Ridders {
+            low,
+            high,
+            settings,
+        }
+    }
+}
+
+impl Solver for Ridders {
+    fn solve<T: Fn(f64) -> f64>(&self, target: f64, the_function: T) -> Result<f64, SolverError> {
+        let mut x_low = self.low;
+        let mut x_high = self.high;
+        let mut f_low = evaluate(&the_function, target, x_low)?;
+        let mut f_high = evaluate(&the_function, target, x_high)?;
+        if f_low.abs() <= self.settings.function_tolerance {
+            return Ok(x_low);
+        }
+        if f_high.abs() <= self.settings.function_tolerance {
+            return Ok(x_high);
+        }
+        check_bracket(target, x_low, x_high, f_low, f_high)?;
+        let mut answer = f64::NAN;
+        for _ in 0..self.settings.max_iterations {
+            let x_mid = 0.5 * (x_low + x_high);
+            let f_mid = evaluate(&the_function, target, x_mid)?;
+            if f_mid.abs() <= self.settings.function_tolerance {
+                return Ok(x_mid);
+            }
+            let s = (f_mid * f_mid - f_low * f_high).sqrt();
+            let sign = if f_low >= f_high { 1.0 } else { -1.0 };
+            let x_new = x_mid + (x_mid - x_low) * sign * f_mid / s;
+            if (x_new - answer).abs() <= self.settings.x_tolerance {
+                return Ok(x_new);
+            }
+            answer = x_new;
+            let f_new = evaluate(&the_function, target, answer)?;
+            if f_new.abs() <= self.settings.function_tolerance {
+                return Ok(answer);
+            }
+            // Keeps the root bracketed by the narrowest interval.
+            if f_mid.signum() != f_new.signum() {
+                x_low = x_mid;
+                f_low = f_mid;
+                x_high = answer;
+                f_high = f_new;
+            } else if f_low.signum() != f_new.signum() {
+                x_high = answer;
+                f_high = f_new;
+            } else {
+                x_low = answer;
+                f_low = f_new;
+            }
+            if (x_high - x_low).abs() <= self.settings.x_tolerance {
+                return Ok(answer);
+            }
+        }
+        Err(NotConverged(self.settings.max_iterations))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chapter9::solver_error::SolverError::BracketError;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_ridders() {
+        let solver = Ridders::new(0.0, 2.0, SolverSettings::default());
+        let x = solver.solve(2.0, |x| x * x).unwrap();
+        assert_relative_eq!(x, 2.0_f64.sqrt(), epsilon = 1e-10);
+        let x = solver.solve(0.0, |x| x.cos() - x).unwrap();
+        assert_relative_eq!(x, 0.7390851332151607, epsilon = 1e-10);
+    }
+
+    #[test]
+    fn test_ridders_errors() {
+        let solver = Ridders::new(0.0, 2.0, SolverSettings::default());
+        assert_eq!(
+            solver.solve(5.0, |x| x * x),
+            Err(BracketError {
+                target: 5.0,
+                low: 0.0,
+                high: 2.0
+            })
+        );
+        let solver = Ridders::new(0.0, 2.0, SolverSettings::new(0.0, 0.0, 1));
+        assert_eq!(solver.solve(2.0, |x| x * x), Err(NotConverged(1)));
+    }
+}
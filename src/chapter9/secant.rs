@@ -0,0 +1,72 @@
+//! Newton-Raphson法の導関数を直近の二点を通る直線の傾きで置き換えたもの。
+//! 導関数が不要で、解の近くでは超一次収束する。ただし区間で解を挟まないので発散することもある。
+use crate::chapter9::solver::{evaluate, Solver, SolverSettings};
+use crate::chapter9::solver_error::SolverError;
+use crate::chapter9::solver_error::SolverError::{NotConverged, ZeroDerivative};
+
+/// The secant method, which moves along the line through the last two points at each iteration.
+#[derive(Debug, Clone, Copy)]
+pub struct Secant {
+    /// The first initial guess
+    x0: f64,
+    /// The second initial guess
+    x1: f64,
+    settings: SolverSettings,
+}
+
+impl Secant {
+    pub fn new(x0: f64, x1: f64, settings: SolverSettings) -> Self {
+        Secant { x0, x1, settings }
+    }
+}
+
+impl Solver for Secant {
+    fn solve<T: Fn(f64) -> f64>(&self, target: f64, the_function: T) -> Result<f64, SolverError> {
+        let mut x0 = self.x0;
+        let mut x1 = self.x1;
+        let mut y0 = evaluate(&the_function, target, x0)?;
+        if y0.abs() <= self.settings.function_tolerance {
+            return Ok(x0);
+        }
+        for _ in 0..self.settings.max_iterations {
+            let y1 = evaluate(&the_function, target, x1)?;
+            if y1.abs() <= self.settings.function_tolerance {
+                return Ok(x1);
+            }
+            if y1 == y0 {
+                return Err(ZeroDerivative(x1));
+            }
+            let step = y1 * (x1 - x0) / (y1 - y0);
+            x0 = x1;
+            y0 = y1;
+            x1 -= step;
+            if step.abs() <= self.settings.x_tolerance {
+                return Ok(x1);
+            }
+        }
+        Err(NotConverged(self.settings.max_iterations))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_secant() {
+        let solver = Secant::new(1.0, 2.0, SolverSettings::default());
+        let x = solver.solve(2.0, |x| x * x).unwrap();
+        assert_relative_eq!(x, 2.0_f64.sqrt(), epsilon = 1e-10);
+        let x = solver.solve(0.0, |x| x.cos() - x).unwrap();
+        assert_relative_eq!(x, 0.7390851332151607, epsilon = 1e-10);
+    }
+
+    #[test]
+    fn test_secant_errors() {
+        let solver = Secant::new(-1.0, 1.0, SolverSettings::default());
+        assert_eq!(solver.solve(2.0, |x| x * x), Err(ZeroDerivative(1.0)));
+        let solver = Secant::new(1.0, 2.0, SolverSettings::new(1e-12, 0.0, 5));
+        assert_eq!(solver.solve(-1.0, |x| x * x), Err(NotConverged(5)));
+    }
+}
@@ -0,0 +1,103 @@
+//! 方程式f(x) = targetを解く求根アルゴリズムの共通インターフェース。
+//! 区間や初期値などのアルゴリズム固有の情報は各構造体に持たせ、
+//! 収束判定と反復回数の上限はSolverSettingsとして共通化した。
+//! これによって、インプライドボラティリティの計算などの呼び出し側はアルゴリズムを差し替えるだけで済む。
+//! 導関数を用いるNewton-Raphson法も含めて差し替えられるように、関数の値と導関数の値を組で受け取るSolverWithDerivativeトレイトを設け、
+//! Solverを実装するアルゴリズムは導関数を無視してこれを実装する。
+use crate::chapter9::solver_error::SolverError;
+use crate::chapter9::solver_error::SolverError::{BracketError, NotFinite};
+
+/// Tolerances and the maximum number of iterations shared by all solvers.
+#[derive(Debug, Clone, Copy)]
+pub struct SolverSettings {
+    /// The tolerance of the absolute difference between the function value and the target
+    pub function_tolerance: f64,
+    /// The tolerance of the length of the last step or the bracketing interval
+    pub x_tolerance: f64,
+    /// The maximum number of iterations
+    pub max_iterations: usize,
+}
+
+impl SolverSettings {
+    pub fn new(function_tolerance: f64, x_tolerance: f64, max_iterations: usize) -> Self {
+        SolverSettings {
+            function_tolerance,
+            x_tolerance,
+            max_iterations,
+        }
+    }
+}
+
+impl Default for SolverSettings {
+    fn default() -> Self {
+        SolverSettings::new(1e-10, 1e-12, 100)
+    }
+}
+
+/// A root-finding algorithm.
+pub trait Solver {
+    /// Finds `x` such that `the_function(x)` equals to `target`.
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - A target value of the function
+    /// * `the_function` - A continuous function
+    fn solve<T: Fn(f64) -> f64>(&self, target: f64, the_function: T) -> Result<f64, SolverError>;
+}
+
+/// A root-finding algorithm which may use the derivative of the function.
+///
+/// The derivative is passed together with the value, so that it can not belong to another function.
+/// Every `Solver` implements this by ignoring the derivative.
+pub trait SolverWithDerivative {
+    /// Finds `x` such that the value of `the_function_and_derivative(x)` equals to `target`.
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - A target value of the function
+    /// * `the_function_and_derivative` - A differentiable function returning the pair of its value and its derivative
+    fn solve_with_derivative<T: Fn(f64) -> (f64, f64)>(
+        &self,
+        target: f64,
+        the_function_and_derivative: T,
+    ) -> Result<f64, SolverError>;
+}
+
+impl<S: Solver> SolverWithDerivative for S {
+    fn solve_with_derivative<T: Fn(f64) -> (f64, f64)>(
+        &self,
+        target: f64,
+        the_function_and_derivative: T,
+    ) -> Result<f64, SolverError> {
+        self.solve(target, |x| the_function_and_derivative(x).0)
+    }
+}
+
+/// Evaluates `the_function(x) - target` and rejects non-finite values.
+pub(crate) fn evaluate<T: Fn(f64) -> f64>(
+    the_function: &T,
+    target: f64,
+    x: f64,
+) -> Result<f64, SolverError> {
+    let y = the_function(x) - target;
+    if y.is_finite() {
+        Ok(y)
+    } else {
+        Err(NotFinite(x))
+    }
+}
+
+/// Checks that the differences from the target at both ends of the interval have opposite signs.
+pub(crate) fn check_bracket(
+    target: f64,
+    low: f64,
+    high: f64,
+    y_low: f64,
+    y_high: f64,
+) -> Result<(), SolverError> {
+    if y_low.signum() == y_high.signum() {
+        Err(BracketError { target, low, high })
+    } else {
+        Ok(())
+    }
+}
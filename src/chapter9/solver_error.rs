@@ -0,0 +1,14 @@
+use std::fmt::Debug;
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum SolverError {
+    #[error("The target {target} is not bracketed by the interval [{low}, {high}]")]
+    BracketError { target: f64, low: f64, high: f64 },
+    #[error("The solver did not converge within {0} iterations")]
+    NotConverged(usize),
+    #[error("The derivative vanished at {0}")]
+    ZeroDerivative(f64),
+    #[error("The function returned a non-finite value at {0}")]
+    NotFinite(f64),
+}
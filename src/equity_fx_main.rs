@@ -3,7 +3,8 @@ use rust_design_pattern_derivative_pricing::chapter10::payoff_registration_error
 use rust_design_pattern_derivative_pricing::chapter4::parameters::ParametersConstant;
 use rust_design_pattern_derivative_pricing::chapter5::convergence_table::ConvergenceTable;
 use rust_design_pattern_derivative_pricing::chapter5::mc_statistics::StatisticsMC;
-use rust_design_pattern_derivative_pricing::chapter5::mc_statistics::StatisticsMean;
+use rust_design_pattern_derivative_pricing::chapter5::mc_statistics::StatisticsVariance;
+use rust_design_pattern_derivative_pricing::chapter5::statistics_pair_average::StatisticsPairAverage;
 use rust_design_pattern_derivative_pricing::chapter6::anti_thetic::AntiThetic;
 use rust_design_pattern_derivative_pricing::chapter6::park_miller::RandomParkMiller;
 use rust_design_pattern_derivative_pricing::chapter7::exotic_bs_engine::ExoticBSEngine;
@@ -23,7 +24,7 @@ pub fn price(
     d: f64,
     number_of_dates: usize,
     number_of_paths: usize,
-) -> Result<f64, FactoryError> {
+) -> Result<(f64, f64), FactoryError> {
     let payoff_factory = PayoffFactory::instance()?;
     let the_payoff = payoff_factory.create_payoff(option_type, strike)?;
     let times: Vec<f64> = (0..number_of_dates)
@@ -32,14 +33,15 @@ pub fn price(
     let vol_param: ParametersConstant = vol.into();
     let r_param: ParametersConstant = r.into();
     let d_param: ParametersConstant = d.into();
-    let gatherer = StatisticsMean::default();
+    // The antithetic paths are negatively correlated, so the standard error is computed from the averages of pairs.
+    let gatherer = StatisticsPairAverage::new(StatisticsVariance::default());
     let mut gatherer_two = ConvergenceTable::new(gatherer);
     let generator = RandomParkMiller::new(number_of_dates, 1);
     let gen_two = AntiThetic::new(generator);
     let mut the_engine = ExoticBSEngine::new(&times, &r_param, d_param, vol_param, gen_two, spot);
     let the_option = PathDependentAsian::new(times, expiry, the_payoff.as_ref());
     let exotic_engine_data = ExoticEngineData::new(&the_option, &r_param);
-    the_engine.do_simulation(&exotic_engine_data, &mut gatherer_two, number_of_paths);
+    the_engine.do_simulation_reproducible(&exotic_engine_data, &mut gatherer_two, number_of_paths);
     let results = gatherer_two.get_results_so_far();
     println!("\nFor the Asian call price the results are \n");
     for result in &results {
@@ -48,15 +50,13 @@ pub fn price(
         }
         println!();
     }
-    Ok(results.last().unwrap()[0])
+    let last = results.last().unwrap();
+    Ok((last[0], last[2]))
 }
 
 #[test]
 pub fn test_main() {
-    rayon::ThreadPoolBuilder::new()
-        .num_threads(1)
-        .build_global()
-        .unwrap();
+    use rust_design_pattern_derivative_pricing::chapter5::mc_statistics::StatisticsMean;
     let option_type = "call";
     let expiry = 30.0;
     let strike = 100.0;
@@ -82,21 +82,21 @@ pub fn test_main() {
     let mut the_engine = ExoticBSEngine::new(&times, &r_param, d_param, vol_param, gen_two, spot);
     let the_option = PathDependentAsian::new(times, expiry, the_payoff.as_ref());
     let exotic_engine_data = ExoticEngineData::new(&the_option, &r_param);
-    the_engine.do_simulation(&exotic_engine_data, &mut gatherer_two, number_of_paths);
+    the_engine.do_simulation_reproducible(&exotic_engine_data, &mut gatherer_two, number_of_paths);
     let results = gatherer_two.get_results_so_far();
     assert_eq!(
         results,
         [
-            [12.297472829420853, 2.0],
-            [12.289559897724873, 4.0],
-            [12.319043908132263, 8.0],
-            [12.320459101851085, 16.0],
-            [12.330259037906742, 32.0],
-            [12.326630351283598, 64.0],
-            [12.308093361180433, 128.0],
-            [12.305294485327694, 256.0],
-            [12.305075198316125, 512.0],
-            [12.304916649159457, 1000.0]
+            [12.297474043646117, 2.0],
+            [12.289560994915373, 4.0],
+            [12.319044893909316, 8.0],
+            [12.320459506448733, 16.0],
+            [12.33025921154599, 32.0],
+            [12.326630448608165, 64.0],
+            [12.328747605722779, 128.0],
+            [12.322480482319502, 256.0],
+            [12.322134637929972, 512.0],
+            [12.3218995544176, 1000.0]
         ]
     )
 }
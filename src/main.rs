@@ -50,8 +50,11 @@ async fn calculate_price(form: axum::extract::Form<PriceParameters>) -> Result<S
         form.number_of_dates,
         form.number_of_paths,
     );
-    if let Ok(result) = result {
-        Ok(format!("The price is {}\n", result))
+    if let Ok((price, standard_error)) = result {
+        Ok(format!(
+            "The price is {} (standard error {})\n",
+            price, standard_error
+        ))
     } else {
         Err(format!("{}", result.err().unwrap()))
     }